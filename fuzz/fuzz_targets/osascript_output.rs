@@ -0,0 +1,43 @@
+//! Feeds arbitrary `osascript` output through the parsers of the presence
+//! loop: document names with separators or unicode, truncated output and
+//! bytes that are not UTF-8.
+//!
+//! Run with `cargo +nightly fuzz run osascript_output` from the repository.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code)]
+#[path = "../../src/utils/osascript_parser.rs"]
+mod osascript_parser;
+
+use osascript_parser::{
+    parse_front_window, parse_project, split_fields, strip_output, FocusedFile,
+    FIELD_SEPARATOR,
+};
+
+fuzz_target!(|data: &[u8]| {
+    // Decoded the way `run_osascript` decodes the standard output
+    let output = String::from_utf8_lossy(data);
+    let output = strip_output(&output);
+
+    let fields: [&str; 4] = split_fields(output);
+    // Missing fields are empty and extra separators stay in the last one
+    assert!(fields.join(&FIELD_SEPARATOR.to_string()).starts_with(output));
+    assert!(!fields[..3].iter().any(|field| field.contains(FIELD_SEPARATOR)));
+
+    let window = parse_front_window(output);
+    if let FocusedFile::Document(name) = &window.file {
+        assert!(!name.is_empty());
+        assert!(fields[1].ends_with(name.as_str()));
+    } else {
+        assert!(!window.unsaved);
+    }
+
+    let [name, path] = split_fields(output);
+    if let Some(project) = parse_project(name, path) {
+        assert!(!project.name.is_empty() || name.starts_with('.'));
+        assert!(name.starts_with(&project.name));
+    }
+});
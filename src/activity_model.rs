@@ -0,0 +1,148 @@
+use std::{
+    borrow::Cow,
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use discord_rich_presence::{
+    activity::{Activity, Assets, Button, Party, Timestamps},
+    DiscordIpc, DiscordIpcClient,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::Result;
+
+/// (text, image) asset keys, borrowed for the assets of the Discord
+/// application and owned for hosted images and per-project overrides
+pub type AssetKeys = (Cow<'static, str>, Cow<'static, str>);
+
+/// Verb Discord shows before the application name
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityType {
+    /// "Playing Xcode"
+    #[default]
+    Playing,
+    /// "Listening to Xcode"
+    Listening,
+    /// "Watching Xcode"
+    Watching,
+    /// "Competing in Xcode"
+    Competing,
+}
+
+impl ActivityType {
+    /// Returns the value of the `type` field of an activity
+    fn code(self) -> u8 {
+        match self {
+            ActivityType::Playing => 0,
+            ActivityType::Listening => 2,
+            ActivityType::Watching => 3,
+            ActivityType::Competing => 5,
+        }
+    }
+}
+
+/// Everything shown in Discord Rich Presence, kept to tell whether an
+/// activity differs from the one Discord already shows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityModel {
+    pub details: Option<String>,
+    pub state: Option<String>,
+    pub large_image: AssetKeys,
+    pub small_image: Option<AssetKeys>,
+    /// Label and URL of each button
+    pub buttons: Vec<(String, String)>,
+    /// Start of the elapsed time, in seconds since the UNIX epoch
+    pub started_at: i64,
+    pub activity_type: ActivityType,
+    /// ID and size of the Discord party, shown as "(2 of 2)"
+    pub party: Option<(String, u32)>,
+}
+
+impl ActivityModel {
+    /// Sets the activity of `client`. The payload is built here rather than
+    /// by the client library, which does not support the activity type.
+    pub fn send(&self, client: &mut DiscordIpcClient) -> Result<()> {
+        static NONCE: AtomicU64 = AtomicU64::new(0);
+        client.send(
+            json!({
+                "cmd": "SET_ACTIVITY",
+                "args": { "pid": process::id(), "activity": self.payload()? },
+                "nonce": NONCE.fetch_add(1, Ordering::Relaxed).to_string(),
+            }),
+            1,
+        )?;
+        Ok(())
+    }
+
+    /// Builds the activity sent to Discord
+    fn payload(&self) -> Result<Value> {
+        let mut payload = serde_json::to_value(self.activity())?;
+        payload["type"] = self.activity_type.code().into();
+        Ok(payload)
+    }
+
+    fn activity(&self) -> Activity<'_> {
+        let (large_text, large_image) = &self.large_image;
+        let mut assets = Assets::new().large_image(large_image);
+        // An empty text is rejected by Discord, leaving it out shows none
+        if !large_text.is_empty() {
+            assets = assets.large_text(large_text);
+        }
+        if let Some((small_text, small_image)) = &self.small_image {
+            assets = assets.small_text(small_text).small_image(small_image);
+        }
+        let mut activity = Activity::new()
+            .timestamps(Timestamps::new().start(self.started_at * 1000))
+            .assets(assets);
+        if let Some(details) = &self.details {
+            activity = activity.details(details);
+        }
+        if let Some(state) = &self.state {
+            activity = activity.state(state);
+        }
+        if let Some((id, size)) = &self.party {
+            let size = i32::try_from(*size).unwrap_or(i32::MAX);
+            activity = activity.party(Party::new().id(id).size([size, size]));
+        }
+        if !self.buttons.is_empty() {
+            activity = activity.buttons(
+                self.buttons
+                    .iter()
+                    .map(|(label, url)| Button::new(label, url))
+                    .collect(),
+            );
+        }
+        activity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sets_activity_type() {
+        let model = ActivityModel {
+            details: Some("Working on main.swift".to_string()),
+            state: None,
+            large_image: (Cow::Borrowed("Swift"), Cow::Borrowed("swift")),
+            small_image: None,
+            buttons: Vec::new(),
+            started_at: 1,
+            activity_type: ActivityType::Competing,
+            party: None,
+        };
+        assert_eq!(
+            model.payload().unwrap(),
+            json!({
+                "details": "Working on main.swift",
+                "assets": { "large_text": "Swift", "large_image": "swift" },
+                "timestamps": { "start": 1000 },
+                "type": 5,
+            })
+        );
+    }
+}
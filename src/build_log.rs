@@ -0,0 +1,330 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    time::SystemTime,
+};
+
+use serde::Serialize;
+
+use crate::{
+    derived_data::project_dirs,
+    locale::{Locale, Text},
+    watchdog::WatchedCommand,
+    Error, Result,
+};
+
+/// Outcome of the most recent build of a project
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum BuildResult {
+    Succeeded { warnings: u32 },
+    Failed { errors: u32, warnings: u32 },
+    Cancelled,
+}
+
+impl BuildResult {
+    /// Returns the number of errors and warnings of the build, `None` when it
+    /// was cancelled
+    pub fn issues(&self) -> Option<(u32, u32)> {
+        match self {
+            BuildResult::Succeeded { warnings } => Some((0, *warnings)),
+            BuildResult::Failed { errors, warnings } => Some((*errors, *warnings)),
+            BuildResult::Cancelled => None,
+        }
+    }
+
+    /// Returns the build result in English, as sent to integrations
+    pub fn summary(&self) -> String {
+        self.localized_summary(&Locale::default())
+    }
+
+    /// Returns the text shown in Discord Rich Presence for the build result
+    pub fn localized_summary(&self, locale: &Locale) -> String {
+        match self {
+            BuildResult::Succeeded { .. } => locale.text(Text::BuildSucceeded).to_string(),
+            BuildResult::Failed { errors: 0, .. } => locale.text(Text::BuildFailed).to_string(),
+            BuildResult::Failed { errors: 1, .. } => {
+                locale.text(Text::BuildFailedError).to_string()
+            }
+            BuildResult::Failed { errors, .. } => {
+                locale.format(Text::BuildFailedErrors, &[("errors", &errors.to_string())])
+            }
+            BuildResult::Cancelled => locale.text(Text::BuildCancelled).to_string(),
+        }
+    }
+}
+
+/// A token of the SLF0 serialization format used by `.xcactivitylog` files
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Int(u64),
+    Double,
+    Null,
+    String(&'a [u8]),
+    ClassName(&'a [u8]),
+    ClassInstance(u64),
+    Array(u64),
+}
+
+/// Splits a decompressed SLF0 log into tokens
+fn tokenize(data: &[u8]) -> Result<Vec<Token<'_>>> {
+    let invalid = |reason: &str| Error::BuildLog(reason.to_string());
+    let mut data = data
+        .strip_prefix(b"SLF0")
+        .ok_or_else(|| invalid("missing SLF0 header"))?;
+    let mut tokens = Vec::new();
+
+    while !data.is_empty() {
+        let prefix_len = data
+            .iter()
+            .position(|byte| !byte.is_ascii_hexdigit())
+            .ok_or_else(|| invalid("unterminated token"))?;
+        let (prefix, rest) = data.split_at(prefix_len);
+        let prefix = std::str::from_utf8(prefix).map_err(|_| invalid("invalid prefix"))?;
+        let number = || prefix.parse::<u64>().map_err(|_| invalid("invalid number"));
+        let (delimiter, rest) = rest
+            .split_first()
+            .ok_or_else(|| invalid("missing delimiter"))?;
+        data = rest;
+
+        let token = match delimiter {
+            b'#' => Token::Int(number()?),
+            b'^' => Token::Double,
+            b'-' => Token::Null,
+            b'"' | b'%' => {
+                let len = number()? as usize;
+                if len > data.len() {
+                    return Err(invalid("string exceeds log length"));
+                }
+                let (value, rest) = data.split_at(len);
+                data = rest;
+                if *delimiter == b'"' {
+                    Token::String(value)
+                } else {
+                    Token::ClassName(value)
+                }
+            }
+            b'@' => Token::ClassInstance(number()?),
+            b'(' => Token::Array(number()?),
+            _ => return Err(invalid("unknown token type")),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Parses the result of a build from a decompressed `.xcactivitylog`.
+///
+/// The log is a flat stream of serialized objects without field names, so
+/// the result is read from the localized result string of the build section
+/// and the error and warning counts from the severity of each diagnostic
+/// message that has no sub-messages.
+pub fn parse_build_log(data: &[u8]) -> Result<BuildResult> {
+    let tokens = tokenize(data)?;
+
+    let mut class_names: Vec<&[u8]> = Vec::new();
+    let mut result = None;
+    let mut errors = 0;
+    let mut warnings = 0;
+
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::ClassName(name) => class_names.push(name),
+            Token::ClassInstance(index) => {
+                let is_message = class_names
+                    .get((*index as usize).wrapping_sub(1))
+                    .is_some_and(|name| name.ends_with(b"ActivityLogMessage"));
+                if !is_message {
+                    continue;
+                }
+                // title, short title, time emitted, range end, range start, sub-messages
+                let severity = match tokens.get(i + 1..i + 8) {
+                    Some(
+                        [Token::String(_) | Token::Null, Token::String(_) | Token::Null, Token::Double, Token::Int(_), Token::Int(_), Token::Array(0), Token::Int(severity)],
+                    ) => *severity,
+                    _ => continue,
+                };
+                match severity {
+                    2 => errors += 1,
+                    1 => warnings += 1,
+                    _ => {}
+                }
+            }
+            Token::String(value) if result.is_none() => {
+                result = match *value {
+                    b"Build succeeded" => Some(true),
+                    b"Build failed" => Some(false),
+                    b"Build cancelled" => return Ok(BuildResult::Cancelled),
+                    _ => None,
+                };
+            }
+            _ => {}
+        }
+    }
+
+    match result {
+        Some(true) if errors == 0 => Ok(BuildResult::Succeeded { warnings }),
+        Some(_) => Ok(BuildResult::Failed { errors, warnings }),
+        None if errors > 0 => Ok(BuildResult::Failed { errors, warnings }),
+        None => Err(Error::BuildLog(String::from("build result not found"))),
+    }
+}
+
+/// Decompresses and parses the `.xcactivitylog` at `path`
+pub fn read_build_log(path: &Path) -> Result<BuildResult> {
+    let output = Command::new("gunzip")
+        .arg("-c")
+        .arg(path)
+        .watched_output()
+        .map_err(|err| Error::BuildLog(err.to_string()))?;
+    if !output.status.success() {
+        return Err(Error::BuildLog(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    parse_build_log(&output.stdout)
+}
+
+/// Watches the DerivedData build logs of a project for new builds
+pub struct BuildLogWatcher {
+    /// Modification time of the newest build log already reported
+    last_seen: SystemTime,
+}
+
+impl BuildLogWatcher {
+    /// Creates a watcher that only reports builds finishing from now on
+    pub fn new() -> Self {
+        Self {
+            last_seen: SystemTime::now(),
+        }
+    }
+
+    /// Returns the result of a build of `project` finished since the last
+    /// call, if there is one
+    pub fn poll(&mut self, project: &str) -> Option<BuildResult> {
+        let (path, modified) = newest_build_log(project)?;
+        if modified <= self.last_seen {
+            return None;
+        }
+        self.last_seen = modified;
+        match read_build_log(&path) {
+            Ok(result) => {
+                log::debug!("Build finished: {:?}", result);
+                Some(result)
+            }
+            Err(err) => {
+                log::debug!("Failed to read build log {}: {}", path.display(), err);
+                None
+            }
+        }
+    }
+}
+
+/// Finds the newest `.xcactivitylog` in the DerivedData folders of `project`
+fn newest_build_log(project: &str) -> Option<(PathBuf, SystemTime)> {
+    project_dirs(project)
+        .into_iter()
+        .filter_map(|dir| fs::read_dir(dir.join("Logs/Build")).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .is_some_and(|ext| ext == "xcactivitylog")
+        })
+        .filter_map(|entry| Some((entry.path(), entry.metadata().ok()?.modified().ok()?)))
+        .max_by_key(|(_, modified)| *modified)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serializes a string token
+    fn s(value: &str) -> String {
+        format!("{}\"{value}", value.len())
+    }
+
+    /// Serializes a diagnostic message with the given severity
+    fn message(instance: u32, severity: u32) -> String {
+        format!(
+            "{instance}@{}{}0000000000000000^0#0#0({severity}#",
+            s("error: oops"),
+            s("oops")
+        )
+    }
+
+    #[test]
+    fn tokenizes_all_token_types() {
+        let tokens = tokenize(b"SLF012#3\"abc-3%Foo1@2(0000000000000000^").unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Int(12),
+                Token::String(b"abc"),
+                Token::Null,
+                Token::ClassName(b"Foo"),
+                Token::ClassInstance(1),
+                Token::Array(2),
+                Token::Double,
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_logs() {
+        assert!(tokenize(b"NOPE").is_err());
+        assert!(tokenize(b"SLF099\"short").is_err());
+        assert!(tokenize(b"SLF012").is_err());
+        assert!(tokenize(b"SLF012!").is_err());
+    }
+
+    #[test]
+    fn parses_succeeded_build() {
+        let log = format!(
+            "SLF010#21%IDEActivityLogSection1@0#{}{}",
+            s("Build MyApp"),
+            s("Build succeeded")
+        );
+        assert_eq!(
+            parse_build_log(log.as_bytes()).unwrap(),
+            BuildResult::Succeeded { warnings: 0 }
+        );
+    }
+
+    #[test]
+    fn counts_errors_and_warnings() {
+        let log = format!(
+            "SLF010#21%IDEActivityLogSection1@0#{}31%IDEDiagnosticActivityLogMessage{}{}{}{}",
+            s("Build MyApp"),
+            message(2, 2),
+            message(2, 2),
+            message(2, 1),
+            s("Build failed"),
+        );
+        assert_eq!(
+            parse_build_log(log.as_bytes()).unwrap(),
+            BuildResult::Failed {
+                errors: 2,
+                warnings: 1
+            }
+        );
+    }
+
+    #[test]
+    fn summarizes_results() {
+        assert_eq!(
+            BuildResult::Failed {
+                errors: 3,
+                warnings: 0
+            }
+            .summary(),
+            "Build failed ❌ (3 errors)"
+        );
+        assert_eq!(
+            BuildResult::Succeeded { warnings: 2 }.summary(),
+            "Build succeeded ✅"
+        );
+    }
+}
@@ -0,0 +1,334 @@
+use std::path::PathBuf;
+
+use clap::{value_parser, Arg, ArgAction, ArgGroup, ArgMatches, Command as ClapCommand};
+
+use crate::{completions::SHELLS, utils::keychain::Secret};
+
+/// Argument ID for hiding the file name in Discord Rich Presence
+pub const HIDE_FILE_ARG_ID: &str = "hide_file";
+/// Argument ID for hiding the project name in Discord Rich Presence
+pub const HIDE_PROJECT_ARG_ID: &str = "hide_project";
+/// Argument ID for printing presence changes as JSON lines instead of logs
+pub const EVENTS_ARG_ID: &str = "events";
+/// Argument ID for the `[profile.<name>]` table overriding the configuration
+pub const PROFILE_ARG_ID: &str = "profile";
+/// Argument ID for logging to the unified logging system instead of stderr
+pub const OS_LOG_ARG_ID: &str = "os_log";
+/// Argument ID for detaching into the background
+pub const DAEMON_ARG_ID: &str = "daemon";
+/// Argument ID for staying attached to the terminal, the default
+pub const FOREGROUND_ARG_ID: &str = "foreground";
+/// Subcommand for showing a pair programming session
+pub const PAIR_COMMAND: &str = "pair";
+/// Subcommand of `pair` starting a pair programming session
+pub const PAIR_START_COMMAND: &str = "start";
+/// Subcommand of `pair` stopping the pair programming session
+pub const PAIR_STOP_COMMAND: &str = "stop";
+/// Argument ID for the scenario file replacing Xcode with a simulation
+pub const SIMULATE_ARG_ID: &str = "simulate";
+/// Subcommand for managing secrets in the Keychain
+pub const SECRET_COMMAND: &str = "secret";
+/// Subcommand of `secret` storing a secret
+pub const SECRET_SET_COMMAND: &str = "set";
+/// Subcommand of `secret` removing a secret
+pub const SECRET_DELETE_COMMAND: &str = "delete";
+/// Argument ID for the name of a secret
+pub const SECRET_NAME_ARG_ID: &str = "name";
+/// Subcommand for updating the executable to the latest release
+pub const UPDATE_COMMAND: &str = "update";
+/// Subcommand for checking the art assets of the Discord application
+pub const VERIFY_ASSETS_COMMAND: &str = "verify-assets";
+/// Subcommand for printing a shell completion script
+pub const COMPLETIONS_COMMAND: &str = "completions";
+/// Argument ID for the shell of the completion script
+pub const SHELL_ARG_ID: &str = "shell";
+/// Subcommand for inspecting the configuration file
+pub const CONFIG_COMMAND: &str = "config";
+/// Subcommand of `config` checking the configuration file
+pub const CONFIG_VALIDATE_COMMAND: &str = "validate";
+/// Subcommand of `config` printing the JSON Schema of the configuration file
+pub const CONFIG_SCHEMA_COMMAND: &str = "schema";
+/// Subcommand for stopping the running instance
+pub const STOP_COMMAND: &str = "stop";
+/// Subcommand for temporarily suppressing presence
+pub const PAUSE_COMMAND: &str = "pause";
+/// Subcommand for resuming presence after a pause
+pub const RESUME_COMMAND: &str = "resume";
+/// Argument ID for the duration of a pause
+pub const FOR_ARG_ID: &str = "for";
+/// Subcommand for manually overriding the presence
+pub const SET_COMMAND: &str = "set";
+/// Argument ID for the details line of a manual presence
+pub const DETAILS_ARG_ID: &str = "details";
+/// Argument ID for the state line of a manual presence
+pub const STATE_ARG_ID: &str = "state";
+/// Argument ID for the duration of a manual presence
+pub const DURATION_ARG_ID: &str = "duration";
+/// Subcommand for printing the time spent in Xcode
+pub const REPORT_COMMAND: &str = "report";
+/// Argument ID for reporting the last 7 days instead of today
+pub const WEEK_ARG_ID: &str = "week";
+/// Argument ID for printing JSON
+pub const JSON_ARG_ID: &str = "json";
+/// Argument ID for printing CSV
+pub const CSV_ARG_ID: &str = "csv";
+/// Subcommand for dumping the recorded sessions
+pub const EXPORT_COMMAND: &str = "export";
+/// Subcommand for printing what the running instance shows and the time
+/// spent today
+pub const STATUS_COMMAND: &str = "status";
+/// Subcommand for printing the recent events of the running instance
+pub const LOGS_COMMAND: &str = "logs";
+/// Argument ID for the number of events to print
+pub const TAIL_ARG_ID: &str = "tail";
+/// Subcommand for controlling the pomodoro timer
+pub const POMODORO_COMMAND: &str = "pomodoro";
+/// Subcommand of `pomodoro` starting a pomodoro
+pub const POMODORO_START_COMMAND: &str = "start";
+/// Subcommand of `pomodoro` stopping the running pomodoro
+pub const POMODORO_STOP_COMMAND: &str = "stop";
+
+/// Builds the command-line interface
+pub fn build_cli() -> ClapCommand {
+    ClapCommand::new("Xcode Discord RPC")
+        .version(clap::crate_version!())
+        .author(clap::crate_authors!())
+        .about("Displays Xcode status on Discord Rich Presence")
+        .arg(
+            Arg::new(HIDE_FILE_ARG_ID)
+                .short('f')
+                .long("hide-file")
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .help("Hide current file in Discord Rich Presence")
+                .default_value("false"),
+        )
+        .arg(
+            Arg::new(HIDE_PROJECT_ARG_ID)
+                .short('p')
+                .long("hide-project")
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .help("Hide current project in Discord Rich Presence")
+                .default_value("false"),
+        )
+        .arg(
+            Arg::new(EVENTS_ARG_ID)
+                .long("events")
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .help("Print presence changes as newline-delimited JSON instead of logs")
+                .default_value("false"),
+        )
+        .arg(
+            Arg::new(PROFILE_ARG_ID)
+                .long("profile")
+                .value_name("NAME")
+                .help("Apply the overrides of [profile.NAME] in the configuration file"),
+        )
+        .arg(
+            Arg::new(OS_LOG_ARG_ID)
+                .long("os-log")
+                .action(ArgAction::SetTrue)
+                .help("Log to the unified logging system, shown in Console.app"),
+        )
+        .arg(
+            Arg::new(DAEMON_ARG_ID)
+                .short('d')
+                .long("daemon")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all([FOREGROUND_ARG_ID, EVENTS_ARG_ID])
+                .help("Run in the background, logging to a file"),
+        )
+        .arg(
+            Arg::new(FOREGROUND_ARG_ID)
+                .long("foreground")
+                .action(ArgAction::SetTrue)
+                .help("Stay attached to the terminal, the default"),
+        )
+        .arg(
+            Arg::new(SIMULATE_ARG_ID)
+                .long("simulate")
+                .value_name("SCENARIO")
+                .value_parser(value_parser!(PathBuf))
+                .help("Replace Xcode with the scripted states of a scenario file"),
+        )
+        .subcommand(ClapCommand::new(STOP_COMMAND).about("Stop the running instance"))
+        .subcommand(
+            ClapCommand::new(PAUSE_COMMAND)
+                .about("Clear and suppress presence until resumed")
+                .arg(
+                    Arg::new(FOR_ARG_ID)
+                        .long("for")
+                        .value_name("DURATION")
+                        .help("Resume automatically after a duration such as 30m or 1h"),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new(SET_COMMAND)
+                .about("Temporarily replace the detected presence")
+                .arg(
+                    Arg::new(DETAILS_ARG_ID)
+                        .long("details")
+                        .value_name("TEXT")
+                        .help("First line of the presence"),
+                )
+                .arg(
+                    Arg::new(STATE_ARG_ID)
+                        .long("state")
+                        .value_name("TEXT")
+                        .help("Second line of the presence"),
+                )
+                .arg(
+                    Arg::new(DURATION_ARG_ID)
+                        .long("duration")
+                        .value_name("DURATION")
+                        .help("Return to automatic presence after a duration such as 30m"),
+                )
+                .group(
+                    ArgGroup::new("presence")
+                        .args([DETAILS_ARG_ID, STATE_ARG_ID])
+                        .required(true)
+                        .multiple(true),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new(RESUME_COMMAND)
+                .about("Resume automatic presence after a pause or a manual presence"),
+        )
+        .subcommand(
+            ClapCommand::new(STATUS_COMMAND).about(
+                "Print what the running instance shows and the progress toward the daily goal",
+            ),
+        )
+        .subcommand(
+            ClapCommand::new(REPORT_COMMAND)
+                .about("Print the time spent per project and language")
+                .arg(
+                    Arg::new(WEEK_ARG_ID)
+                        .long("week")
+                        .action(ArgAction::SetTrue)
+                        .help("Report the last 7 days instead of today"),
+                )
+                .arg(
+                    Arg::new(JSON_ARG_ID)
+                        .long("json")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with(CSV_ARG_ID)
+                        .help("Print the report as JSON"),
+                )
+                .arg(
+                    Arg::new(CSV_ARG_ID)
+                        .long("csv")
+                        .action(ArgAction::SetTrue)
+                        .help("Print the report as CSV"),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new(EXPORT_COMMAND)
+                .about("Print all recorded sessions as CSV or JSON")
+                .arg(
+                    Arg::new(JSON_ARG_ID)
+                        .long("json")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with(CSV_ARG_ID)
+                        .help("Print the sessions as JSON"),
+                )
+                .arg(
+                    Arg::new(CSV_ARG_ID)
+                        .long("csv")
+                        .action(ArgAction::SetTrue)
+                        .help("Print the sessions as CSV, the default"),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new(LOGS_COMMAND)
+                .about("Print the recent presence and state changes of the running instance")
+                .arg(
+                    Arg::new(TAIL_ARG_ID)
+                        .long("tail")
+                        .short('n')
+                        .value_name("COUNT")
+                        .value_parser(value_parser!(usize))
+                        .help("Print only the last COUNT events"),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new(POMODORO_COMMAND)
+                .about("Alternate focus intervals and breaks in the presence")
+                .subcommand_required(true)
+                .subcommand(ClapCommand::new(POMODORO_START_COMMAND).about("Start a pomodoro"))
+                .subcommand(
+                    ClapCommand::new(POMODORO_STOP_COMMAND).about("Stop the running pomodoro"),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new(PAIR_COMMAND)
+                .about("Show the session as pair programming with a Discord party")
+                .subcommand_required(true)
+                .subcommand(
+                    ClapCommand::new(PAIR_START_COMMAND).about("Start a pair programming session"),
+                )
+                .subcommand(
+                    ClapCommand::new(PAIR_STOP_COMMAND).about("Stop the pair programming session"),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new(COMPLETIONS_COMMAND)
+                .about("Print a completion script for bash, zsh or fish")
+                .arg(
+                    Arg::new(SHELL_ARG_ID)
+                        .required(true)
+                        .value_parser(SHELLS)
+                        .help("Shell to complete in"),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new(VERIFY_ASSETS_COMMAND)
+                .about("Report asset keys missing in the configured Discord application"),
+        )
+        .subcommand(
+            ClapCommand::new(UPDATE_COMMAND)
+                .about("Install the latest release from GitHub if it is newer"),
+        )
+        .subcommand(
+            ClapCommand::new(CONFIG_COMMAND)
+                .about("Inspect the configuration file")
+                .subcommand_required(true)
+                .subcommand(ClapCommand::new(CONFIG_VALIDATE_COMMAND).about(
+                    "Report unknown keys, invalid values and conflicting options with their line",
+                ))
+                .subcommand(
+                    ClapCommand::new(CONFIG_SCHEMA_COMMAND)
+                        .about("Print the JSON Schema of the configuration file"),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new(SECRET_COMMAND)
+                .about("Store or remove integration tokens in the login Keychain")
+                .subcommand_required(true)
+                .subcommand(
+                    ClapCommand::new(SECRET_SET_COMMAND)
+                        .about("Prompt for a secret and store it")
+                        .arg(secret_name_arg()),
+                )
+                .subcommand(
+                    ClapCommand::new(SECRET_DELETE_COMMAND)
+                        .about("Remove a stored secret")
+                        .arg(secret_name_arg()),
+                ),
+        )
+}
+
+/// Positional argument naming a Keychain secret
+fn secret_name_arg() -> Arg {
+    Arg::new(SECRET_NAME_ARG_ID)
+        .required(true)
+        .value_parser(value_parser!(Secret))
+        .help("Name of the secret")
+}
+
+/// Parses the command-line arguments
+pub fn get_matches() -> ArgMatches {
+    build_cli().get_matches()
+}
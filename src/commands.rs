@@ -0,0 +1,132 @@
+use clap::ArgMatches;
+
+use crate::{
+    cli::{
+        COMPLETIONS_COMMAND, CONFIG_COMMAND, CONFIG_SCHEMA_COMMAND, CONFIG_VALIDATE_COMMAND,
+        CSV_ARG_ID, DETAILS_ARG_ID, DURATION_ARG_ID, EXPORT_COMMAND, FOR_ARG_ID, JSON_ARG_ID,
+        LOGS_COMMAND, PAIR_COMMAND, PAIR_START_COMMAND, PAIR_STOP_COMMAND, PAUSE_COMMAND,
+        POMODORO_COMMAND, POMODORO_START_COMMAND, POMODORO_STOP_COMMAND, REPORT_COMMAND,
+        RESUME_COMMAND, SECRET_COMMAND, SECRET_DELETE_COMMAND, SECRET_NAME_ARG_ID,
+        SECRET_SET_COMMAND, SET_COMMAND, SHELL_ARG_ID, STATE_ARG_ID, STATUS_COMMAND, STOP_COMMAND,
+        TAIL_ARG_ID, UPDATE_COMMAND, VERIFY_ASSETS_COMMAND, WEEK_ARG_ID,
+    },
+    completions::print_completions,
+    config::{self, AppConfig},
+    control::{self, Request},
+    export::print_sessions,
+    instance,
+    report::{print_report, print_status, ReportFormat, ReportPeriod},
+    schema::{print_schema, validate_config},
+    update::update,
+    utils::{
+        duration::parse_duration,
+        keychain::{delete_secret, write_secret, Secret},
+    },
+    verify_assets::verify_assets,
+    Result,
+};
+
+/// Runs a subcommand, `matches` are the top-level arguments used to load the
+/// configuration
+pub fn run(name: &str, args: &ArgMatches, matches: &ArgMatches) -> Result<()> {
+    match name {
+        STOP_COMMAND => instance::stop(),
+        PAUSE_COMMAND => send(Request::Pause {
+            seconds: get_duration(args, FOR_ARG_ID)?,
+        }),
+        SET_COMMAND => send(Request::Set {
+            details: args.get_one::<String>(DETAILS_ARG_ID).cloned(),
+            state: args.get_one::<String>(STATE_ARG_ID).cloned(),
+            seconds: get_duration(args, DURATION_ARG_ID)?,
+        }),
+        RESUME_COMMAND => send(Request::Resume),
+        LOGS_COMMAND => send(Request::Logs {
+            tail: args.get_one::<usize>(TAIL_ARG_ID).copied(),
+        }),
+        REPORT_COMMAND => {
+            let period = if args.get_flag(WEEK_ARG_ID) {
+                ReportPeriod::Week
+            } else {
+                ReportPeriod::Today
+            };
+            print_report(period, get_format(args), config::daily_goal(matches))
+        }
+        STATUS_COMMAND => {
+            let instance = match control::send(&Request::Status) {
+                Ok(response) => response.message,
+                Err(_) => String::from("Not running"),
+            };
+            print_status(&instance, config::daily_goal(matches))
+        }
+        EXPORT_COMMAND => print_sessions(get_format(args)),
+        POMODORO_COMMAND => match args.subcommand_name() {
+            Some(POMODORO_START_COMMAND) => send(Request::PomodoroStart),
+            Some(POMODORO_STOP_COMMAND) => send(Request::PomodoroStop),
+            _ => unreachable!("unknown pomodoro subcommand"),
+        },
+        PAIR_COMMAND => match args.subcommand_name() {
+            Some(PAIR_START_COMMAND) => send(Request::PairStart),
+            Some(PAIR_STOP_COMMAND) => send(Request::PairStop),
+            _ => unreachable!("unknown pair subcommand"),
+        },
+        UPDATE_COMMAND => update(),
+        COMPLETIONS_COMMAND => print_completions(
+            args.get_one::<String>(SHELL_ARG_ID)
+                .expect("shell is required"),
+        ),
+        VERIFY_ASSETS_COMMAND => verify_assets(&AppConfig::new(matches)?),
+        CONFIG_COMMAND => match args.subcommand_name() {
+            Some(CONFIG_VALIDATE_COMMAND) => validate_config(),
+            Some(CONFIG_SCHEMA_COMMAND) => print_schema(),
+            _ => unreachable!("unknown config subcommand"),
+        },
+        SECRET_COMMAND => match args.subcommand() {
+            Some((SECRET_SET_COMMAND, args)) => {
+                let secret = get_secret(args);
+                write_secret(secret)?;
+                println!("Stored the {} secret", secret.account());
+                Ok(())
+            }
+            Some((SECRET_DELETE_COMMAND, args)) => {
+                let secret = get_secret(args);
+                delete_secret(secret)?;
+                println!("Deleted the {} secret", secret.account());
+                Ok(())
+            }
+            _ => unreachable!("unknown secret subcommand"),
+        },
+        _ => unreachable!("unknown subcommand {name}"),
+    }
+}
+
+/// Parses the optional duration argument `id` into seconds
+fn get_duration(args: &ArgMatches, id: &str) -> Result<Option<u64>> {
+    args.get_one::<String>(id)
+        .map(|duration| parse_duration(duration))
+        .transpose()
+}
+
+/// Reads the required secret name argument
+fn get_secret(args: &ArgMatches) -> Secret {
+    *args
+        .get_one::<Secret>(SECRET_NAME_ARG_ID)
+        .expect("secret name is required")
+}
+
+/// Reads the output format from the `--json` and `--csv` flags
+fn get_format(args: &ArgMatches) -> ReportFormat {
+    if args.get_flag(JSON_ARG_ID) {
+        ReportFormat::Json
+    } else if args.get_flag(CSV_ARG_ID) {
+        ReportFormat::Csv
+    } else {
+        ReportFormat::Text
+    }
+}
+
+/// Sends `request` to the running instance and prints its response
+fn send(request: Request) -> Result<()> {
+    let response = control::send(&request)?;
+    println!("{}", response.message);
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use serde::Deserialize;
+
+use crate::{
+    locale::Text,
+    utils::{assets::ActivityAsset, osascript_parser::TITLE_SEPARATOR},
+};
+
+/// A developer tool shipped with Xcode whose use is shown in the presence
+/// while it is the frontmost application
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompanionApp {
+    /// Reality Composer Pro, editing RealityKit scenes for visionOS
+    RealityComposerPro,
+    /// Create ML, training machine learning models
+    CreateMl,
+}
+
+impl CompanionApp {
+    /// Returns the name of the application process
+    pub fn process_name(&self) -> &'static str {
+        match self {
+            CompanionApp::RealityComposerPro => "Reality Composer Pro",
+            CompanionApp::CreateMl => "Create ML",
+        }
+    }
+
+    /// Returns the details line shown while the app is frontmost
+    pub fn details(&self) -> Text {
+        match self {
+            CompanionApp::RealityComposerPro => Text::ComposingScene,
+            CompanionApp::CreateMl => Text::TrainingModel,
+        }
+    }
+
+    /// Returns the image shown while the app is frontmost
+    pub fn asset(&self) -> ActivityAsset {
+        match self {
+            CompanionApp::RealityComposerPro => ActivityAsset::RealityComposerPro,
+            CompanionApp::CreateMl => ActivityAsset::CreateMl,
+        }
+    }
+
+    /// Returns the extensions of the documents the app opens
+    fn document_extensions(&self) -> &'static [&'static str] {
+        match self {
+            CompanionApp::RealityComposerPro => &[".realitycomposerpro", ".usda", ".usdz", ".usdc"],
+            CompanionApp::CreateMl => &[".mlproj"],
+        }
+    }
+
+    /// Returns the enabled app running as process `name`
+    pub fn find(enabled: &[CompanionApp], name: &str) -> Option<CompanionApp> {
+        enabled
+            .iter()
+            .copied()
+            .find(|app| app.process_name() == name)
+    }
+
+    /// Returns the name of the document open in the window titled
+    /// `window_title`, without its extension, or `None` when no document is open
+    pub fn document_name<'a>(&self, window_title: &'a str) -> Option<&'a str> {
+        let document = window_title.split(TITLE_SEPARATOR).next()?.trim();
+        let name = self
+            .document_extensions()
+            .iter()
+            .find_map(|extension| document.strip_suffix(extension))
+            .unwrap_or(document);
+        (!name.is_empty()).then_some(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_enabled_apps() {
+        let enabled = [CompanionApp::RealityComposerPro];
+        assert_eq!(
+            CompanionApp::find(&enabled, "Reality Composer Pro"),
+            Some(CompanionApp::RealityComposerPro)
+        );
+        assert_eq!(CompanionApp::find(&[], "Reality Composer Pro"), None);
+        assert_eq!(CompanionApp::find(&enabled, "Xcode"), None);
+    }
+
+    #[test]
+    fn reads_document_name_from_title() {
+        let app = CompanionApp::RealityComposerPro;
+        assert_eq!(
+            app.document_name("Garden.realitycomposerpro — Scene.usda"),
+            Some("Garden")
+        );
+        assert_eq!(app.document_name("Immersive.usda"), Some("Immersive"));
+        assert_eq!(app.document_name(""), None);
+        assert_eq!(
+            CompanionApp::CreateMl.document_name("FlowerClassifier.mlproj"),
+            Some("FlowerClassifier")
+        );
+    }
+}
@@ -0,0 +1,56 @@
+use std::io::{self, Write};
+
+use clap_complete::{generate, Shell};
+
+use crate::{cli::build_cli, Result};
+
+/// Name of the completed command
+const BIN_NAME: &str = env!("CARGO_PKG_NAME");
+
+/// Shells for which completion scripts are generated
+pub const SHELLS: [&str; 3] = ["bash", "zsh", "fish"];
+
+/// Writes the completion script for `shell`, one of `SHELLS`, to `out`,
+/// generated from the command-line interface so that it covers every
+/// subcommand and flag
+fn write_completions(shell: &str, out: &mut dyn Write) {
+    let shell: Shell = shell
+        .parse()
+        .unwrap_or_else(|_| unreachable!("unknown shell {shell}"));
+    generate(shell, &mut build_cli(), BIN_NAME, out);
+}
+
+/// Prints the completion script for `shell`, one of `SHELLS`
+pub fn print_completions(shell: &str) -> Result<()> {
+    write_completions(shell, &mut io::stdout());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script(shell: &str) -> String {
+        let mut script = Vec::new();
+        write_completions(shell, &mut script);
+        String::from_utf8(script).unwrap()
+    }
+
+    #[test]
+    fn completes_subcommands_and_flags() {
+        for shell in SHELLS {
+            let script = script(shell);
+            for word in ["report", "secret", "schema", "hide-file", "week"] {
+                assert!(script.contains(word), "{shell} does not complete {word}");
+            }
+        }
+    }
+
+    #[test]
+    fn completes_possible_values() {
+        let script = script("bash");
+        assert!(script.contains("github"));
+        assert!(script.contains("bash zsh fish"));
+        assert!(script.contains("complete -F _xcode__discord__rpc"));
+    }
+}
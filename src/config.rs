@@ -1,75 +1,479 @@
-use clap::{Arg, ArgAction, ArgMatches, Command as ClapCommand};
-use config::{Config, Environment, File, FileFormat};
+use std::{collections::HashMap, env, path::PathBuf};
+
+use clap::ArgMatches;
+use config::{
+    builder::DefaultState, Config, ConfigBuilder, ConfigError, Environment, File, FileFormat, Map,
+    Source, Value,
+};
 use directories::ProjectDirs;
 use serde::Deserialize;
 
-/// Argument ID for hiding the file name in Discord Rich Presence
-const HIDE_FILE_ARG_ID: &str = "hide_file";
-/// Argument ID for hiding the project name in Discord Rich Presence
-const HIDE_PROJECT_ARG_ID: &str = "hide_project";
+#[cfg(feature = "activitywatch")]
+use crate::integrations::activitywatch::ActivityWatchConfig;
+#[cfg(feature = "mqtt")]
+use crate::integrations::mqtt::MqttConfig;
+#[cfg(feature = "overlay")]
+use crate::integrations::overlay::OverlayConfig;
+#[cfg(feature = "slack")]
+use crate::integrations::slack::SlackConfig;
+#[cfg(feature = "time-tracking")]
+use crate::integrations::time_tracking::TimeTrackingConfig;
+#[cfg(feature = "webhooks")]
+use crate::integrations::webhooks::WebhookConfig;
+#[cfg(feature = "websocket")]
+use crate::integrations::websocket::WebSocketConfig;
+use crate::{
+    activity_model::ActivityType,
+    cli::{EVENTS_ARG_ID, HIDE_FILE_ARG_ID, HIDE_PROJECT_ARG_ID, PROFILE_ARG_ID},
+    companion_apps::CompanionApp,
+    locale::Text,
+    pairing::PairingConfig,
+    pomodoro::PomodoroConfig,
+    schedule::Schedule,
+    schema::list_keys,
+    utils::{duration::deserialize_seconds, file_display::FileDisplay},
+};
+
 /// Content of the default configuration file
-const DEFAULT_CONFIG: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/default.toml"));
+pub const DEFAULT_CONFIG: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/default.toml"));
 
 #[derive(Debug, Deserialize)]
 pub struct AppConfig {
+    /// ID of the Discord application whose name and art assets are shown
+    pub application_id: String,
     /// Interval in seconds for checking status for Discord and Xcode
+    #[serde(deserialize_with = "deserialize_seconds")]
     pub update_interval: u64,
     /// Interval in seconds for checking updates in Xcode
+    #[serde(deserialize_with = "deserialize_seconds")]
     pub xcode_update_interval: u64,
-    /// Number of update cycles before re-checking if Xcode is running
-    pub xcode_check_cycle: u8,
+    /// Interval in seconds between two checks whether Xcode was launched
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub xcode_check_interval: u64,
+    /// Interval in seconds between two checks whether Xcode is frontmost
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub frontmost_check_interval: u64,
+    /// System sleep in seconds after which the elapsed time is reset on wake
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub wake_reset_threshold: u64,
     /// Threshold in seconds for considering the user idle status
+    #[serde(deserialize_with = "deserialize_seconds")]
     pub idle_threshold: i64,
+    /// Absences from Xcode shorter than this many seconds do not count toward
+    /// `idle_threshold`, longer ones add up until Xcode is back for as long
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub frontmost_grace: i64,
+    /// Multiplier applied to the update intervals while on battery or in Low Power Mode
+    pub battery_interval_multiplier: u64,
+    /// Whether presence requires Xcode to be the frontmost application
+    pub require_frontmost: bool,
+    /// What to do with presence once Xcode has not been frontmost for `idle_threshold`
+    pub idle_action: IdleAction,
+    /// Verb shown before the application name, such as "Playing" or "Watching"
+    pub activity_type: ActivityType,
+    /// Duration in seconds the result of a finished build is shown, 0 to disable
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub build_result_duration: i64,
+    /// Whether to show when Xcode is building or indexing
+    pub show_build_phase: bool,
+    /// Whether to show the progress and the result of test runs
+    pub show_test_progress: bool,
+    /// Whether to show when `xcodebuild` or `swift build` runs in the project
+    pub detect_cli_builds: bool,
+    /// Whether to detect Xcode's commit sheet, requires the Accessibility permission
+    pub detect_source_control: bool,
+    /// Whether to detect the focused SwiftUI preview canvas, requires the Accessibility permission
+    pub detect_swiftui_previews: bool,
+    /// Whether to show when the app runs on a physical device, requires the Accessibility permission
+    pub detect_device_runs: bool,
+    /// Whether to show the model of the device instead of its name
+    pub redact_device_name: bool,
+    /// Duration in seconds Simulator has to be frontmost before the presence
+    /// shows testing in it, 0 to disable
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub simulator_threshold: i64,
+    /// Developer tools besides Xcode shown in the presence while frontmost
+    pub companion_apps: Vec<CompanionApp>,
+    /// Whether to switch to a generic presence while streaming or recording the screen
+    pub streamer_mode: bool,
+    /// Process names that indicate the screen is being streamed or recorded
+    pub streamer_apps: Vec<String>,
     /// Whether to hide the file name in Discord Rich Presence
     pub hide_file: bool,
+    /// Globs of file names hidden even while `hide_file` is disabled
+    pub hidden_files: Vec<String>,
+    /// Name of the file marking a project as confidential, hiding the
+    /// presence while it is open, empty to disable
+    pub ignore_marker: String,
     /// Whether to hide the project name in Discord Rich Presence
     pub hide_project: bool,
+    /// Whether to show a codename derived from the project name instead of the name
+    pub pseudonymize: bool,
+    /// How the focused file is shown in `{file}`
+    pub file_display: FileDisplay,
+    /// Whether to append "●" to the file while it has unsaved changes
+    pub unsaved_indicator: bool,
+    /// Number of files focused within `file_switch_window` from which the
+    /// details say the files are being switched between, 0 to disable
+    pub file_switch_threshold: usize,
+    /// Seconds during which a focused file counts toward `file_switch_threshold`
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub file_switch_window: i64,
+    /// Whether to mention the other open projects in the state line
+    pub show_other_projects: bool,
+    /// Whether to prefix the details and state with an emoji of the activity
+    pub emoji: bool,
+    /// Whether to print presence changes as newline-delimited JSON instead of logs
+    pub events: bool,
+    /// Whether to show a notification with the time worked on the project
+    /// when a session ends
+    pub session_summary: bool,
+    /// Coding time in seconds to reach each day, 0 to disable
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub daily_goal: u64,
+    /// Template for the details line, `{file}` is replaced with the current
+    /// file. Defaults to the template of `language`.
+    #[serde(default)]
+    pub details_template: Option<String>,
+    /// Template for the state line, `{project}` is replaced with the current
+    /// project. Defaults to the template of `language`.
+    #[serde(default)]
+    pub state_template: Option<String>,
+    /// Template for the details line when no file is focused, `{project}` is
+    /// replaced with the current project. Defaults to the template of `language`.
+    #[serde(default)]
+    pub browsing_template: Option<String>,
+    /// Language code of the presence texts, e.g. `de`, the system locale when unset
+    #[serde(default)]
+    pub language: Option<String>,
+    /// User translations of the presence texts, keyed by language code
+    #[serde(default)]
+    pub translations: HashMap<String, HashMap<Text, String>>,
+    /// Facts the details line cycles through, empty to always show the file
+    #[serde(default)]
+    pub rotation: Vec<RotationItem>,
+    /// Interval in seconds between two facts of `rotation`
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub rotation_interval: u64,
+    /// Buttons shown in Discord Rich Presence, Discord displays at most 2
+    #[serde(default)]
+    pub buttons: Vec<ButtonConfig>,
+    /// Whether to add a button linking to the project's public GitHub or GitLab repository
+    pub repository_button: bool,
+    /// Token used for GitHub API requests when the `github` Keychain secret is
+    /// not set. Deprecated in favor of the Keychain.
+    #[serde(default)]
+    pub github_token: Option<String>,
+    /// Base URL of the GitHub REST API the pull requests are looked up with
+    pub github_api_url: String,
+    /// Interval in seconds between two checks for an open pull request
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub pull_request_interval: u64,
+    /// Whether to count the lines of code of the project with tokei
+    pub language_stats: bool,
+    /// Interval in seconds between two tokei scans of the project
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub language_stats_interval: u64,
+    /// Whether to notify when a newer release is available
+    pub update_check: bool,
+    /// Interval in seconds between two checks for a newer release
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub update_check_interval: u64,
+    /// URL of a hosted project icon shown as the large image, `{project}` is
+    /// replaced with the project name
+    #[serde(default)]
+    pub icon_url_template: Option<String>,
+    /// Whether to show the project type as the small image
+    pub project_type_icon: bool,
+    /// Template for the hover text of the small image, the project type when
+    /// it renders empty
+    #[serde(default)]
+    pub small_text: Option<String>,
+    /// Template for the hover text of the large image, the language when unset
+    #[serde(default)]
+    pub large_text_template: Option<String>,
+    /// Interval in seconds between two counts of uncommitted changes
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub git_status_interval: u64,
+    /// Ticket keys recognized in branch names in any case, e.g. `ENG` for `eng-42`
+    #[serde(default)]
+    pub ticket_prefixes: Vec<String>,
+    /// URL of a ticket, where `{ticket}` is replaced with the ticket ID
+    #[serde(default)]
+    pub ticket_url_template: Option<String>,
+    /// Display names used in Discord Rich Presence, keyed by the real project name
+    #[serde(default)]
+    pub project_aliases: HashMap<String, String>,
+    /// Presence behavior for each macOS Focus mode, keyed by Focus name
+    #[serde(default)]
+    pub focus_modes: HashMap<String, FocusBehavior>,
+    /// Time window outside of which presence is suppressed
+    #[serde(default)]
+    pub schedule: Option<Schedule>,
+    /// Interval lengths of the pomodoro started with the `pomodoro` command
+    #[serde(default)]
+    pub pomodoro: PomodoroConfig,
+    /// How the pair programming session started with the `pair` command is shown
+    #[serde(default)]
+    pub pairing: PairingConfig,
+    /// ActivityWatch server receiving editor activity
+    #[cfg(feature = "activitywatch")]
+    #[serde(default)]
+    pub activitywatch: Option<ActivityWatchConfig>,
+    /// Toggl or Clockify workspace receiving a time entry per working session
+    #[cfg(feature = "time-tracking")]
+    #[serde(default)]
+    pub time_tracking: Option<TimeTrackingConfig>,
+    /// Slack status set during working sessions
+    #[cfg(feature = "slack")]
+    #[serde(default)]
+    pub slack: Option<SlackConfig>,
+    /// URLs receiving a JSON POST on session and build events
+    #[cfg(feature = "webhooks")]
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+    /// MQTT topic receiving the presence
+    #[cfg(feature = "mqtt")]
+    #[serde(default)]
+    pub mqtt: Option<MqttConfig>,
+    /// File receiving the presence for stream overlays
+    #[cfg(feature = "overlay")]
+    #[serde(default)]
+    pub overlay: Option<OverlayConfig>,
+    /// Localhost WebSocket server pushing the presence
+    #[cfg(feature = "websocket")]
+    #[serde(default)]
+    pub websocket: Option<WebSocketConfig>,
 }
 
-impl AppConfig {
-    pub fn new() -> crate::Result<Self> {
-        let clap_matches = Self::get_clap_matches();
+/// Presence behavior when the user is considered idle
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdleAction {
+    /// Show an "Idle" presence
+    Idle,
+    /// Clear the presence
+    Clear,
+}
 
-        let mut builder =
-            Config::builder().add_source(File::from_str(DEFAULT_CONFIG, FileFormat::Toml));
+/// Presence behavior while a macOS Focus mode is active
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FocusBehavior {
+    /// Show the presence with all configured details
+    #[default]
+    Full,
+    /// Show the presence with the project and file hidden
+    Hidden,
+    /// Clear the presence
+    Off,
+}
+
+/// A fact shown on the details line when `rotation` is enabled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationItem {
+    /// The rendered `details_template`
+    File,
+    /// The language of the current file
+    Language,
+    /// The checked out git branch of the project
+    Branch,
+    /// The time elapsed since the session started
+    Elapsed,
+    /// The open GitHub pull request of the checked out branch
+    PullRequest,
+    /// The issue tracker ticket found in the branch name
+    Ticket,
+    /// The number of files with uncommitted changes
+    Changes,
+}
+
+/// A button linking to an URL in Discord Rich Presence
+#[derive(Debug, Clone, Deserialize)]
+pub struct ButtonConfig {
+    /// Text displayed on the button
+    pub label: String,
+    /// URL opened when the button is clicked
+    pub url: String,
+}
+
+/// Prefix of the environment variables overriding configuration keys
+pub const ENV_PREFIX: &str = "XDRPC";
+
+/// Separator after the prefix of the environment variables of earlier
+/// versions, e.g. `XDRPC__HIDE_PROJECT`
+const LEGACY_ENV_PREFIX_SEPARATOR: &str = "__";
+
+/// Environment variables overriding configuration keys, e.g.
+/// `XDRPC_UPDATE_INTERVAL=10` with `prefix_separator` `_`. Keys of tables are
+/// separated by `__`, e.g. `XDRPC_MQTT__HOST`, and lists by commas.
+fn environment(prefix_separator: &str) -> Environment {
+    list_keys().iter().fold(
+        Environment::with_prefix(ENV_PREFIX)
+            .prefix_separator(prefix_separator)
+            .separator("__")
+            .try_parsing(true)
+            .ignore_empty(true)
+            .list_separator(","),
+        |environment, key| environment.with_list_parse_key(key),
+    )
+}
+
+/// Environment variables with the prefix of earlier versions, when any is
+/// set. They are still honored, below the ones with the current prefix.
+fn legacy_environment() -> Option<Environment> {
+    let prefix = format!("{ENV_PREFIX}{LEGACY_ENV_PREFIX_SEPARATOR}");
+    if !env::vars_os().any(|(key, _)| key.to_string_lossy().starts_with(&prefix)) {
+        return None;
+    }
+    log::warn!("`{prefix}` environment variables are deprecated, use the `{ENV_PREFIX}_` prefix");
+    Some(environment(LEGACY_ENV_PREFIX_SEPARATOR))
+}
+
+/// Keys of a `[profile.<name>]` table, layered above the rest of the
+/// configuration file
+#[derive(Debug, Clone)]
+struct Profile(Map<String, Value>);
+
+impl Profile {
+    /// Reads the profile `name` from the sources added to `builder` so far
+    fn load(builder: &ConfigBuilder<DefaultState>, name: &str) -> crate::Result<Self> {
+        builder
+            .build_cloned()?
+            .get_table(&format!("profile.{name}"))
+            .map(Self)
+            .map_err(|_| crate::Error::InvalidConfig(format!("unknown profile {name}")))
+    }
+}
+
+impl Source for Profile {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        Ok(self.0.clone())
+    }
+}
 
-        if let Some(proj_dirs) = ProjectDirs::from("", "", "xcode-discord-rpc") {
-            let config_path = proj_dirs.config_dir().join("config.toml");
-            builder = builder.add_source(File::from(config_path).required(false));
+/// Keys of the integrations with the Cargo feature building them and whether
+/// this build includes it
+const INTEGRATION_FEATURES: [(&str, &str, bool); 7] = [
+    (
+        "activitywatch",
+        "activitywatch",
+        cfg!(feature = "activitywatch"),
+    ),
+    ("mqtt", "mqtt", cfg!(feature = "mqtt")),
+    ("overlay", "overlay", cfg!(feature = "overlay")),
+    ("slack", "slack", cfg!(feature = "slack")),
+    (
+        "time_tracking",
+        "time-tracking",
+        cfg!(feature = "time-tracking"),
+    ),
+    ("webhooks", "webhooks", cfg!(feature = "webhooks")),
+    ("websocket", "websocket", cfg!(feature = "websocket")),
+];
+
+/// Warns about the configured integrations this build leaves out
+fn warn_missing_integrations(config: &Config) {
+    for (key, feature, _) in INTEGRATION_FEATURES
+        .iter()
+        .filter(|(key, _, enabled)| !enabled && config.get::<Value>(key).is_ok())
+    {
+        log::warn!("Ignoring `{key}`, this build does not include the {feature} feature");
+    }
+}
+
+/// Warns about configured keys that are deprecated
+fn warn_deprecated_keys(config: &Config) {
+    if config.get::<String>("github_token").is_ok() {
+        log::warn!(
+            "`github_token` is deprecated, store the token in the Keychain with \
+             `xcode-discord-rpc secret set github` instead"
+        );
+    }
+}
+
+/// Returns the path of the user configuration file
+pub fn config_path() -> Option<PathBuf> {
+    ProjectDirs::from("", "", "xcode-discord-rpc")
+        .map(|proj_dirs| proj_dirs.config_dir().join("config.toml"))
+}
+
+/// Layers the defaults, the configuration file, the selected profile, the
+/// environment and the flags
+fn load(clap_matches: &ArgMatches) -> crate::Result<Config> {
+    let mut builder =
+        Config::builder().add_source(File::from_str(DEFAULT_CONFIG, FileFormat::Toml));
+
+    if let Some(config_path) = config_path() {
+        builder = builder.add_source(File::from(config_path).required(false));
+    }
+
+    if let Some(name) = clap_matches.get_one::<String>(PROFILE_ARG_ID) {
+        let profile = Profile::load(&builder, name)?;
+        builder = builder.add_source(profile);
+        log::info!("Using the {name} profile");
+    }
+    if let Some(legacy) = legacy_environment() {
+        builder = builder.add_source(legacy);
+    }
+    builder = builder.add_source(environment("_"));
+    // Flags only override when given, the argument IDs are the config keys
+    for id in [HIDE_FILE_ARG_ID, HIDE_PROJECT_ARG_ID, EVENTS_ARG_ID] {
+        if clap_matches.get_flag(id) {
+            builder = builder.set_override(id, true)?;
         }
+    }
 
-        let c = builder
-            .add_source(Environment::with_prefix("XDRPC").separator("__"))
-            .set_override("hide_file", clap_matches.get_flag(HIDE_FILE_ARG_ID))?
-            .set_override("hide_project", clap_matches.get_flag(HIDE_PROJECT_ARG_ID))?
-            .build()?;
+    Ok(builder.build()?)
+}
+
+impl AppConfig {
+    pub fn new(clap_matches: &ArgMatches) -> crate::Result<Self> {
+        let c = load(clap_matches)?;
+        warn_missing_integrations(&c);
+        warn_deprecated_keys(&c);
 
         Ok(c.try_deserialize()?)
     }
+}
+
+/// Reads `daily_goal` alone, so that printing the progress toward it does not
+/// depend on the rest of the configuration being valid, 0 when it is not set
+/// or invalid too
+pub fn daily_goal(clap_matches: &ArgMatches) -> u64 {
+    #[derive(Deserialize)]
+    struct Goal {
+        #[serde(deserialize_with = "deserialize_seconds")]
+        daily_goal: u64,
+    }
+
+    load(clap_matches)
+        .and_then(|c| Ok(c.try_deserialize::<Goal>()?))
+        .map_or(0, |goal| goal.daily_goal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    fn get_clap_matches() -> ArgMatches {
-        ClapCommand::new("Xcode Discord RPC")
-            .version(clap::crate_version!())
-            .author(clap::crate_authors!())
-            .about("Displays Xcode status on Discord Rich Presence")
-            .arg(
-                Arg::new(HIDE_FILE_ARG_ID)
-                    .short('f')
-                    .long("hide-file")
-                    .num_args(0)
-                    .action(ArgAction::SetTrue)
-                    .help("Hide current file in Discord Rich Presence")
-                    .default_value("false"),
-            )
-            .arg(
-                Arg::new(HIDE_PROJECT_ARG_ID)
-                    .short('p')
-                    .long("hide-project")
-                    .num_args(0)
-                    .action(ArgAction::SetTrue)
-                    .help("Hide current project in Discord Rich Presence")
-                    .default_value("false"),
-            )
-            .get_matches()
+    #[test]
+    fn reads_both_environment_prefixes() {
+        let vars = Map::from([
+            ("XDRPC__HIDE_PROJECT".to_string(), "true".to_string()),
+            ("XDRPC__UPDATE_INTERVAL".to_string(), "5".to_string()),
+            ("XDRPC_UPDATE_INTERVAL".to_string(), "10".to_string()),
+        ]);
+        let config = Config::builder()
+            .add_source(environment(LEGACY_ENV_PREFIX_SEPARATOR).source(Some(vars.clone())))
+            .add_source(environment("_").source(Some(vars)))
+            .build()
+            .unwrap();
+        assert_eq!(config.get::<bool>("hide_project").ok(), Some(true));
+        assert_eq!(config.get::<u64>("update_interval").ok(), Some(10));
     }
 }
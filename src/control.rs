@@ -0,0 +1,481 @@
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::PathBuf,
+    sync::{Arc, Mutex, MutexGuard},
+    thread,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    event_log::{EventLog, LoggedEvent},
+    utils::{
+        clock::{Clock, SystemClock},
+        runtime_dir,
+    },
+    Error, Result,
+};
+
+/// Name of the control socket in the runtime directory
+const SOCKET_FILE: &str = "xcode-discord-rpc.sock";
+/// Time after which a client that did not send its request or read the
+/// response is dropped, so that it cannot block the other clients
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A command sent to the running instance over the control socket
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Request {
+    /// Suppress presence, for `seconds` or until resumed
+    Pause { seconds: Option<u64> },
+    /// Show the given details and state instead of the detected presence,
+    /// for `seconds` or until resumed
+    Set {
+        details: Option<String>,
+        state: Option<String>,
+        seconds: Option<u64>,
+    },
+    /// Resume automatic presence after a pause or a manual override
+    Resume,
+    /// Start a pomodoro, restarting it when one is already running
+    PomodoroStart,
+    /// Stop the running pomodoro
+    PomodoroStop,
+    /// Show the session as pair programming
+    PairStart,
+    /// Stop showing the session as pair programming
+    PairStop,
+    /// Return the last `tail` recorded events, or all of them
+    Logs { tail: Option<usize> },
+    /// Describe what the running instance shows
+    Status,
+}
+
+impl Request {
+    /// Name of the request in the event log
+    fn kind(&self) -> &'static str {
+        match self {
+            Request::Pause { .. } => "pause",
+            Request::Set { .. } => "set",
+            Request::Resume => "resume",
+            Request::PomodoroStart => "pomodoro_start",
+            Request::PomodoroStop => "pomodoro_stop",
+            Request::PairStart => "pair_start",
+            Request::PairStop => "pair_stop",
+            Request::Logs { .. } => "logs",
+            Request::Status => "status",
+        }
+    }
+}
+
+/// The answer of the running instance to a `Request`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    pub message: String,
+}
+
+impl Response {
+    fn ok(message: impl Into<String>) -> Self {
+        Self {
+            ok: true,
+            message: message.into(),
+        }
+    }
+
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            message: message.into(),
+        }
+    }
+}
+
+/// Presence set manually with the `set` command
+#[derive(Debug, Clone)]
+pub struct PresenceOverride {
+    pub details: Option<String>,
+    pub state: Option<String>,
+    /// When the override ends, `None` when kept until resumed
+    until: Option<i64>,
+}
+
+/// State changed through the control socket and read by the main loop
+#[derive(Debug)]
+pub struct ControlState {
+    /// Clock of the presence loop, the deadlines and start times are on
+    clock: Arc<dyn Clock>,
+    /// Whether presence is paused
+    paused: bool,
+    /// When the pause ends, `None` when paused until resumed
+    paused_until: Option<i64>,
+    /// Manually set presence replacing the detected one
+    presence_override: Option<PresenceOverride>,
+    /// When the running pomodoro was started
+    pomodoro_started_at: Option<i64>,
+    /// When the pair programming session was started
+    pairing_started_at: Option<i64>,
+    /// Last presence and state changes, shown by the `logs` command
+    events: EventLog,
+}
+
+impl Default for ControlState {
+    fn default() -> Self {
+        Self::new(Arc::new(SystemClock))
+    }
+}
+
+impl ControlState {
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            paused: false,
+            paused_until: None,
+            presence_override: None,
+            pomodoro_started_at: None,
+            pairing_started_at: None,
+            events: EventLog::default(),
+        }
+    }
+
+    /// Returns whether presence is paused, ending the pause once it expired
+    pub fn is_paused(&mut self) -> bool {
+        let now = self.clock.now();
+        if self
+            .paused_until
+            .is_some_and(|paused_until| now >= paused_until)
+        {
+            log::info!("Pause ended, resuming presence");
+            self.record("pause_end", "Pause ended");
+            self.paused = false;
+            self.paused_until = None;
+        }
+        self.paused
+    }
+
+    /// Returns the manual presence override, ending it once it expired
+    pub fn presence_override(&mut self) -> Option<PresenceOverride> {
+        let now = self.clock.now();
+        if self
+            .presence_override
+            .as_ref()
+            .and_then(|presence| presence.until)
+            .is_some_and(|until| now >= until)
+        {
+            log::info!("Presence override ended, returning to automatic presence");
+            self.record("set_end", "Presence override ended");
+            self.presence_override = None;
+        }
+        self.presence_override.clone()
+    }
+
+    /// Returns when the running pomodoro was started
+    pub fn pomodoro_started_at(&self) -> Option<i64> {
+        self.pomodoro_started_at
+    }
+
+    /// Returns when the pair programming session was started
+    pub fn pairing_started_at(&self) -> Option<i64> {
+        self.pairing_started_at
+    }
+
+    /// Records an event shown by the `logs` command
+    pub fn record(&mut self, kind: &'static str, message: impl Into<String>) {
+        self.events.push(LoggedEvent {
+            time: self.clock.now(),
+            kind,
+            message: message.into(),
+        });
+    }
+}
+
+/// `ControlState` shared between the control socket thread and the main loop
+#[derive(Debug, Clone, Default)]
+pub struct SharedControlState(Arc<Mutex<ControlState>>);
+
+impl SharedControlState {
+    /// State whose deadlines and start times are on `clock`
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        Self(Arc::new(Mutex::new(ControlState::new(clock))))
+    }
+
+    /// Locks the state, recovering it if a thread panicked while holding the lock
+    pub fn lock(&self) -> MutexGuard<'_, ControlState> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+fn socket_path() -> Result<PathBuf> {
+    Ok(runtime_dir()?.join(SOCKET_FILE))
+}
+
+/// Starts listening on the control socket in a background thread, with the
+/// deadlines of the requests on `clock`
+pub fn start(clock: Arc<dyn Clock>) -> Result<SharedControlState> {
+    let path = socket_path()?;
+    // only the instance holding the PID file lock gets here, so the socket is stale
+    if path.exists() {
+        fs::remove_file(&path)?;
+    }
+    let listener = UnixListener::bind(&path)?;
+    let state = SharedControlState::new(clock);
+
+    let thread_state = state.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    if let Err(err) = handle_connection(stream, &thread_state) {
+                        log::warn!("Control socket error: {}", err);
+                    }
+                }
+                Err(err) => log::warn!("Control socket error: {}", err),
+            }
+        }
+    });
+
+    log::debug!("Listening on control socket {}", path.display());
+    Ok(state)
+}
+
+/// Reads a single request from `stream` and writes back the response
+fn handle_connection(stream: UnixStream, state: &SharedControlState) -> Result<()> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+    let response = match serde_json::from_str::<Request>(&line) {
+        Ok(request) => {
+            log::debug!("Control request: {:?}", request);
+            handle_request(request, state)
+        }
+        Err(err) => Response::error(format!("invalid request: {err}")),
+    };
+    let mut stream = stream;
+    writeln!(stream, "{}", serde_json::to_string(&response)?)?;
+    Ok(())
+}
+
+/// Applies `request` to `state`, recording it in the event log when it succeeds
+pub fn handle_request(request: Request, state: &SharedControlState) -> Response {
+    let mut state = state.lock();
+    let now = state.clock.now();
+    let kind = request.kind();
+    let response = match request {
+        Request::Pause { seconds } => {
+            let paused_until = match deadline(now, seconds) {
+                Ok(until) => until,
+                Err(response) => return response,
+            };
+            state.paused = true;
+            state.paused_until = paused_until;
+            match seconds {
+                Some(seconds) => Response::ok(format!("Paused for {seconds}s")),
+                None => Response::ok("Paused until resumed"),
+            }
+        }
+        Request::Set {
+            details,
+            state: presence_state,
+            seconds,
+        } => {
+            if details.is_none() && presence_state.is_none() {
+                return Response::error("details or state is required");
+            }
+            let until = match deadline(now, seconds) {
+                Ok(until) => until,
+                Err(response) => return response,
+            };
+            state.presence_override = Some(PresenceOverride {
+                details,
+                state: presence_state,
+                until,
+            });
+            match seconds {
+                Some(seconds) => Response::ok(format!("Presence set for {seconds}s")),
+                None => Response::ok("Presence set until resumed"),
+            }
+        }
+        Request::Resume => {
+            state.paused = false;
+            state.paused_until = None;
+            state.presence_override = None;
+            Response::ok("Resumed")
+        }
+        Request::PomodoroStart => {
+            state.pomodoro_started_at = Some(now);
+            Response::ok("Pomodoro started")
+        }
+        Request::PomodoroStop => match state.pomodoro_started_at.take() {
+            Some(_) => Response::ok("Pomodoro stopped"),
+            None => Response::error("no pomodoro is running"),
+        },
+        Request::PairStart => {
+            if state.pairing_started_at.is_some() {
+                return Response::error("already pairing");
+            }
+            state.pairing_started_at = Some(now);
+            Response::ok("Pairing started")
+        }
+        Request::PairStop => match state.pairing_started_at.take() {
+            Some(_) => Response::ok("Pairing stopped"),
+            None => Response::error("not pairing"),
+        },
+        Request::Logs { tail } => {
+            let events = state
+                .events
+                .tail(tail)
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            return if events.is_empty() {
+                Response::ok("No events recorded yet")
+            } else {
+                Response::ok(events.join("\n"))
+            };
+        }
+        Request::Status => {
+            let mut status = vec![if state.is_paused() {
+                "paused"
+            } else if state.presence_override().is_some() {
+                "showing a presence set manually"
+            } else {
+                "showing the presence"
+            }];
+            if state.pomodoro_started_at.is_some() {
+                status.push("pomodoro running");
+            }
+            if state.pairing_started_at.is_some() {
+                status.push("pairing");
+            }
+            return Response::ok(format!("Running, {}", status.join(", ")));
+        }
+    };
+    if response.ok {
+        state.record(kind, response.message.clone());
+    }
+    response
+}
+
+/// Returns the UNIX time `seconds` after `now`, or an error response when it
+/// is too far in the future to be represented
+fn deadline(now: i64, seconds: Option<u64>) -> std::result::Result<Option<i64>, Response> {
+    seconds
+        .map(|seconds| {
+            i64::try_from(seconds)
+                .ok()
+                .and_then(|seconds| now.checked_add(seconds))
+                .ok_or_else(|| Response::error(format!("{seconds}s is too long")))
+        })
+        .transpose()
+}
+
+/// Sends `request` to the running instance and returns its response
+pub fn send(request: &Request) -> Result<Response> {
+    let mut stream = UnixStream::connect(socket_path()?).map_err(|err| {
+        Error::Control(format!("could not connect to the running instance: {err}"))
+    })?;
+    writeln!(stream, "{}", serde_json::to_string(request)?)?;
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+    let response: Response = serde_json::from_str(&line)?;
+    if !response.ok {
+        return Err(Error::Control(response.message));
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::clock::SimulatedClock;
+
+    #[test]
+    fn rejects_durations_out_of_range() {
+        let state = SharedControlState::default();
+        let response = handle_request(
+            Request::Pause {
+                seconds: Some(u64::MAX),
+            },
+            &state,
+        );
+        assert!(!response.ok);
+        assert!(!state.lock().is_paused());
+
+        let response = handle_request(
+            Request::Set {
+                details: Some("Reviewing".to_string()),
+                state: None,
+                seconds: Some(i64::MAX as u64),
+            },
+            &state,
+        );
+        assert!(!response.ok);
+        assert!(state.lock().presence_override().is_none());
+
+        assert!(handle_request(Request::Pause { seconds: Some(60) }, &state).ok);
+        assert!(state.lock().is_paused());
+    }
+
+    #[test]
+    fn ends_pause_and_override_on_clock() {
+        let clock = SimulatedClock::new();
+        let state = SharedControlState::new(Arc::new(clock.clone()));
+        assert!(handle_request(Request::Pause { seconds: Some(60) }, &state).ok);
+        let set = Request::Set {
+            details: Some("Reviewing".to_string()),
+            state: None,
+            seconds: Some(120),
+        };
+        assert!(handle_request(set, &state).ok);
+        assert!(handle_request(Request::PomodoroStart, &state).ok);
+        assert_eq!(
+            state.lock().pomodoro_started_at(),
+            Some(SimulatedClock::EPOCH)
+        );
+
+        clock.advance(Duration::from_secs(59));
+        assert!(state.lock().is_paused());
+        clock.advance(Duration::from_secs(1));
+        assert!(!state.lock().is_paused());
+        assert!(state.lock().presence_override().is_some());
+        clock.advance(Duration::from_secs(60));
+        assert!(state.lock().presence_override().is_none());
+    }
+
+    #[test]
+    fn drops_silent_clients() {
+        let state = SharedControlState::default();
+        let (_client, server) = UnixStream::pair().unwrap();
+        assert!(handle_connection(server, &state).is_err());
+
+        let (mut client, server) = UnixStream::pair().unwrap();
+        writeln!(
+            client,
+            "{}",
+            serde_json::to_string(&Request::Resume).unwrap()
+        )
+        .unwrap();
+        handle_connection(server, &state).unwrap();
+        let mut line = String::new();
+        BufReader::new(&client).read_line(&mut line).unwrap();
+        assert!(serde_json::from_str::<Response>(&line).unwrap().ok);
+    }
+
+    #[test]
+    fn describes_status() {
+        let state = SharedControlState::default();
+        let status = || handle_request(Request::Status, &state).message;
+        assert_eq!(status(), "Running, showing the presence");
+        handle_request(Request::PomodoroStart, &state);
+        handle_request(Request::Pause { seconds: None }, &state);
+        assert_eq!(status(), "Running, paused, pomodoro running");
+        assert!(state
+            .lock()
+            .events
+            .tail(None)
+            .all(|event| event.kind != "status"));
+    }
+}
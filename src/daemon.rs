@@ -0,0 +1,58 @@
+use std::{
+    fs::{File, OpenOptions},
+    os::fd::AsRawFd,
+    path::PathBuf,
+};
+
+use crate::{utils::data_dir, Error, Result};
+
+/// Name of the log file in the data directory, written to when running as a daemon
+const LOG_FILE: &str = "xcode-discord-rpc.log";
+
+/// Returns the path of the daemon's log file
+pub fn log_file_path() -> Result<PathBuf> {
+    Ok(data_dir()?.join(LOG_FILE))
+}
+
+/// Duplicates `file` onto the standard stream `fd`
+fn redirect(file: &File, fd: i32) -> Result<()> {
+    // SAFETY: both file descriptors are valid for the duration of the call
+    if unsafe { libc::dup2(file.as_raw_fd(), fd) } == -1 {
+        return Err(Error::Io(std::io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+/// Detaches from the terminal: forks, lets the parent exit, starts a new
+/// session and redirects stdout and stderr to the log file. Has to be called
+/// before any thread is spawned.
+pub fn daemonize() -> Result<()> {
+    let log_path = log_file_path()?;
+    let log_file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)?;
+    let null = File::open("/dev/null")?;
+
+    // SAFETY: no other thread is running, so the child starts in a consistent state
+    match unsafe { libc::fork() } {
+        -1 => return Err(Error::Io(std::io::Error::last_os_error())),
+        0 => {}
+        pid => {
+            println!(
+                "Started xcode-discord-rpc in the background (PID {pid}), logging to {}",
+                log_path.display()
+            );
+            std::process::exit(0);
+        }
+    }
+
+    // SAFETY: `setsid` has no memory safety requirements
+    if unsafe { libc::setsid() } == -1 {
+        return Err(Error::Io(std::io::Error::last_os_error()));
+    }
+    redirect(&null, libc::STDIN_FILENO)?;
+    redirect(&log_file, libc::STDOUT_FILENO)?;
+    redirect(&log_file, libc::STDERR_FILENO)?;
+    Ok(())
+}
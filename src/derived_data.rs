@@ -0,0 +1,425 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Component, Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant, SystemTime},
+};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::{
+    locale::{Locale, Text},
+    utils::platform::Platform,
+};
+
+/// Time after the last write in DerivedData during which a phase is
+/// considered to still be running
+const PHASE_WINDOW: Duration = Duration::from_secs(10);
+/// Interval between two attempts to watch DerivedData while it does not exist
+const WATCH_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+/// Interval between two scans of the temporary directory for exports
+const EXPORT_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// Extension of the folders the Organizer writes distribution logs to
+const DISTRIBUTION_LOGS_EXTENSION: &str = ".xcdistributionlogs";
+/// Length of the hash Xcode appends to the DerivedData folder of a project
+const PROJECT_HASH_LEN: usize = 28;
+
+/// Long running Xcode phase inferred from DerivedData activity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BuildPhase {
+    /// The build system is writing its database
+    Building,
+    /// The index store is receiving new units
+    Indexing,
+    /// Swift packages are being fetched and resolved
+    ResolvingPackages,
+    /// `xcodebuild` or `swift build` is running in the project
+    CliBuilding,
+    /// Xcode is archiving the project or the Organizer exports an archive
+    Archiving,
+}
+
+impl BuildPhase {
+    /// Returns whether the phase is a build the user waits for, as opposed to
+    /// indexing, which runs in the background
+    pub fn is_build(&self) -> bool {
+        !matches!(self, BuildPhase::Indexing)
+    }
+
+    /// Returns the text shown in Discord Rich Presence for the phase of
+    /// `project`, mentioning the platform being built for when it is known
+    pub fn summary(&self, project: &str, platform: Option<Platform>, locale: &Locale) -> String {
+        match (self, platform) {
+            (BuildPhase::Building, Some(platform)) => {
+                locale.format(Text::BuildingFor, &[("platform", platform.name())])
+            }
+            (BuildPhase::Building, None) => locale.text(Text::Building).to_string(),
+            (BuildPhase::Indexing, _) => locale.text(Text::Indexing).to_string(),
+            (BuildPhase::ResolvingPackages, _) => locale.text(Text::ResolvingPackages).to_string(),
+            (BuildPhase::CliBuilding, _) => locale.text(Text::BuildingFromCli).to_string(),
+            (BuildPhase::Archiving, _) => locale.format(Text::Archiving, &[("project", project)]),
+        }
+    }
+}
+
+/// Xcode's DerivedData directory
+pub fn derived_data_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .map(|home| PathBuf::from(home).join("Library/Developer/Xcode/DerivedData"))
+}
+
+/// Returns whether `name` is a DerivedData folder of `project`, named
+/// `<project>-<hash>` with a hash of 28 lowercase letters
+fn is_project_dir(name: &str, project: &str) -> bool {
+    name.strip_prefix(project)
+        .and_then(|rest| rest.strip_prefix('-'))
+        .is_some_and(|hash| {
+            hash.len() == PROJECT_HASH_LEN && hash.bytes().all(|byte| byte.is_ascii_lowercase())
+        })
+}
+
+/// Returns the DerivedData folders of `project`
+pub fn project_dirs(project: &str) -> Vec<PathBuf> {
+    let Some(derived_data) = derived_data_dir() else {
+        return Vec::new();
+    };
+    fs::read_dir(derived_data)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| is_project_dir(&entry.file_name().to_string_lossy(), project))
+                .map(|entry| entry.path())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns whether an archive build of the project in its DerivedData folder
+/// `dir` is writing its build database. Archive builds keep it per scheme.
+fn is_archiving(dir: &Path) -> bool {
+    fs::read_dir(dir.join("Build/Intermediates.noindex/ArchiveIntermediates"))
+        .map(|schemes| {
+            schemes.flatten().any(|scheme| {
+                recently_modified(
+                    &scheme
+                        .path()
+                        .join("IntermediateBuildFilesPath/XCBuildData/build.db"),
+                )
+            })
+        })
+        .unwrap_or(false)
+}
+
+/// Returns whether Xcode is resolving the package graph of the project in its
+/// DerivedData folder `dir`, cloning or fetching the repositories of the
+/// packages and writing the resolved state
+fn is_resolving_packages(dir: &Path) -> bool {
+    let packages = dir.join("SourcePackages");
+    let repositories = packages.join("repositories");
+    ["workspace-state.json", "repositories", "checkouts"]
+        .iter()
+        .any(|path| recently_modified(&packages.join(path)))
+        || fs::read_dir(repositories)
+            .map(|repositories| {
+                repositories
+                    .flatten()
+                    .any(|repository| recently_modified(&repository.path().join("FETCH_HEAD")))
+            })
+            .unwrap_or(false)
+}
+
+/// Returns whether the Organizer is exporting or uploading an archive of
+/// `project`, writing its distribution log to a temporary folder named after
+/// the app such as `MyApp_2024-05-01_10-00-00.xcdistributionlogs`
+fn is_exporting(project: &str) -> bool {
+    let prefix = format!("{project}_");
+    fs::read_dir(std::env::temp_dir())
+        .map(|entries| {
+            entries.flatten().any(|entry| {
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                name.starts_with(&prefix)
+                    && name.ends_with(DISTRIBUTION_LOGS_EXTENSION)
+                    && recently_modified(&entry.path().join("IDEDistribution.standard.log"))
+            })
+        })
+        .unwrap_or(false)
+}
+
+/// Returns whether `path` was modified within `PHASE_WINDOW`
+fn recently_modified(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|elapsed| elapsed < PHASE_WINDOW)
+}
+
+/// Infers the phase running in the DerivedData folder `dir` from the
+/// modification times of the build database, the package checkouts and the
+/// index store
+fn scan_phase(dir: &Path) -> Option<BuildPhase> {
+    // Archive builds also write to the regular build database
+    if is_archiving(dir) {
+        Some(BuildPhase::Archiving)
+    } else if recently_modified(&dir.join("Build/Intermediates.noindex/XCBuildData/build.db")) {
+        Some(BuildPhase::Building)
+    } else if is_resolving_packages(dir) {
+        Some(BuildPhase::ResolvingPackages)
+    } else if recently_modified(&dir.join("Index.noindex/DataStore/v5/units")) {
+        Some(BuildPhase::Indexing)
+    } else {
+        None
+    }
+}
+
+/// Returns whether `name` is a file of the build database, which SQLite
+/// writes along with its journal
+fn is_build_db(name: &str) -> bool {
+    name.strip_prefix("build.db")
+        .is_some_and(|journal| ["", "-wal", "-shm", "-journal"].contains(&journal))
+}
+
+/// Returns the DerivedData folder and the phase written to by a change of
+/// `path`, relative to DerivedData
+fn classify(path: &Path) -> Option<(String, BuildPhase)> {
+    let components = path
+        .components()
+        .map(|component| match component {
+            Component::Normal(name) => name.to_str(),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let (dir, rest) = components.split_first()?;
+    let phase = match rest {
+        ["Build", "Intermediates.noindex", "ArchiveIntermediates", _, "IntermediateBuildFilesPath", "XCBuildData", name]
+            if is_build_db(name) =>
+        {
+            BuildPhase::Archiving
+        }
+        ["Build", "Intermediates.noindex", "XCBuildData", name] if is_build_db(name) => {
+            BuildPhase::Building
+        }
+        ["SourcePackages", "workspace-state.json" | "repositories" | "checkouts", ..] => {
+            BuildPhase::ResolvingPackages
+        }
+        ["Index.noindex", "DataStore", "v5", "units", ..] => BuildPhase::Indexing,
+        _ => return None,
+    };
+    Some((dir.to_string(), phase))
+}
+
+/// When each phase of each DerivedData folder last wrote to it
+type Writes = Arc<Mutex<HashMap<(String, BuildPhase), Instant>>>;
+
+/// Locks `writes`, recovering them if the watcher panicked while holding the lock
+fn lock(writes: &Writes) -> MutexGuard<'_, HashMap<(String, BuildPhase), Instant>> {
+    writes.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Infers the phase Xcode is in from the writes to DerivedData, watched with
+/// FSEvents instead of going through AppleScript or scanning DerivedData
+pub struct PhaseMonitor {
+    /// The DerivedData directory
+    root: Option<PathBuf>,
+    /// The FSEvents stream, `None` while DerivedData does not exist
+    watcher: Option<RecommendedWatcher>,
+    /// When the stream was last tried to be started
+    watch_attempted_at: Option<Instant>,
+    writes: Writes,
+    /// When the temporary directory was last scanned for an export of the
+    /// project, and whether one was running
+    export_checked: Option<(String, Instant, bool)>,
+}
+
+impl PhaseMonitor {
+    /// Monitors Xcode's DerivedData directory
+    pub fn new() -> Self {
+        Self::watching(derived_data_dir())
+    }
+
+    /// Monitors the DerivedData directory `root`
+    fn watching(root: Option<PathBuf>) -> Self {
+        let mut monitor = Self {
+            root,
+            watcher: None,
+            watch_attempted_at: None,
+            writes: Arc::default(),
+            export_checked: None,
+        };
+        monitor.watch();
+        monitor
+    }
+
+    /// Starts the FSEvents stream once DerivedData exists. Phases that were
+    /// already running are picked up from the modification times.
+    fn watch(&mut self) {
+        if self.watcher.is_some()
+            || self
+                .watch_attempted_at
+                .is_some_and(|attempted_at| attempted_at.elapsed() < WATCH_RETRY_INTERVAL)
+        {
+            return;
+        }
+        self.watch_attempted_at = Some(Instant::now());
+        let Some(root) = self
+            .root
+            .as_ref()
+            .and_then(|root| fs::canonicalize(root).ok())
+        else {
+            return;
+        };
+        let writes = self.writes.clone();
+        let events_root = root.clone();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            if matches!(event.kind, EventKind::Access(_)) {
+                return;
+            }
+            let now = Instant::now();
+            let mut writes = lock(&writes);
+            for path in &event.paths {
+                if let Some(key) = path.strip_prefix(&events_root).ok().and_then(classify) {
+                    writes.insert(key, now);
+                }
+            }
+        })
+        .and_then(|mut watcher| {
+            watcher.watch(&root, RecursiveMode::Recursive)?;
+            Ok(watcher)
+        });
+        match watcher {
+            Ok(watcher) => self.watcher = Some(watcher),
+            Err(err) => {
+                log::debug!("Failed to watch {}: {}", root.display(), err);
+                return;
+            }
+        }
+        let Ok(entries) = fs::read_dir(&root) else {
+            return;
+        };
+        let now = Instant::now();
+        let mut writes = lock(&self.writes);
+        for entry in entries.flatten() {
+            if let Some(phase) = scan_phase(&entry.path()) {
+                writes.insert(
+                    (entry.file_name().to_string_lossy().to_string(), phase),
+                    now,
+                );
+            }
+        }
+    }
+
+    /// Returns whether the Organizer is exporting `project`, scanning the
+    /// temporary directory at most every `EXPORT_CHECK_INTERVAL`
+    fn is_exporting(&mut self, project: &str) -> bool {
+        match &self.export_checked {
+            Some((checked_project, checked_at, exporting))
+                if checked_project == project && checked_at.elapsed() < EXPORT_CHECK_INTERVAL =>
+            {
+                *exporting
+            }
+            _ => {
+                let exporting = is_exporting(project);
+                self.export_checked = Some((project.to_string(), Instant::now(), exporting));
+                exporting
+            }
+        }
+    }
+
+    /// Returns the phase Xcode is in for `project`: archiving before
+    /// building, resolving packages and indexing, as archive builds also
+    /// write to the regular build database
+    pub fn phase(&mut self, project: &str) -> Option<BuildPhase> {
+        self.watch();
+        if self.is_exporting(project) {
+            return Some(BuildPhase::Archiving);
+        }
+        let writes = lock(&self.writes);
+        [
+            BuildPhase::Archiving,
+            BuildPhase::Building,
+            BuildPhase::ResolvingPackages,
+            BuildPhase::Indexing,
+        ]
+        .into_iter()
+        .find(|phase| {
+            writes.iter().any(|((dir, written), at)| {
+                written == phase && at.elapsed() < PHASE_WINDOW && is_project_dir(dir, project)
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::testing::sandbox;
+
+    #[test]
+    fn matches_project_dirs_exactly() {
+        assert!(is_project_dir("App-bqxnyuvcdpcfhnfyxjgwzkhtgbqd", "App"));
+        assert!(!is_project_dir(
+            "App-Extras-bqxnyuvcdpcfhnfyxjgwzkhtgbqd",
+            "App"
+        ));
+        assert!(!is_project_dir("App-abc", "App"));
+        assert!(!is_project_dir("App-BQXNYUVCDPCFHNFYXJGWZKHTGBQD", "App"));
+    }
+
+    #[test]
+    fn classifies_writes() {
+        let classify = |path: &str| classify(Path::new(path)).map(|(_, phase)| phase);
+        assert_eq!(
+            classify("App-h/Build/Intermediates.noindex/XCBuildData/build.db-wal"),
+            Some(BuildPhase::Building)
+        );
+        assert_eq!(
+            classify(
+                "App-h/Build/Intermediates.noindex/ArchiveIntermediates/App/\
+                 IntermediateBuildFilesPath/XCBuildData/build.db"
+            ),
+            Some(BuildPhase::Archiving)
+        );
+        assert_eq!(
+            classify("App-h/SourcePackages/checkouts/swift-log/Package.swift"),
+            Some(BuildPhase::ResolvingPackages)
+        );
+        assert_eq!(
+            classify("App-h/Index.noindex/DataStore/v5/units/main.o-1A2B"),
+            Some(BuildPhase::Indexing)
+        );
+        // Opening a project writes its PIF cache next to the build database
+        assert_eq!(
+            classify("App-h/Build/Intermediates.noindex/XCBuildData/PIFCache/project"),
+            None
+        );
+        assert_eq!(classify("App-h/Logs/Build/LogStoreManifest.plist"), None);
+        assert_eq!(classify("App-h"), None);
+    }
+
+    #[test]
+    fn follows_writes_to_derived_data() {
+        let root = sandbox().join("WatchedDerivedData");
+        let dir = root.join("Watched-bqxnyuvcdpcfhnfyxjgwzkhtgbqd");
+        let build_data = dir.join("Build/Intermediates.noindex/XCBuildData");
+        fs::create_dir_all(&build_data).unwrap();
+        fs::create_dir_all(dir.join("Index.noindex/DataStore/v5/units")).unwrap();
+        // Indexing started before the monitor, found from the modification time
+        let mut monitor = PhaseMonitor::watching(Some(root));
+        assert_eq!(monitor.phase("Watched"), Some(BuildPhase::Indexing));
+        assert_eq!(monitor.phase("Other"), None);
+
+        fs::write(build_data.join("build.db"), "").unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while monitor.phase("Watched") != Some(BuildPhase::Building) {
+            assert!(Instant::now() < deadline, "the write was not noticed");
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+}
@@ -4,12 +4,48 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub enum Error {
     #[error("Config error: {0}")]
     Config(#[from] config::ConfigError),
+    #[error("Invalid config: {0}")]
+    InvalidConfig(String),
     #[error("DiscordIPC error: {0}")]
     DiscordIpc(String),
     #[error("Oascript error: {0}")]
     Oascript(String),
+    #[error("Asset error: {0}")]
+    Assets(String),
+    #[error("Build log error: {0}")]
+    BuildLog(String),
+    #[error("Control error: {0}")]
+    Control(String),
+    #[error("Invalid duration: {0}")]
+    Duration(String),
+    #[error("Git error: {0}")]
+    Git(String),
+    #[error("HTTP error: {0}")]
+    Http(String),
+    #[error("Keychain error: {0}")]
+    Keychain(String),
+    #[error("MQTT error: {0}")]
+    Mqtt(String),
+    #[error("Instance error: {0}")]
+    Instance(String),
+    #[error("Panic: {0}")]
+    Panic(String),
+    #[error("pmset error: {0}")]
+    Pmset(String),
+    #[error("Process error: {0}")]
+    Process(String),
+    #[error("Scenario error: {0}")]
+    Scenario(String),
+    #[error("Update error: {0}")]
+    Update(String),
+    #[error("tokei error: {0}")]
+    Tokei(String),
     #[error("BoxDyn error: {0}")]
     BoxDyn(#[from] Box<dyn std::error::Error>),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
     #[error("SimpleLogger error: {0}")]
     SimpleLogger(#[from] log::SetLoggerError),
 }
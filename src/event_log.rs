@@ -0,0 +1,100 @@
+use std::{collections::VecDeque, fmt};
+
+use chrono::{DateTime, Local};
+
+/// Number of events kept by the running instance
+const CAPACITY: usize = 200;
+
+/// A presence or state change of the running instance
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoggedEvent {
+    /// UNIX time of the event
+    pub time: i64,
+    /// Short name of the event, e.g. `update` or `pause`
+    pub kind: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for LoggedEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let time = DateTime::from_timestamp(self.time, 0)
+            .map(|time| {
+                time.with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_default();
+        write!(f, "{time} {:<14} {}", self.kind, self.message)
+    }
+}
+
+/// The last events of the running instance, dropping the oldest once full
+#[derive(Debug)]
+pub struct EventLog {
+    events: VecDeque<LoggedEvent>,
+    capacity: usize,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::with_capacity(CAPACITY)
+    }
+}
+
+impl EventLog {
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            events: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Records an event, dropping the oldest one when the log is full
+    pub fn push(&mut self, event: LoggedEvent) {
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    /// Returns the last `count` events, oldest first, or all when `None`
+    pub fn tail(&self, count: Option<usize>) -> impl Iterator<Item = &LoggedEvent> {
+        let skip = count.map_or(0, |count| self.events.len().saturating_sub(count));
+        self.events.iter().skip(skip)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(time: i64) -> LoggedEvent {
+        LoggedEvent {
+            time,
+            kind: "update",
+            message: String::new(),
+        }
+    }
+
+    fn times<'e>(events: impl Iterator<Item = &'e LoggedEvent>) -> Vec<i64> {
+        events.map(|event| event.time).collect()
+    }
+
+    #[test]
+    fn drops_oldest_events() {
+        let mut log = EventLog::with_capacity(3);
+        for time in 1..=5 {
+            log.push(event(time));
+        }
+        assert_eq!(times(log.tail(None)), vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn returns_tail() {
+        let mut log = EventLog::with_capacity(3);
+        log.push(event(1));
+        log.push(event(2));
+        assert_eq!(times(log.tail(Some(1))), vec![2]);
+        assert_eq!(times(log.tail(Some(10))), vec![1, 2]);
+    }
+}
@@ -0,0 +1,81 @@
+use chrono::{DateTime, Local};
+use serde::Serialize;
+
+use crate::{
+    report::{csv_field, ReportFormat},
+    stats::{Session, Stats},
+    Result,
+};
+
+/// Session as exported, with local times instead of UNIX times
+#[derive(Debug, Serialize)]
+struct ExportedSession<'s> {
+    start: String,
+    end: String,
+    project: &'s str,
+    files: usize,
+    languages: &'s std::collections::BTreeMap<String, u64>,
+    idle_seconds: u64,
+}
+
+/// Formats a UNIX time as an RFC 3339 local time
+fn format_time(time: i64) -> String {
+    DateTime::from_timestamp(time, 0)
+        .map(|time| time.with_timezone(&Local).to_rfc3339())
+        .unwrap_or_default()
+}
+
+impl<'s> From<&'s Session> for ExportedSession<'s> {
+    fn from(session: &'s Session) -> Self {
+        ExportedSession {
+            start: format_time(session.start),
+            end: format_time(session.end),
+            project: &session.project,
+            files: session.files,
+            languages: &session.languages,
+            idle_seconds: session.idle_seconds,
+        }
+    }
+}
+
+/// Renders the sessions as CSV. Languages are written as `Swift=3600;C=20`
+/// in a single column.
+pub fn sessions_to_csv(sessions: &[Session]) -> String {
+    let mut csv = String::from("start,end,project,files,languages,idle_seconds\n");
+    for session in sessions {
+        let exported = ExportedSession::from(session);
+        let languages = exported
+            .languages
+            .iter()
+            .map(|(language, seconds)| format!("{language}={seconds}"))
+            .collect::<Vec<_>>()
+            .join(";");
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            exported.start,
+            exported.end,
+            csv_field(exported.project),
+            exported.files,
+            csv_field(&languages),
+            exported.idle_seconds
+        ));
+    }
+    csv
+}
+
+/// Prints all stored sessions in `format`, CSV unless JSON is requested
+pub fn print_sessions(format: ReportFormat) -> Result<()> {
+    let stats = Stats::load()?;
+    match format {
+        ReportFormat::Json => {
+            let sessions = stats
+                .sessions
+                .iter()
+                .map(ExportedSession::from)
+                .collect::<Vec<_>>();
+            println!("{}", serde_json::to_string_pretty(&sessions)?);
+        }
+        ReportFormat::Text | ReportFormat::Csv => print!("{}", sessions_to_csv(&stats.sessions)),
+    }
+    Ok(())
+}
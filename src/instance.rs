@@ -0,0 +1,123 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{Read, Seek, Write},
+    os::fd::AsRawFd,
+    path::PathBuf,
+};
+
+use crate::{utils::runtime_dir, Error, Result};
+
+/// Name of the PID file in the runtime directory
+const PID_FILE: &str = "xcode-discord-rpc.pid";
+
+/// Exclusive lock on the PID file, held for as long as the daemon runs
+pub struct InstanceLock {
+    file: File,
+}
+
+impl InstanceLock {
+    /// Writes the current PID into the PID file, e.g. after forking
+    pub fn write_pid(&mut self) -> Result<()> {
+        write_pid(&mut self.file)
+    }
+}
+
+fn pid_file_path() -> Result<PathBuf> {
+    Ok(runtime_dir()?.join(PID_FILE))
+}
+
+/// Tries to take an exclusive, non-blocking lock on `file`
+fn try_lock(file: &File) -> bool {
+    // SAFETY: the file descriptor is valid for as long as `file` is alive
+    unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) == 0 }
+}
+
+/// Reads the PID stored in the PID file
+fn read_pid(file: &mut File) -> Option<i32> {
+    let mut content = String::new();
+    file.read_to_string(&mut content).ok()?;
+    content.trim().parse().ok()
+}
+
+/// Replaces the content of the PID file with the current PID
+fn write_pid(file: &mut File) -> Result<()> {
+    file.set_len(0)?;
+    file.rewind()?;
+    write!(file, "{}", std::process::id())?;
+    file.flush()?;
+    Ok(())
+}
+
+/// Takes the PID file lock and writes the current PID into it, failing when
+/// another instance is already running
+pub fn acquire() -> Result<InstanceLock> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(pid_file_path()?)?;
+
+    if !try_lock(&file) {
+        let pid = read_pid(&mut file).map_or(String::from("unknown"), |pid| pid.to_string());
+        return Err(Error::Instance(format!(
+            "another instance is already running (PID {pid})"
+        )));
+    }
+
+    write_pid(&mut file)?;
+
+    Ok(InstanceLock { file })
+}
+
+/// Returns the PID of the running instance, or `None` if no instance holds the lock
+pub fn running_pid() -> Result<Option<i32>> {
+    let path = pid_file_path()?;
+    let Ok(mut file) = File::open(&path) else {
+        return Ok(None);
+    };
+    if try_lock(&file) {
+        // nobody holds the lock, the PID file is stale
+        drop(file);
+        fs::remove_file(path)?;
+        return Ok(None);
+    }
+    Ok(read_pid(&mut file))
+}
+
+/// Stops the running instance by sending it `SIGTERM`
+pub fn stop() -> Result<()> {
+    let Some(pid) = running_pid()? else {
+        return Err(Error::Instance(String::from("no running instance found")));
+    };
+    // SAFETY: `kill` has no memory safety requirements
+    if unsafe { libc::kill(pid, libc::SIGTERM) } != 0 {
+        return Err(Error::Instance(format!(
+            "failed to stop PID {pid}: {}",
+            std::io::Error::last_os_error()
+        )));
+    }
+    log::info!("Stopped xcode-discord-rpc (PID {})", pid);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sandbox;
+
+    #[test]
+    fn allows_a_single_instance() {
+        sandbox();
+        let lock = acquire().unwrap();
+        let pid = std::process::id() as i32;
+        assert_eq!(running_pid().unwrap(), Some(pid));
+        let err = acquire().err().unwrap();
+        assert!(err.to_string().contains(&format!("(PID {pid})")), "{err}");
+
+        drop(lock);
+        assert_eq!(running_pid().unwrap(), None);
+        assert!(!pid_file_path().unwrap().exists());
+        assert!(acquire().is_ok());
+    }
+}
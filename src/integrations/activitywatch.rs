@@ -0,0 +1,100 @@
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    integrations::{http::post_json, ActivitySnapshot, Integration},
+    Result,
+};
+
+/// Seconds within which heartbeats with the same data are merged into one event
+const PULSETIME: u64 = 30;
+
+/// Connection to a local ActivityWatch server
+#[derive(Debug, Clone, Deserialize)]
+pub struct ActivityWatchConfig {
+    /// Address of the ActivityWatch server
+    #[serde(default = "default_url")]
+    pub url: String,
+}
+
+fn default_url() -> String {
+    String::from("http://localhost:5600")
+}
+
+/// Sends editor activity heartbeats to an ActivityWatch bucket
+#[derive(Debug)]
+pub struct ActivityWatch {
+    url: String,
+    hostname: String,
+    bucket_created: bool,
+}
+
+impl ActivityWatch {
+    pub fn new(config: &ActivityWatchConfig) -> Self {
+        ActivityWatch {
+            url: config.url.trim_end_matches('/').to_string(),
+            hostname: hostname(),
+            bucket_created: false,
+        }
+    }
+
+    fn bucket_url(&self) -> String {
+        format!(
+            "{}/api/0/buckets/aw-watcher-xcode_{}",
+            self.url, self.hostname
+        )
+    }
+}
+
+impl Integration for ActivityWatch {
+    fn name(&self) -> &'static str {
+        "ActivityWatch"
+    }
+
+    /// Sends a heartbeat for the activity, creating the bucket first. The
+    /// server merges consecutive heartbeats with the same project and file,
+    /// so a new event starts whenever they change.
+    fn updated(&mut self, snapshot: &ActivitySnapshot) -> Result<()> {
+        if !self.bucket_created {
+            post_json(
+                &self.bucket_url(),
+                &json!({
+                    "client": "xcode-discord-rpc",
+                    "type": "app.editor.activity",
+                    "hostname": self.hostname,
+                }),
+            )?;
+            self.bucket_created = true;
+        }
+        post_json(
+            &format!("{}/heartbeat?pulsetime={PULSETIME}", self.bucket_url()),
+            &json!({
+                "timestamp": Utc::now().to_rfc3339(),
+                "duration": 0,
+                "data": {
+                    "project": snapshot.project,
+                    "file": snapshot.file.as_deref().unwrap_or_default(),
+                    "language": snapshot.language.as_deref().unwrap_or_default(),
+                },
+            }),
+        )
+        .inspect_err(|_| self.bucket_created = false)?;
+        Ok(())
+    }
+}
+
+/// Name of this Mac as used for ActivityWatch buckets
+pub fn hostname() -> String {
+    let mut buffer = [0u8; 256];
+    // SAFETY: the buffer outlives the call and its length is passed along
+    let result = unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len()) };
+    if result != 0 {
+        return String::from("unknown");
+    }
+    let end = buffer
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(buffer.len());
+    String::from_utf8_lossy(&buffer[..end]).to_string()
+}
@@ -0,0 +1,85 @@
+//! Newline-delimited JSON on stdout, enabled with `--events`
+
+use std::io::Write;
+
+use chrono::Utc;
+use serde_json::{json, Value};
+
+use crate::{
+    build_log::BuildResult,
+    integrations::{ActivitySnapshot, Integration},
+    Result,
+};
+
+/// Prints one JSON object per presence change, to stdout unless created
+/// with another output
+pub struct EventStream {
+    out: Box<dyn Write>,
+    /// Last printed activity, to skip updates that changed nothing
+    last: Option<ActivitySnapshot>,
+}
+
+impl Default for EventStream {
+    fn default() -> Self {
+        Self::new(Box::new(std::io::stdout()))
+    }
+}
+
+impl EventStream {
+    pub fn new(out: Box<dyn Write>) -> Self {
+        EventStream { out, last: None }
+    }
+
+    /// Prints `payload` extended with the event name and a timestamp as a line
+    fn print(&mut self, event: &str, mut payload: Value) -> Result<()> {
+        payload["event"] = json!(event);
+        payload["timestamp"] = json!(Utc::now().to_rfc3339());
+        writeln!(self.out, "{}", payload)?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+impl Integration for EventStream {
+    fn name(&self) -> &'static str {
+        "Event stream"
+    }
+
+    fn session_started(&mut self, snapshot: &ActivitySnapshot) -> Result<()> {
+        self.print("session_start", json!(snapshot))
+    }
+
+    fn updated(&mut self, snapshot: &ActivitySnapshot) -> Result<()> {
+        if self.last.as_ref() == Some(snapshot) {
+            return Ok(());
+        }
+        self.last = Some(snapshot.clone());
+        self.print("update", json!(snapshot))
+    }
+
+    fn session_ended(&mut self, snapshot: &ActivitySnapshot) -> Result<()> {
+        self.last = None;
+        self.print("session_end", json!(snapshot))
+    }
+
+    fn project_switched(
+        &mut self,
+        previous: &ActivitySnapshot,
+        snapshot: &ActivitySnapshot,
+    ) -> Result<()> {
+        let mut payload = json!(snapshot);
+        payload["previous_project"] = json!(previous.project);
+        self.print("project_switch", payload)
+    }
+
+    fn build_finished(&mut self, project: &str, result: &BuildResult) -> Result<()> {
+        self.print(
+            "build_finished",
+            json!({
+                "project": project,
+                "build": result,
+                "summary": result.summary(),
+            }),
+        )
+    }
+}
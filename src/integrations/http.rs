@@ -0,0 +1,109 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use serde::Serialize;
+
+use crate::{Error, Result};
+
+/// Seconds after which a request is aborted
+const TIMEOUT: u64 = 5;
+
+/// Quotes a value for a curl config file
+fn quote(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r")
+    )
+}
+
+/// Sends an HTTP request with curl and returns the response body. The
+/// request is passed as a curl config through stdin, so that headers with
+/// credentials and the body do not show up in the process list.
+pub fn request(
+    method: &str,
+    url: &str,
+    headers: &[String],
+    body: Option<&impl Serialize>,
+) -> Result<String> {
+    let mut config = format!(
+        "url = {}\nrequest = {}\nmax-time = {TIMEOUT}\nsilent\nfail\n",
+        quote(url),
+        quote(method)
+    );
+    for header in headers {
+        config.push_str(&format!("header = {}\n", quote(header)));
+    }
+    if let Some(body) = body {
+        config.push_str("header = \"Content-Type: application/json\"\n");
+        config.push_str(&format!(
+            "data-binary = {}\n",
+            quote(&serde_json::to_string(body)?)
+        ));
+    }
+    let mut child = Command::new("curl")
+        .args(["-K", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|err| Error::Http(err.to_string()))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(config.as_bytes())?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|err| Error::Http(err.to_string()))?;
+    if !output.status.success() {
+        return Err(Error::Http(format!(
+            "{method} {url} failed with {}",
+            output.status
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Check if a `HEAD` request to `url` answers with 200 without redirects
+pub fn is_ok(url: &str) -> bool {
+    let output = Command::new("curl")
+        .args([
+            "-s",
+            "-o",
+            "/dev/null",
+            "-w",
+            "%{http_code}",
+            "-I",
+            "-m",
+            &TIMEOUT.to_string(),
+        ])
+        .arg(url)
+        .output();
+    match output {
+        Ok(output) => String::from_utf8_lossy(&output.stdout) == "200",
+        Err(err) => {
+            log::debug!("Failed to check {}: {}", url, err);
+            false
+        }
+    }
+}
+
+/// POSTs `body` as JSON to `url` and returns the response body
+#[cfg(any(feature = "activitywatch", feature = "webhooks"))]
+pub fn post_json(url: &str, body: &impl Serialize) -> Result<String> {
+    request("POST", url, &[], Some(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_config_values() {
+        assert_eq!(quote(r#"{"a":"b\"c"}"#), r#""{\"a\":\"b\\\"c\"}""#);
+        assert_eq!(quote("line\nbreak"), r#""line\nbreak""#);
+    }
+}
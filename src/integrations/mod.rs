@@ -0,0 +1,203 @@
+use serde::Serialize;
+
+use crate::{build_log::BuildResult, config::AppConfig, control::SharedControlState, Result};
+
+#[cfg(feature = "activitywatch")]
+pub mod activitywatch;
+pub mod events;
+pub mod http;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+#[cfg(feature = "overlay")]
+pub mod overlay;
+pub mod recorder;
+#[cfg(feature = "slack")]
+pub mod slack;
+#[cfg(feature = "time-tracking")]
+pub mod time_tracking;
+#[cfg(feature = "webhooks")]
+pub mod webhooks;
+#[cfg(feature = "websocket")]
+pub mod websocket;
+
+#[cfg(feature = "activitywatch")]
+use activitywatch::ActivityWatch;
+use events::EventStream;
+#[cfg(feature = "mqtt")]
+use mqtt::Mqtt;
+#[cfg(feature = "overlay")]
+use overlay::Overlay;
+use recorder::EventRecorder;
+#[cfg(feature = "slack")]
+use slack::Slack;
+#[cfg(feature = "time-tracking")]
+use time_tracking::TimeTracker;
+#[cfg(feature = "webhooks")]
+use webhooks::Webhooks;
+#[cfg(feature = "websocket")]
+use websocket::WebSocketServer;
+
+/// What the working presence shows, passed to the integrations on every update
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ActivitySnapshot {
+    /// Real name of the project, also when it is hidden in the presence
+    pub project: String,
+    /// Project name as displayed in the presence, respecting `hide_project` and aliases
+    pub display_project: String,
+    /// Focused file, `None` when hidden or no file is focused
+    pub file: Option<String>,
+    /// Language of the focused file
+    pub language: Option<String>,
+    /// First line of the presence
+    pub details: String,
+    /// Second line of the presence
+    pub state: Option<String>,
+    /// UNIX time the presence's elapsed time counts from
+    pub started_at: i64,
+}
+
+/// A service other than Discord that follows the working sessions.
+///
+/// A session starts when the working presence is first shown for a project
+/// and ends when the presence is cleared, goes idle or switches project.
+pub trait Integration {
+    /// Name used in log messages
+    fn name(&self) -> &'static str;
+
+    /// Called when a working session on `snapshot.project` starts
+    fn session_started(&mut self, _snapshot: &ActivitySnapshot) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called on every update of the working presence, including the first
+    fn updated(&mut self, _snapshot: &ActivitySnapshot) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called when the working session of `snapshot.project` ends
+    fn session_ended(&mut self, _snapshot: &ActivitySnapshot) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called between the end of the session on `previous.project` and the
+    /// start of the session on `snapshot.project`
+    fn project_switched(
+        &mut self,
+        _previous: &ActivitySnapshot,
+        _snapshot: &ActivitySnapshot,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called when a new build log of `project` was found
+    fn build_finished(&mut self, _project: &str, _result: &BuildResult) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// The enabled integrations and the session they follow
+#[derive(Default)]
+pub struct Integrations {
+    integrations: Vec<Box<dyn Integration>>,
+    /// Last activity of the running session
+    session: Option<ActivitySnapshot>,
+}
+
+impl Integrations {
+    /// Creates the integrations enabled in `config`, recording the presence
+    /// changes in the event log of `control`
+    pub fn new(config: &AppConfig, control: SharedControlState) -> Self {
+        let mut integrations: Vec<Box<dyn Integration>> =
+            vec![Box::new(EventRecorder::new(control))];
+        if config.events {
+            integrations.push(Box::<EventStream>::default());
+        }
+        #[cfg(feature = "activitywatch")]
+        if let Some(activitywatch) = &config.activitywatch {
+            integrations.push(Box::new(ActivityWatch::new(activitywatch)));
+        }
+        #[cfg(feature = "time-tracking")]
+        if let Some(time_tracking) = &config.time_tracking {
+            integrations.push(Box::new(TimeTracker::new(time_tracking)));
+        }
+        #[cfg(feature = "slack")]
+        if let Some(slack) = &config.slack {
+            integrations.push(Box::new(Slack::new(slack)));
+        }
+        #[cfg(feature = "overlay")]
+        if let Some(overlay) = &config.overlay {
+            integrations.push(Box::new(Overlay::new(overlay)));
+        }
+        #[cfg(feature = "websocket")]
+        if let Some(websocket) = &config.websocket {
+            match WebSocketServer::start(websocket) {
+                Ok(server) => integrations.push(Box::new(server)),
+                Err(err) => log::warn!("Failed to start WebSocket server: {}", err),
+            }
+        }
+        #[cfg(feature = "mqtt")]
+        if let Some(mqtt) = &config.mqtt {
+            integrations.push(Box::new(Mqtt::new(mqtt)));
+        }
+        #[cfg(feature = "webhooks")]
+        if !config.webhooks.is_empty() {
+            integrations.push(Box::new(Webhooks::new(&config.webhooks)));
+        }
+        Integrations {
+            integrations,
+            session: None,
+        }
+    }
+
+    /// Runs `event` on every integration. Failures are logged and never
+    /// interrupt the presence.
+    fn notify(&mut self, mut event: impl FnMut(&mut dyn Integration) -> Result<()>) {
+        for integration in &mut self.integrations {
+            if let Err(err) = event(integration.as_mut()) {
+                log::warn!("{} integration failed: {}", integration.name(), err);
+            }
+        }
+    }
+
+    /// Adds `integration` to the enabled ones
+    #[cfg(test)]
+    pub fn push(&mut self, integration: Box<dyn Integration>) {
+        self.integrations.push(integration);
+    }
+
+    /// Sends the activity of the working presence, starting a session first
+    /// when none is running or the project changed
+    pub fn publish(&mut self, snapshot: &ActivitySnapshot) {
+        let previous = self
+            .session
+            .clone()
+            .filter(|session| session.project != snapshot.project);
+        if let Some(previous) = &previous {
+            self.end_session();
+            self.notify(|integration| integration.project_switched(previous, snapshot));
+        }
+        if self.session.is_none() {
+            self.notify(|integration| integration.session_started(snapshot));
+        }
+        self.notify(|integration| integration.updated(snapshot));
+        self.session = Some(snapshot.clone());
+    }
+
+    /// Ends the running session, e.g. when the presence is cleared or idle
+    pub fn end_session(&mut self) {
+        if let Some(session) = self.session.take() {
+            self.notify(|integration| integration.session_ended(&session));
+        }
+    }
+
+    /// Reports a finished build of `project`
+    pub fn build_finished(&mut self, project: &str, result: &BuildResult) {
+        self.notify(|integration| integration.build_finished(project, result));
+    }
+}
+
+impl Drop for Integrations {
+    fn drop(&mut self) {
+        self.end_session();
+    }
+}
@@ -0,0 +1,171 @@
+use std::{
+    env, fs,
+    io::Write,
+    os::unix::fs::{DirBuilderExt, OpenOptionsExt},
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{
+    integrations::{ActivitySnapshot, Integration},
+    utils::keychain::{read_secret, Secret},
+    Error, Result,
+};
+
+/// MQTT broker and topic receiving the presence
+#[derive(Debug, Clone, Deserialize)]
+pub struct MqttConfig {
+    #[serde(default = "default_host")]
+    pub host: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    #[serde(default = "default_topic")]
+    pub topic: String,
+    /// User name, the password is read from the Keychain when set
+    #[serde(default)]
+    pub username: Option<String>,
+}
+
+fn default_host() -> String {
+    String::from("localhost")
+}
+
+fn default_port() -> u16 {
+    1883
+}
+
+fn default_topic() -> String {
+    String::from("xcode-discord-rpc/presence")
+}
+
+/// `mosquitto_pub` options file of the user, whose options are kept when
+/// credentials are added
+fn user_options_file() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("mosquitto_pub"))
+}
+
+/// Appends the options passing `username` and `password` to the `options`
+/// of the user
+fn with_credentials(mut options: String, username: &str, password: &str) -> String {
+    if !options.is_empty() && !options.ends_with('\n') {
+        options.push('\n');
+    }
+    options.push_str(&format!("-u {username}\n-P {password}\n"));
+    options
+}
+
+/// Writes the options of the user followed by `username` and the password
+/// from the Keychain to a `mosquitto_pub` options file in a new directory
+/// only readable by the user, and returns that directory. `mosquitto_pub`
+/// reads the file from `$XDG_CONFIG_HOME`, so that the password does not
+/// show up in the process list.
+fn write_credentials(username: &str) -> Result<PathBuf> {
+    let password = read_secret(Secret::Mqtt)?;
+    let dir = env::temp_dir().join(format!("xcode-discord-rpc-mqtt-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::DirBuilder::new().mode(0o700).create(&dir)?;
+    let user_options = user_options_file()
+        .and_then(|file| fs::read_to_string(file).ok())
+        .unwrap_or_default();
+    let options = with_credentials(user_options, username, &password);
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(dir.join("mosquitto_pub"))?
+        .write_all(options.as_bytes())?;
+    Ok(dir)
+}
+
+/// Publishes the presence as a retained JSON message with `mosquitto_pub`
+#[derive(Debug)]
+pub struct Mqtt {
+    config: MqttConfig,
+    /// Last published message, to only publish changes
+    published: Option<Value>,
+}
+
+impl Mqtt {
+    pub fn new(config: &MqttConfig) -> Self {
+        Mqtt {
+            config: config.clone(),
+            published: None,
+        }
+    }
+
+    /// Publishes `message` unless it was the last one published
+    fn publish(&mut self, message: Value) -> Result<()> {
+        if self.published.as_ref() == Some(&message) {
+            return Ok(());
+        }
+        let mut command = Command::new("mosquitto_pub");
+        command
+            .args(["-h", &self.config.host, "-p", &self.config.port.to_string()])
+            .args(["-t", &self.config.topic, "-r", "-s"]);
+        let credentials = match &self.config.username {
+            Some(username) => Some(write_credentials(username)?),
+            None => None,
+        };
+        if let Some(dir) = &credentials {
+            command.env("XDG_CONFIG_HOME", dir);
+        }
+        let status = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .and_then(|mut child| {
+                if let Some(mut stdin) = child.stdin.take() {
+                    stdin.write_all(message.to_string().as_bytes())?;
+                }
+                child.wait()
+            });
+        if let Some(dir) = credentials {
+            let _ = fs::remove_dir_all(dir);
+        }
+        let status = status.map_err(|err| Error::Mqtt(err.to_string()))?;
+        if !status.success() {
+            return Err(Error::Mqtt(format!("mosquitto_pub failed with {status}")));
+        }
+        self.published = Some(message);
+        Ok(())
+    }
+}
+
+impl Integration for Mqtt {
+    fn name(&self) -> &'static str {
+        "MQTT"
+    }
+
+    fn updated(&mut self, snapshot: &ActivitySnapshot) -> Result<()> {
+        let mut message = json!(snapshot);
+        message["active"] = json!(true);
+        self.publish(message)
+    }
+
+    fn session_ended(&mut self, _snapshot: &ActivitySnapshot) -> Result<()> {
+        self.publish(json!({ "active": false }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adds_credentials_to_options_of_user() {
+        assert_eq!(
+            with_credentials(String::new(), "light", "secret"),
+            "-u light\n-P secret\n"
+        );
+        assert_eq!(
+            with_credentials("--cafile ca.crt".to_string(), "light", "secret"),
+            "--cafile ca.crt\n-u light\n-P secret\n"
+        );
+    }
+}
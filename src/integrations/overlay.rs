@@ -0,0 +1,98 @@
+use std::{fs, path::PathBuf};
+
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    integrations::{ActivitySnapshot, Integration},
+    utils::{current_time, duration::format_duration},
+    Result,
+};
+
+/// Format of the overlay file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlayFormat {
+    /// Details, state and elapsed time on separate lines
+    #[default]
+    Text,
+    Json,
+}
+
+/// File an OBS text source can read the presence from
+#[derive(Debug, Clone, Deserialize)]
+pub struct OverlayConfig {
+    /// Path of the file, `~` is expanded to the home directory
+    pub path: String,
+    #[serde(default)]
+    pub format: OverlayFormat,
+}
+
+/// Writes the presence to a file on every update
+#[derive(Debug)]
+pub struct Overlay {
+    path: PathBuf,
+    format: OverlayFormat,
+}
+
+/// Expands a leading `~` to the home directory
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+impl Overlay {
+    pub fn new(config: &OverlayConfig) -> Self {
+        Overlay {
+            path: expand_home(&config.path),
+            format: config.format,
+        }
+    }
+
+    /// Replaces the file through a rename, so OBS never reads it half written
+    fn write(&self, contents: &str) -> Result<()> {
+        let temporary = self.path.with_extension("tmp");
+        fs::write(&temporary, contents)?;
+        fs::rename(&temporary, &self.path)?;
+        Ok(())
+    }
+}
+
+impl Integration for Overlay {
+    fn name(&self) -> &'static str {
+        "Overlay"
+    }
+
+    fn updated(&mut self, snapshot: &ActivitySnapshot) -> Result<()> {
+        let elapsed = format_duration((current_time() - snapshot.started_at).max(0) as u64);
+        let contents = match self.format {
+            OverlayFormat::Text => [
+                Some(snapshot.details.as_str()),
+                snapshot.state.as_deref(),
+                Some(elapsed.as_str()),
+            ]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join("\n"),
+            OverlayFormat::Json => json!({
+                "active": true,
+                "details": snapshot.details,
+                "state": snapshot.state,
+                "elapsed": elapsed,
+                "started_at": snapshot.started_at,
+            })
+            .to_string(),
+        };
+        self.write(&contents)
+    }
+
+    fn session_ended(&mut self, _snapshot: &ActivitySnapshot) -> Result<()> {
+        match self.format {
+            OverlayFormat::Text => self.write(""),
+            OverlayFormat::Json => self.write(&json!({ "active": false }).to_string()),
+        }
+    }
+}
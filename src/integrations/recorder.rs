@@ -0,0 +1,75 @@
+//! Presence changes kept by the running instance for the `logs` command
+
+use crate::{
+    build_log::BuildResult,
+    control::SharedControlState,
+    integrations::{ActivitySnapshot, Integration},
+    Result,
+};
+
+/// Records the presence changes in the event log of the control state
+pub struct EventRecorder {
+    control: SharedControlState,
+    /// Last recorded activity, to skip updates that changed nothing
+    last: Option<ActivitySnapshot>,
+}
+
+impl EventRecorder {
+    pub fn new(control: SharedControlState) -> Self {
+        Self {
+            control,
+            last: None,
+        }
+    }
+
+    fn record(&self, kind: &'static str, message: String) {
+        self.control.lock().record(kind, message);
+    }
+}
+
+impl Integration for EventRecorder {
+    fn name(&self) -> &'static str {
+        "Event log"
+    }
+
+    fn session_started(&mut self, snapshot: &ActivitySnapshot) -> Result<()> {
+        self.record("session_start", snapshot.project.clone());
+        Ok(())
+    }
+
+    fn updated(&mut self, snapshot: &ActivitySnapshot) -> Result<()> {
+        if self.last.as_ref() == Some(snapshot) {
+            return Ok(());
+        }
+        self.last = Some(snapshot.clone());
+        let message = match &snapshot.state {
+            Some(state) => format!("{} | {}", snapshot.details, state),
+            None => snapshot.details.clone(),
+        };
+        self.record("update", message);
+        Ok(())
+    }
+
+    fn session_ended(&mut self, snapshot: &ActivitySnapshot) -> Result<()> {
+        self.last = None;
+        self.record("session_end", snapshot.project.clone());
+        Ok(())
+    }
+
+    fn project_switched(
+        &mut self,
+        previous: &ActivitySnapshot,
+        snapshot: &ActivitySnapshot,
+    ) -> Result<()> {
+        self.record(
+            "project_switch",
+            format!("{} -> {}", previous.project, snapshot.project),
+        );
+        Ok(())
+    }
+
+    fn build_finished(&mut self, project: &str, result: &BuildResult) -> Result<()> {
+        self.record("build_finished", format!("{project}: {}", result.summary()));
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{
+    integrations::{http::request, ActivitySnapshot, Integration},
+    utils::{
+        keychain::{read_secret, Secret},
+        template::render_template,
+    },
+    Error, Result,
+};
+
+/// Slack status shown during working sessions
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SlackConfig {
+    /// Status text, where `{project}` is replaced like in `state_template`
+    pub status_template: String,
+    /// Status emoji in Slack's `:name:` form
+    pub status_emoji: String,
+    /// Base URL of the Slack Web API
+    pub api_url: String,
+}
+
+impl Default for SlackConfig {
+    fn default() -> Self {
+        Self {
+            status_template: String::from("Working on {project}"),
+            status_emoji: String::from(":hammer_and_wrench:"),
+            api_url: String::from("https://slack.com/api"),
+        }
+    }
+}
+
+/// Mirrors the working session to the Slack status of the user
+#[derive(Debug)]
+pub struct Slack {
+    config: SlackConfig,
+    /// User token read from the Keychain on first use
+    token: Option<String>,
+    /// Status text currently set, to only call Slack when it changes
+    status: Option<String>,
+}
+
+impl Slack {
+    pub fn new(config: &SlackConfig) -> Self {
+        Slack {
+            config: config.clone(),
+            token: None,
+            status: None,
+        }
+    }
+
+    /// Sets the Slack status, clearing it when `text` and `emoji` are empty
+    fn set_status(&mut self, text: &str, emoji: &str) -> Result<()> {
+        if self.token.is_none() {
+            self.token = Some(read_secret(Secret::Slack)?);
+        }
+        let headers = [format!(
+            "Authorization: Bearer {}",
+            self.token.as_deref().unwrap_or_default()
+        )];
+        let body = json!({
+            "profile": {
+                "status_text": text,
+                "status_emoji": emoji,
+                "status_expiration": 0,
+            }
+        });
+        let url = format!(
+            "{}/users.profile.set",
+            self.config.api_url.trim_end_matches('/')
+        );
+        let response: Value = serde_json::from_str(&request("POST", &url, &headers, Some(&body))?)?;
+        if response["ok"] != Value::Bool(true) {
+            return Err(Error::Http(format!(
+                "Slack refused the status: {}",
+                response["error"].as_str().unwrap_or("unknown error")
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Integration for Slack {
+    fn name(&self) -> &'static str {
+        "Slack"
+    }
+
+    fn updated(&mut self, snapshot: &ActivitySnapshot) -> Result<()> {
+        let text = render_template(
+            &self.config.status_template,
+            &[("project", &snapshot.display_project)],
+        );
+        if self.status.as_ref() == Some(&text) {
+            return Ok(());
+        }
+        let emoji = self.config.status_emoji.clone();
+        self.set_status(&text, &emoji)?;
+        self.status = Some(text);
+        Ok(())
+    }
+
+    fn session_ended(&mut self, _snapshot: &ActivitySnapshot) -> Result<()> {
+        if self.status.take().is_some() {
+            self.set_status("", "")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use base64::Engine;
+use chrono::{SecondsFormat, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{
+    integrations::{http::request, ActivitySnapshot, Integration},
+    utils::keychain::{read_secret, Secret},
+    Error, Result,
+};
+
+/// Time tracking service receiving the time entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeTrackingService {
+    Toggl,
+    Clockify,
+}
+
+impl TimeTrackingService {
+    /// Keychain secret holding the API key of the service
+    fn secret(&self) -> Secret {
+        match self {
+            TimeTrackingService::Toggl => Secret::Toggl,
+            TimeTrackingService::Clockify => Secret::Clockify,
+        }
+    }
+}
+
+/// Where time entries are created
+#[derive(Debug, Clone, Deserialize)]
+pub struct TimeTrackingConfig {
+    pub service: TimeTrackingService,
+    /// ID of the workspace in which time entries are created
+    pub workspace_id: String,
+    /// Project IDs of the service, keyed by Xcode project name
+    #[serde(default)]
+    pub projects: HashMap<String, String>,
+}
+
+/// Starts a time entry when a working session begins and stops it when the
+/// session ends
+#[derive(Debug)]
+pub struct TimeTracker {
+    config: TimeTrackingConfig,
+    /// API key read from the Keychain on first use
+    api_key: Option<String>,
+    /// Clockify user stopping the entry, looked up on first use
+    clockify_user_id: Option<String>,
+    /// ID of the running time entry
+    running_entry: Option<String>,
+}
+
+impl TimeTracker {
+    pub fn new(config: &TimeTrackingConfig) -> Self {
+        TimeTracker {
+            config: config.clone(),
+            api_key: None,
+            clockify_user_id: None,
+            running_entry: None,
+        }
+    }
+
+    fn api_key(&mut self) -> Result<String> {
+        if self.api_key.is_none() {
+            self.api_key = Some(read_secret(self.config.service.secret())?);
+        }
+        Ok(self.api_key.clone().unwrap_or_default())
+    }
+
+    fn auth_header(&mut self) -> Result<String> {
+        let api_key = self.api_key()?;
+        Ok(match self.config.service {
+            TimeTrackingService::Toggl => {
+                let credentials = base64::engine::general_purpose::STANDARD
+                    .encode(format!("{api_key}:api_token"));
+                format!("Authorization: Basic {credentials}")
+            }
+            TimeTrackingService::Clockify => format!("X-Api-Key: {api_key}"),
+        })
+    }
+
+    /// Sends a request to the API of the service and parses the JSON answer
+    fn call(&mut self, method: &str, url: &str, body: Option<&Value>) -> Result<Value> {
+        let headers = [self.auth_header()?];
+        let response = request(method, url, &headers, body)?;
+        Ok(serde_json::from_str(&response)?)
+    }
+
+    /// Reads the ID of a created time entry from the response
+    fn entry_id(entry: &Value) -> Result<String> {
+        match &entry["id"] {
+            Value::String(id) => Ok(id.clone()),
+            Value::Number(id) => Ok(id.to_string()),
+            _ => Err(Error::Http(String::from("time entry without an ID"))),
+        }
+    }
+
+    fn now() -> String {
+        Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+    }
+}
+
+impl Integration for TimeTracker {
+    fn name(&self) -> &'static str {
+        match self.config.service {
+            TimeTrackingService::Toggl => "Toggl",
+            TimeTrackingService::Clockify => "Clockify",
+        }
+    }
+
+    fn session_started(&mut self, snapshot: &ActivitySnapshot) -> Result<()> {
+        let workspace = self.config.workspace_id.clone();
+        let project_id = self.config.projects.get(&snapshot.project).cloned();
+        let description = format!("Working on {}", snapshot.project);
+        let entry = match self.config.service {
+            TimeTrackingService::Toggl => {
+                let workspace_id = workspace.parse::<u64>().map_err(|_| {
+                    Error::Http(format!("invalid Toggl workspace ID {workspace:?}"))
+                })?;
+                let body = json!({
+                    "created_with": "xcode-discord-rpc",
+                    "description": description,
+                    "workspace_id": workspace_id,
+                    "project_id": project_id.and_then(|id| id.parse::<u64>().ok()),
+                    "start": Self::now(),
+                    "duration": -1,
+                });
+                self.call(
+                    "POST",
+                    &format!(
+                        "https://api.track.toggl.com/api/v9/workspaces/{workspace}/time_entries"
+                    ),
+                    Some(&body),
+                )?
+            }
+            TimeTrackingService::Clockify => {
+                let body = json!({
+                    "description": description,
+                    "projectId": project_id,
+                    "start": Self::now(),
+                });
+                self.call(
+                    "POST",
+                    &format!("https://api.clockify.me/api/v1/workspaces/{workspace}/time-entries"),
+                    Some(&body),
+                )?
+            }
+        };
+        self.running_entry = Some(Self::entry_id(&entry)?);
+        Ok(())
+    }
+
+    fn session_ended(&mut self, _snapshot: &ActivitySnapshot) -> Result<()> {
+        let Some(entry) = self.running_entry.take() else {
+            return Ok(());
+        };
+        let workspace = self.config.workspace_id.clone();
+        match self.config.service {
+            TimeTrackingService::Toggl => {
+                self.call(
+                    "PATCH",
+                    &format!(
+                        "https://api.track.toggl.com/api/v9/workspaces/{workspace}/time_entries/{entry}/stop"
+                    ),
+                    None,
+                )?;
+            }
+            TimeTrackingService::Clockify => {
+                if self.clockify_user_id.is_none() {
+                    let user = self.call("GET", "https://api.clockify.me/api/v1/user", None)?;
+                    self.clockify_user_id = Some(Self::entry_id(&user)?);
+                }
+                let user = self.clockify_user_id.clone().unwrap_or_default();
+                self.call(
+                    "PATCH",
+                    &format!(
+                        "https://api.clockify.me/api/v1/workspaces/{workspace}/user/{user}/time-entries"
+                    ),
+                    Some(&json!({ "end": Self::now() })),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
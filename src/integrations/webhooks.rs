@@ -0,0 +1,111 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    build_log::BuildResult,
+    integrations::{http::post_json, ActivitySnapshot, Integration},
+    Result,
+};
+
+/// Event sent to webhooks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    SessionStart,
+    SessionEnd,
+    ProjectSwitch,
+    BuildFinished,
+}
+
+fn all_events() -> Vec<WebhookEvent> {
+    vec![
+        WebhookEvent::SessionStart,
+        WebhookEvent::SessionEnd,
+        WebhookEvent::ProjectSwitch,
+        WebhookEvent::BuildFinished,
+    ]
+}
+
+/// URL receiving a JSON POST for each of `events`
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    #[serde(default = "all_events")]
+    pub events: Vec<WebhookEvent>,
+}
+
+/// Posts session and build events to the configured webhooks
+#[derive(Debug)]
+pub struct Webhooks {
+    webhooks: Vec<WebhookConfig>,
+}
+
+impl Webhooks {
+    pub fn new(webhooks: &[WebhookConfig]) -> Self {
+        Webhooks {
+            webhooks: webhooks.to_vec(),
+        }
+    }
+
+    /// Posts `payload`, extended with the event and a timestamp, to every
+    /// webhook subscribed to `event`
+    fn send(&self, event: WebhookEvent, mut payload: serde_json::Value) -> Result<()> {
+        payload["event"] = json!(event);
+        payload["timestamp"] = json!(Utc::now().to_rfc3339());
+        let mut result = Ok(());
+        for webhook in self
+            .webhooks
+            .iter()
+            .filter(|webhook| webhook.events.contains(&event))
+        {
+            if let Err(err) = post_json(&webhook.url, &payload) {
+                result = Err(err);
+            }
+        }
+        result
+    }
+
+    fn activity_payload(snapshot: &ActivitySnapshot) -> serde_json::Value {
+        json!({
+            "project": snapshot.project,
+            "file": snapshot.file,
+            "language": snapshot.language,
+        })
+    }
+}
+
+impl Integration for Webhooks {
+    fn name(&self) -> &'static str {
+        "Webhook"
+    }
+
+    fn session_started(&mut self, snapshot: &ActivitySnapshot) -> Result<()> {
+        self.send(WebhookEvent::SessionStart, Self::activity_payload(snapshot))
+    }
+
+    fn session_ended(&mut self, snapshot: &ActivitySnapshot) -> Result<()> {
+        self.send(WebhookEvent::SessionEnd, Self::activity_payload(snapshot))
+    }
+
+    fn project_switched(
+        &mut self,
+        previous: &ActivitySnapshot,
+        snapshot: &ActivitySnapshot,
+    ) -> Result<()> {
+        let mut payload = Self::activity_payload(snapshot);
+        payload["previous_project"] = json!(previous.project);
+        self.send(WebhookEvent::ProjectSwitch, payload)
+    }
+
+    fn build_finished(&mut self, project: &str, result: &BuildResult) -> Result<()> {
+        self.send(
+            WebhookEvent::BuildFinished,
+            json!({
+                "project": project,
+                "build": result,
+                "summary": result.summary(),
+            }),
+        )
+    }
+}
@@ -0,0 +1,402 @@
+//! Minimal WebSocket server pushing the presence to local overlays. Only
+//! what browsers need to subscribe is implemented: the opening handshake and
+//! unmasked text frames from the server. Messages from clients are ignored.
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{
+    integrations::{ActivitySnapshot, Integration},
+    Result,
+};
+
+/// GUID appended to the client key in the handshake (RFC 6455)
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+/// Time a client gets to send its handshake or to receive a message before
+/// it is dropped, so that a stalled browser tab cannot block the presence
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Port of the WebSocket server on localhost and the web pages allowed to
+/// connect to it
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebSocketConfig {
+    #[serde(default = "default_port")]
+    pub port: u16,
+    /// Values of the `Origin` header accepted from browsers, e.g.
+    /// `http://localhost:8080`. Clients sending none, which are not web
+    /// pages, are always accepted.
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+}
+
+fn default_port() -> u16 {
+    6464
+}
+
+/// Connected clients and the last message, sent to clients when they connect
+#[derive(Debug, Default)]
+struct Clients {
+    streams: Vec<TcpStream>,
+    last_message: Option<String>,
+}
+
+/// Broadcasts presence changes as JSON to WebSocket clients on localhost.
+/// The server stops listening when it is dropped, so that the presence loop
+/// can start it again when it restarts.
+#[derive(Debug)]
+pub struct WebSocketServer {
+    clients: Arc<Mutex<Clients>>,
+    /// Port the server listens on
+    port: u16,
+    /// Tells the thread accepting connections to stop
+    stopping: Arc<AtomicBool>,
+    listener: Option<JoinHandle<()>>,
+}
+
+impl WebSocketServer {
+    /// Starts accepting connections on `127.0.0.1` in a background thread
+    pub fn start(config: &WebSocketConfig) -> Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, config.port))?;
+        let port = listener.local_addr()?.port();
+        let clients = Arc::new(Mutex::new(Clients::default()));
+        let stopping = Arc::new(AtomicBool::new(false));
+        let thread_clients = clients.clone();
+        let thread_stopping = stopping.clone();
+        let allowed_origins = config.allowed_origins.clone();
+        let listener = thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if thread_stopping.load(Ordering::Relaxed) {
+                    break;
+                }
+                if let Err(err) = accept(stream, &allowed_origins, &thread_clients) {
+                    log::debug!("WebSocket handshake failed: {}", err);
+                }
+            }
+        });
+        log::debug!("WebSocket server listening on port {}", port);
+        Ok(WebSocketServer {
+            clients,
+            port,
+            stopping,
+            listener: Some(listener),
+        })
+    }
+
+    /// Sends `message` to every client unless it did not change, dropping
+    /// clients that disconnected or did not take it within `CLIENT_TIMEOUT`
+    fn broadcast(&self, message: Value) {
+        let message = message.to_string();
+        let mut clients = self.clients.lock().unwrap_or_else(|err| err.into_inner());
+        if clients.last_message.as_ref() == Some(&message) {
+            return;
+        }
+        let frame = text_frame(&message);
+        clients
+            .streams
+            .retain_mut(|stream| stream.write_all(&frame).is_ok());
+        clients.last_message = Some(message);
+    }
+}
+
+impl Integration for WebSocketServer {
+    fn name(&self) -> &'static str {
+        "WebSocket"
+    }
+
+    fn updated(&mut self, snapshot: &ActivitySnapshot) -> Result<()> {
+        self.broadcast(message(snapshot));
+        Ok(())
+    }
+
+    fn session_ended(&mut self, _snapshot: &ActivitySnapshot) -> Result<()> {
+        self.broadcast(json!({ "active": false }));
+        Ok(())
+    }
+}
+
+impl Drop for WebSocketServer {
+    fn drop(&mut self) {
+        self.stopping.store(true, Ordering::Relaxed);
+        // Wakes the thread up from waiting for the next connection
+        let _ = TcpStream::connect((Ipv4Addr::LOCALHOST, self.port));
+        if let Some(listener) = self.listener.take() {
+            let _ = listener.join();
+        }
+    }
+}
+
+/// Message sent for `snapshot`, with what the presence shows only, so that
+/// hidden or pseudonymized projects stay hidden from the clients
+fn message(snapshot: &ActivitySnapshot) -> Value {
+    json!({
+        "active": true,
+        "project": snapshot.display_project,
+        "file": snapshot.file,
+        "language": snapshot.language,
+        "details": snapshot.details,
+        "state": snapshot.state,
+        "started_at": snapshot.started_at,
+    })
+}
+
+/// Returns whether a client sending the `Origin` header `origin` may connect.
+/// Browsers send one with every WebSocket handshake, so that any web page
+/// could otherwise read the presence.
+fn is_allowed_origin(origin: Option<&str>, allowed_origins: &[String]) -> bool {
+    origin.is_none_or(|origin| allowed_origins.iter().any(|allowed| allowed == origin))
+}
+
+/// Completes the opening handshake and registers the client
+fn accept(
+    mut stream: TcpStream,
+    allowed_origins: &[String],
+    clients: &Mutex<Clients>,
+) -> Result<()> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut key = None;
+    let mut origin = None;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let name = name.trim();
+            if name.eq_ignore_ascii_case("sec-websocket-key") {
+                key = Some(value.trim().to_string());
+            } else if name.eq_ignore_ascii_case("origin") {
+                origin = Some(value.trim().to_string());
+            }
+        }
+    }
+    if !is_allowed_origin(origin.as_deref(), allowed_origins) {
+        log::debug!(
+            "Rejected WebSocket client from {}",
+            origin.unwrap_or_default()
+        );
+        stream.write_all(b"HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\n\r\n")?;
+        return Ok(());
+    }
+    let Some(key) = key else {
+        stream.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n")?;
+        return Ok(());
+    };
+    write!(
+        stream,
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+         Sec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(&key)
+    )?;
+    let mut clients = clients.lock().unwrap_or_else(|err| err.into_inner());
+    if let Some(message) = &clients.last_message {
+        stream.write_all(&text_frame(message))?;
+    }
+    clients.streams.push(stream);
+    Ok(())
+}
+
+/// Computes the `Sec-WebSocket-Accept` header for a client key
+fn accept_key(key: &str) -> String {
+    base64::engine::general_purpose::STANDARD
+        .encode(sha1(format!("{key}{HANDSHAKE_GUID}").as_bytes()))
+}
+
+/// Encodes a single unmasked text frame
+fn text_frame(message: &str) -> Vec<u8> {
+    let payload = message.as_bytes();
+    let mut frame = vec![0x81];
+    match payload.len() {
+        len @ 0..=125 => frame.push(len as u8),
+        len @ 126..=65535 => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// SHA-1 as required by the WebSocket handshake, not used for security
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+    for chunk in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in chunk.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (state, value) in h.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+    let mut digest = [0u8; 20];
+    for (bytes, state) in digest.chunks_mut(4).zip(h) {
+        bytes.copy_from_slice(&state.to_be_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_accept_key_from_rfc_example() {
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    /// Sends a handshake with `origin` to the server on `port`, returning the
+    /// status line of the response
+    fn handshake(port: u16, origin: Option<&str>) -> String {
+        let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        let origin = origin
+            .map(|origin| format!("Origin: {origin}\r\n"))
+            .unwrap_or_default();
+        write!(
+            stream,
+            "GET / HTTP/1.1\r\nHost: localhost\r\n{origin}\
+             Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n"
+        )
+        .unwrap();
+        let mut status = String::new();
+        BufReader::new(stream).read_line(&mut status).unwrap();
+        status.trim().to_string()
+    }
+
+    #[test]
+    fn rejects_web_pages_not_allowed() {
+        let config = WebSocketConfig {
+            port: 0,
+            allowed_origins: vec!["http://localhost:8080".to_string()],
+        };
+        let server = WebSocketServer::start(&config).unwrap();
+        assert_eq!(
+            handshake(server.port, None),
+            "HTTP/1.1 101 Switching Protocols"
+        );
+        assert_eq!(
+            handshake(server.port, Some("http://localhost:8080")),
+            "HTTP/1.1 101 Switching Protocols"
+        );
+        assert_eq!(
+            handshake(server.port, Some("https://example.com")),
+            "HTTP/1.1 403 Forbidden"
+        );
+    }
+
+    #[test]
+    fn frees_port_when_dropped() {
+        let server = WebSocketServer::start(&WebSocketConfig {
+            port: 0,
+            allowed_origins: Vec::new(),
+        })
+        .unwrap();
+        let port = server.port;
+        drop(server);
+        let config = WebSocketConfig {
+            port,
+            allowed_origins: Vec::new(),
+        };
+        assert!(WebSocketServer::start(&config).is_ok());
+    }
+
+    #[test]
+    fn drops_stalled_clients() {
+        let server = WebSocketServer::start(&WebSocketConfig {
+            port: 0,
+            allowed_origins: Vec::new(),
+        })
+        .unwrap();
+        // The client never reads the messages it is sent
+        let mut stalled = TcpStream::connect((Ipv4Addr::LOCALHOST, server.port)).unwrap();
+        write!(
+            stalled,
+            "GET / HTTP/1.1\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n"
+        )
+        .unwrap();
+        let connected = || !server.clients.lock().unwrap().streams.is_empty();
+        while !connected() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let padding = "x".repeat(1 << 20);
+        for count in 0..64 {
+            server.broadcast(json!({ "count": count, "padding": padding }));
+            if !connected() {
+                return;
+            }
+        }
+        panic!("the stalled client was not dropped");
+    }
+
+    #[test]
+    fn sends_displayed_project_only() {
+        let message = message(&ActivitySnapshot {
+            project: "Secret".to_string(),
+            display_project: "Obsidian".to_string(),
+            file: None,
+            language: Some("Swift".to_string()),
+            details: "Working on a file".to_string(),
+            state: Some("in Obsidian".to_string()),
+            started_at: 0,
+        });
+        assert_eq!(message["project"], "Obsidian");
+        assert!(!message.to_string().contains("Secret"));
+    }
+
+    #[test]
+    fn encodes_frame_lengths() {
+        assert_eq!(text_frame("hi"), [0x81, 2, b'h', b'i']);
+        let frame = text_frame(&"a".repeat(300));
+        assert_eq!(frame[..4], [0x81, 126, 1, 44]);
+        assert_eq!(frame.len(), 304);
+    }
+}
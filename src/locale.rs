@@ -0,0 +1,407 @@
+use std::{collections::HashMap, process::Command};
+
+use serde::Deserialize;
+
+use crate::utils::template::render_template;
+
+/// A text shown in Discord Rich Presence. Placeholders in braces are
+/// replaced like in the templates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Text {
+    Idle,
+    DetailsTemplate,
+    StateTemplate,
+    BrowsingTemplate,
+    AFile,
+    AProject,
+    ProjectCodename,
+    CodingInXcode,
+    ReadingDocumentation,
+    Committing,
+    Reviewing,
+    Designing,
+    SwitchingFiles,
+    EditingPlayground,
+    EditingPlaygroundPage,
+    RunningPlayground,
+    RunningPlaygroundPage,
+    ComposingScene,
+    TrainingModel,
+    TestingInSimulator,
+    TestingInSimulatorOn,
+    RunningOnDevice,
+    ADevice,
+    Archiving,
+    Building,
+    BuildingFor,
+    Indexing,
+    ResolvingPackages,
+    BuildingFromCli,
+    BuildSucceeded,
+    BuildFailed,
+    BuildFailedError,
+    BuildFailedErrors,
+    BuildCancelled,
+    RunningTests,
+    RunningTestsCount,
+    TestsPassed,
+    TestsFailed,
+    Error,
+    Errors,
+    Warning,
+    Warnings,
+    OnBreak,
+    Focus,
+    Pairing,
+    PairingWith,
+    Writing,
+    OnBranch,
+    Session,
+    SessionSummary,
+    DailyGoal,
+    WorkingTreeClean,
+    FileChanged,
+    FilesChanged,
+    Ticket,
+    PullRequestOpen,
+    ViewRepository,
+    OpenTicket,
+    OtherProject,
+    OtherProjects,
+}
+
+impl Text {
+    /// Returns the English text, used when no translation exists
+    fn english(&self) -> &'static str {
+        match self {
+            Text::Idle => "Idle",
+            Text::DetailsTemplate => "Working on {file}",
+            Text::StateTemplate => "in {project}",
+            Text::BrowsingTemplate => "Browsing {project}",
+            Text::AFile => "a file",
+            Text::AProject => "a Project",
+            Text::ProjectCodename => "Project {codename}",
+            Text::CodingInXcode => "Coding in Xcode",
+            Text::ReadingDocumentation => "Reading documentation",
+            Text::Committing => "Committing changes to {project}",
+            Text::Reviewing => "Reviewing changes in {project}",
+            Text::Designing => "Designing {file}",
+            Text::SwitchingFiles => "Switching between {count} files",
+            Text::EditingPlayground => "Editing a Playground",
+            Text::EditingPlaygroundPage => "Editing {page}",
+            Text::RunningPlayground => "Running a Playground",
+            Text::RunningPlaygroundPage => "Running {page}",
+            Text::ComposingScene => "Composing a RealityKit scene",
+            Text::TrainingModel => "Training a model in Create ML",
+            Text::TestingInSimulator => "Testing {project} in the Simulator",
+            Text::TestingInSimulatorOn => "Testing {project} in the Simulator ({device})",
+            Text::RunningOnDevice => "Running on {device}",
+            Text::ADevice => "a device",
+            Text::Archiving => "Archiving {project} for release 🚀",
+            Text::Building => "Building…",
+            Text::BuildingFor => "Building for {platform}…",
+            Text::Indexing => "Indexing…",
+            Text::ResolvingPackages => "Resolving Package Graph…",
+            Text::BuildingFromCli => "Building from CLI",
+            Text::BuildSucceeded => "Build succeeded ✅",
+            Text::BuildFailed => "Build failed ❌",
+            Text::BuildFailedError => "Build failed ❌ (1 error)",
+            Text::BuildFailedErrors => "Build failed ❌ ({errors} errors)",
+            Text::BuildCancelled => "Build cancelled",
+            Text::RunningTests => "Running tests ({finished}/{total})…",
+            Text::RunningTestsCount => "Running tests ({finished})…",
+            Text::TestsPassed => "Tests passed ✅ ({passed})",
+            Text::TestsFailed => "Tests failed ❌ ({failed} of {total})",
+            Text::Error => "1 error",
+            Text::Errors => "{count} errors",
+            Text::Warning => "1 warning",
+            Text::Warnings => "{count} warnings",
+            Text::OnBreak => "On a break, back in {countdown}",
+            Text::Focus => "Focus {countdown} remaining",
+            Text::Pairing => "👥 Pairing",
+            Text::PairingWith => "👥 Pairing with {partner}",
+            Text::Writing => "Writing {language}",
+            Text::OnBranch => "On {branch}",
+            Text::Session => "Session: {duration}",
+            Text::SessionSummary => "You worked {duration} on {project} today",
+            Text::DailyGoal => "{done} / {goal} today",
+            Text::WorkingTreeClean => "Working tree clean",
+            Text::FileChanged => "● 1 file changed",
+            Text::FilesChanged => "● {count} files changed",
+            Text::Ticket => "Ticket {ticket}",
+            Text::PullRequestOpen => "PR #{number} open",
+            Text::ViewRepository => "View Repository",
+            Text::OpenTicket => "Open {ticket}",
+            Text::OtherProject => "and 1 other project",
+            Text::OtherProjects => "and {count} other projects",
+        }
+    }
+
+    /// Returns the German text
+    fn german(&self) -> &'static str {
+        match self {
+            Text::Idle => "Inaktiv",
+            Text::DetailsTemplate => "Arbeitet an {file}",
+            Text::StateTemplate => "in {project}",
+            Text::BrowsingTemplate => "Stöbert in {project}",
+            Text::AFile => "einer Datei",
+            Text::AProject => "einem Projekt",
+            Text::ProjectCodename => "Projekt {codename}",
+            Text::CodingInXcode => "Programmiert in Xcode",
+            Text::ReadingDocumentation => "Liest Dokumentation",
+            Text::Committing => "Committet Änderungen an {project}",
+            Text::Reviewing => "Prüft Änderungen in {project}",
+            Text::Designing => "Gestaltet {file}",
+            Text::SwitchingFiles => "Wechselt zwischen {count} Dateien",
+            Text::EditingPlayground => "Bearbeitet einen Playground",
+            Text::EditingPlaygroundPage => "Bearbeitet {page}",
+            Text::RunningPlayground => "Führt einen Playground aus",
+            Text::RunningPlaygroundPage => "Führt {page} aus",
+            Text::ComposingScene => "Gestaltet eine RealityKit-Szene",
+            Text::TrainingModel => "Trainiert ein Modell in Create ML",
+            Text::TestingInSimulator => "Testet {project} im Simulator",
+            Text::TestingInSimulatorOn => "Testet {project} im Simulator ({device})",
+            Text::RunningOnDevice => "Läuft auf {device}",
+            Text::ADevice => "einem Gerät",
+            Text::Archiving => "Archiviert {project} für die Veröffentlichung 🚀",
+            Text::Building => "Baut…",
+            Text::BuildingFor => "Baut für {platform}…",
+            Text::Indexing => "Indiziert…",
+            Text::ResolvingPackages => "Löst Paketgraph auf…",
+            Text::BuildingFromCli => "Baut über die Kommandozeile",
+            Text::BuildSucceeded => "Build erfolgreich ✅",
+            Text::BuildFailed => "Build fehlgeschlagen ❌",
+            Text::BuildFailedError => "Build fehlgeschlagen ❌ (1 Fehler)",
+            Text::BuildFailedErrors => "Build fehlgeschlagen ❌ ({errors} Fehler)",
+            Text::BuildCancelled => "Build abgebrochen",
+            Text::RunningTests => "Führt Tests aus ({finished}/{total})…",
+            Text::RunningTestsCount => "Führt Tests aus ({finished})…",
+            Text::TestsPassed => "Tests bestanden ✅ ({passed})",
+            Text::TestsFailed => "Tests fehlgeschlagen ❌ ({failed} von {total})",
+            Text::Error => "1 Fehler",
+            Text::Errors => "{count} Fehler",
+            Text::Warning => "1 Warnung",
+            Text::Warnings => "{count} Warnungen",
+            Text::OnBreak => "In der Pause, zurück in {countdown}",
+            Text::Focus => "Fokus, noch {countdown}",
+            Text::Pairing => "👥 Pair-Programming",
+            Text::PairingWith => "👥 Pair-Programming mit {partner}",
+            Text::Writing => "Schreibt {language}",
+            Text::OnBranch => "Auf {branch}",
+            Text::Session => "Sitzung: {duration}",
+            Text::SessionSummary => "Du hast heute {duration} an {project} gearbeitet",
+            Text::DailyGoal => "{done} / {goal} heute",
+            Text::WorkingTreeClean => "Keine Änderungen",
+            Text::FileChanged => "● 1 Datei geändert",
+            Text::FilesChanged => "● {count} Dateien geändert",
+            Text::Ticket => "Ticket {ticket}",
+            Text::PullRequestOpen => "PR #{number} offen",
+            Text::ViewRepository => "Repository ansehen",
+            Text::OpenTicket => "{ticket} öffnen",
+            Text::OtherProject => "und 1 weiteres Projekt",
+            Text::OtherProjects => "und {count} weitere Projekte",
+        }
+    }
+
+    /// Returns the built-in translation into `language`, if any
+    fn builtin(&self, language: &str) -> Option<&'static str> {
+        match language {
+            "en" => Some(self.english()),
+            "de" => Some(self.german()),
+            _ => None,
+        }
+    }
+}
+
+/// Texts of the presence in the configured language. User translations take
+/// precedence over the built-in ones, and English fills any gap.
+#[derive(Debug, Clone)]
+pub struct Locale {
+    language: String,
+    translations: HashMap<Text, String>,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale {
+            language: String::from("en"),
+            translations: HashMap::new(),
+        }
+    }
+}
+
+impl Locale {
+    /// Creates the locale of `language`, or of the system locale when it is
+    /// `None`, with the user `translations` keyed by language code
+    pub fn new(
+        language: Option<&str>,
+        translations: &HashMap<String, HashMap<Text, String>>,
+    ) -> Self {
+        let language = language
+            .map(String::from)
+            .or_else(system_locale)
+            .map(|locale| language_code(&locale))
+            .unwrap_or_else(|| String::from("en"));
+        Locale {
+            translations: translations.get(&language).cloned().unwrap_or_default(),
+            language,
+        }
+    }
+
+    /// Returns `text` in the locale's language
+    pub fn text(&self, text: Text) -> &str {
+        self.translations
+            .get(&text)
+            .map(String::as_str)
+            .or_else(|| text.builtin(&self.language))
+            .unwrap_or_else(|| text.english())
+    }
+
+    /// Returns `text` with its `placeholders` replaced
+    pub fn format(&self, text: Text, placeholders: &[(&str, &str)]) -> String {
+        render_template(self.text(text), placeholders)
+    }
+}
+
+/// Reads the locale of the user from macOS, e.g. `de_DE`, falling back to `LANG`
+fn system_locale() -> Option<String> {
+    Command::new("defaults")
+        .args(["read", "-g", "AppleLocale"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|locale| !locale.is_empty())
+        .or_else(|| std::env::var("LANG").ok())
+}
+
+/// Returns the lower case language code of a locale such as `de_DE.UTF-8` or `pt-BR`
+fn language_code(locale: &str) -> String {
+    locale
+        .split(['_', '-', '.', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every text, in declaration order
+    const ALL: [Text; 60] = [
+        Text::Idle,
+        Text::DetailsTemplate,
+        Text::StateTemplate,
+        Text::BrowsingTemplate,
+        Text::AFile,
+        Text::AProject,
+        Text::ProjectCodename,
+        Text::CodingInXcode,
+        Text::ReadingDocumentation,
+        Text::Committing,
+        Text::Reviewing,
+        Text::Designing,
+        Text::SwitchingFiles,
+        Text::EditingPlayground,
+        Text::EditingPlaygroundPage,
+        Text::RunningPlayground,
+        Text::RunningPlaygroundPage,
+        Text::ComposingScene,
+        Text::TrainingModel,
+        Text::TestingInSimulator,
+        Text::TestingInSimulatorOn,
+        Text::RunningOnDevice,
+        Text::ADevice,
+        Text::Archiving,
+        Text::Building,
+        Text::BuildingFor,
+        Text::Indexing,
+        Text::ResolvingPackages,
+        Text::BuildingFromCli,
+        Text::BuildSucceeded,
+        Text::BuildFailed,
+        Text::BuildFailedError,
+        Text::BuildFailedErrors,
+        Text::BuildCancelled,
+        Text::RunningTests,
+        Text::RunningTestsCount,
+        Text::TestsPassed,
+        Text::TestsFailed,
+        Text::Error,
+        Text::Errors,
+        Text::Warning,
+        Text::Warnings,
+        Text::OnBreak,
+        Text::Focus,
+        Text::Pairing,
+        Text::PairingWith,
+        Text::Writing,
+        Text::OnBranch,
+        Text::Session,
+        Text::SessionSummary,
+        Text::DailyGoal,
+        Text::WorkingTreeClean,
+        Text::FileChanged,
+        Text::FilesChanged,
+        Text::Ticket,
+        Text::PullRequestOpen,
+        Text::ViewRepository,
+        Text::OpenTicket,
+        Text::OtherProject,
+        Text::OtherProjects,
+    ];
+
+    #[test]
+    fn parses_language_codes() {
+        assert_eq!(language_code("de_DE"), "de");
+        assert_eq!(language_code("en_US.UTF-8"), "en");
+        assert_eq!(language_code("pt-BR"), "pt");
+        assert_eq!(language_code("FR"), "fr");
+    }
+
+    #[test]
+    fn prefers_user_translations_over_builtin_and_english() {
+        let translations = HashMap::from([(
+            String::from("de"),
+            HashMap::from([(Text::Idle, String::from("Abwesend"))]),
+        )]);
+        let locale = Locale::new(Some("de_AT"), &translations);
+        assert_eq!(locale.text(Text::Idle), "Abwesend");
+        assert_eq!(locale.text(Text::AFile), "einer Datei");
+
+        let locale = Locale::new(Some("xx"), &translations);
+        assert_eq!(locale.text(Text::Idle), "Idle");
+    }
+
+    #[test]
+    fn keeps_placeholders_in_translations() {
+        for text in ALL {
+            let placeholders = |text: &str| text.matches('{').count();
+            assert_eq!(
+                placeholders(text.german()),
+                placeholders(text.english()),
+                "{text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn schema_lists_every_text() {
+        let keys: Vec<Text> = crate::schema::TEXTS
+            .iter()
+            .map(|key| serde_json::from_value(serde_json::json!(key)).unwrap())
+            .collect();
+        assert_eq!(keys, ALL);
+    }
+
+    #[test]
+    fn formats_placeholders() {
+        let locale = Locale::default();
+        assert_eq!(
+            locale.format(Text::BuildFailedErrors, &[("errors", "3")]),
+            "Build failed ❌ (3 errors)"
+        );
+    }
+}
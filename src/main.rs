@@ -1,51 +1,222 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+
+use discord_rich_presence::DiscordIpc;
 use simple_logger::SimpleLogger;
 
+mod activity_model;
+mod build_log;
+mod cli;
+mod commands;
+mod companion_apps;
+mod completions;
 mod config;
+mod control;
+mod daemon;
+mod derived_data;
 mod error;
+mod event_log;
+mod export;
+mod instance;
+mod integrations;
+mod locale;
+mod migration;
+mod os_log;
+mod pairing;
+mod pomodoro;
+mod presence_timer;
+mod project_config;
+mod report;
+mod schedule;
+mod schema;
+mod simulation;
+mod stats;
+mod test_log;
+#[cfg(test)]
+mod testing;
+mod update;
 mod utils;
+mod verify_assets;
+mod watchdog;
+mod xcode;
 mod xcode_state;
 
 use config::AppConfig;
+use control::SharedControlState;
 #[allow(unused)]
 pub use error::{Error, Result};
-use utils::{init_discord_ipc, sleep};
+use simulation::SimulatedXcode;
+use utils::{
+    clock::{Clock, SystemClock},
+    init_discord_ipc, sleep,
+};
+use xcode::{AppleScript, Xcode};
 use xcode_state::XcodeState;
 
 fn main() -> Result<()> {
-    #[cfg(debug_assertions)]
-    SimpleLogger::new()
-        .with_level(log::LevelFilter::Debug)
-        .init()?;
+    let matches = cli::get_matches();
 
-    #[cfg(not(debug_assertions))]
-    SimpleLogger::new()
-        .with_level(log::LevelFilter::Info)
-        .init()?;
+    let level = if cfg!(debug_assertions) {
+        log::LevelFilter::Debug
+    } else {
+        log::LevelFilter::Info
+    };
+    if matches.get_flag(cli::OS_LOG_ARG_ID) {
+        os_log::init(level)?;
+    } else if matches.get_flag(cli::EVENTS_ARG_ID) {
+        // Logs would interleave with the JSON lines on stdout
+        SimpleLogger::new()
+            .with_level(log::LevelFilter::Off)
+            .init()?;
+    } else {
+        SimpleLogger::new().with_level(level).init()?;
+    }
 
-    let config = AppConfig::new()?;
+    if let Some((name, args)) = matches.subcommand() {
+        return commands::run(name, args, &matches);
+    }
+
+    migration::migrate_config()?;
+    let config = AppConfig::new(&matches)?;
+    let simulation = matches
+        .get_one::<PathBuf>(cli::SIMULATE_ARG_ID)
+        .map(|path| SimulatedXcode::load(path))
+        .transpose()?;
+    // The lock is taken before forking so that a second instance fails in
+    // the terminal, the flock is inherited by the child
+    let mut instance_lock = instance::acquire()?;
+    if matches.get_flag(cli::DAEMON_ARG_ID) {
+        daemon::daemonize()?;
+        instance_lock.write_pid()?;
+    }
+    // Log panics of every thread instead of printing them to stderr
+    panic::set_hook(Box::new(|info| log::error!("{}", info)));
+    let control = control::start(Arc::new(SystemClock))?;
+    watchdog::spawn();
+    if config.update_check {
+        update::spawn_update_check(config.update_check_interval);
+    }
 
     log::info!("Starting xcode-discord-rpc");
 
     loop {
-        if let Err(err) = discord_rpc(&config) {
-            log::error!("{}", err);
-            log::debug!("Trying to reconnect...");
-            sleep(config.update_interval)
-        }
-        sleep(config.update_interval)
+        let wait = match discord_rpc(&config, &control, simulation.as_ref()) {
+            Err(Error::Panic(_)) => {
+                log::info!("Restarting after {}s", PANIC_COOLDOWN.as_secs());
+                PANIC_COOLDOWN
+            }
+            Err(err) => {
+                log::error!("{}", err);
+                log::debug!("Trying to reconnect...");
+                Duration::from_secs(config.update_interval)
+            }
+            Ok(()) => Duration::from_secs(config.update_interval),
+        };
+        sleep(wait);
     }
 
     #[allow(unreachable_code)]
     Ok(())
 }
 
-fn discord_rpc(config: &AppConfig) -> Result<()> {
-    let mut client = init_discord_ipc()?;
+/// Time to wait before restarting the loop after a panic
+const PANIC_COOLDOWN: Duration = Duration::from_secs(30);
 
-    let mut xcode_state = XcodeState::new(config, &mut client);
+/// Runs the presence loop until it fails. Xcode is queried with AppleScript
+/// unless a `simulation` replaces it.
+fn discord_rpc(
+    config: &AppConfig,
+    control: &SharedControlState,
+    simulation: Option<&SimulatedXcode>,
+) -> Result<()> {
+    let xcode: Box<dyn Xcode> = match simulation {
+        Some(simulation) => Box::new(simulation.clone()),
+        None => Box::new(AppleScript),
+    };
+    supervise(config, control, Box::new(SystemClock), xcode)
+}
 
-    xcode_state.run()?;
+/// Runs the presence loop on `clock` and `xcode` until it fails. A panic in
+/// the loop is caught and the presence cleared, so that the loop can be
+/// restarted.
+fn supervise(
+    config: &AppConfig,
+    control: &SharedControlState,
+    clock: Box<dyn Clock>,
+    xcode: Box<dyn Xcode>,
+) -> Result<()> {
+    let mut client = init_discord_ipc(&config.application_id)?;
 
-    #[allow(unreachable_code)]
-    Ok(())
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        XcodeState::new(config, &mut client, control.clone(), clock, xcode).run()
+    }));
+
+    result.unwrap_or_else(|payload| {
+        // The panic itself was logged by the panic hook
+        if let Err(err) = client.clear_activity() {
+            log::debug!("Failed to clear the presence after a panic: {}", err);
+        }
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Err(Error::Panic(message))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use serde_json::Value;
+
+    use super::*;
+    use crate::{
+        simulation::Scene,
+        testing::{config, discord_server::FakeDiscord, scripted_xcode::ScriptedXcode},
+        utils::clock::SimulatedClock,
+    };
+
+    /// A simulated clock panicking when the loop first waits for the refresh
+    #[derive(Debug)]
+    struct PanickingClock(SimulatedClock);
+
+    impl Clock for PanickingClock {
+        fn now(&self) -> i64 {
+            self.0.now()
+        }
+
+        fn instant(&self) -> Instant {
+            self.0.instant()
+        }
+
+        fn sleep(&self, _duration: Duration) -> Option<u64> {
+            panic!("probe failed");
+        }
+    }
+
+    #[test]
+    fn clears_presence_after_panic() {
+        let config = config("language = \"en\"\n");
+        let discord = FakeDiscord::start();
+        let result = supervise(
+            &config,
+            &SharedControlState::default(),
+            Box::new(PanickingClock(SimulatedClock::new())),
+            Box::new(ScriptedXcode::new([Some(Scene::editing(
+                "Panicking",
+                "main.swift",
+            ))])),
+        );
+        assert!(matches!(result, Err(Error::Panic(message)) if message == "probe failed"));
+        let activities = discord.stop();
+        assert_eq!(activities.len(), 2);
+        assert_eq!(activities[0]["details"], "Working on main.swift");
+        assert_eq!(activities[1], Value::Null);
+    }
 }
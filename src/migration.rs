@@ -0,0 +1,311 @@
+use std::{fs, io::ErrorKind, path::Path};
+
+use toml_edit::{value, DocumentMut, Item, Table, TableLike, Value};
+
+use crate::{config::config_path, Result};
+
+/// Key of the configuration file holding its version
+pub const VERSION_KEY: &str = "config_version";
+
+/// Version of the configuration file understood by this release, files
+/// without `config_version` are version 1
+pub const CONFIG_VERSION: i64 = 2;
+
+/// Changes applied to the configuration file when upgrading to a version,
+/// ordered by version
+const MIGRATIONS: &[Migration] = &[Migration {
+    version: 2,
+    changes: &[Change::Derive {
+        from: "xcode_check_cycle",
+        to: "xcode_check_interval",
+        derive: check_cycle_to_interval,
+    }],
+}];
+
+/// Turns the number of `update_interval` cycles between two checks whether
+/// Xcode is running into the duration they lasted
+fn check_cycle_to_interval(cycles: i64, table: &dyn TableLike) -> Value {
+    const DEFAULT_UPDATE_INTERVAL: i64 = 30;
+    let update_interval = table
+        .get("update_interval")
+        .and_then(Item::as_integer)
+        .unwrap_or(DEFAULT_UPDATE_INTERVAL);
+    format!("{}s", cycles * update_interval).into()
+}
+
+/// Changes bringing the configuration file to `version`
+#[derive(Debug)]
+pub struct Migration {
+    pub version: i64,
+    pub changes: &'static [Change],
+}
+
+/// A change of an option between two versions, keys are dotted paths
+#[derive(Debug)]
+// Constructed by the migrations of future versions
+#[allow(dead_code)]
+pub enum Change {
+    /// The option `from` is now called `to`
+    Rename {
+        from: &'static str,
+        to: &'static str,
+    },
+    /// The integer option `key` now uses another unit
+    Convert {
+        key: &'static str,
+        convert: fn(i64) -> i64,
+    },
+    /// The integer option `from` is replaced by `to`, whose value is derived
+    /// from it and the other options of its table
+    Derive {
+        from: &'static str,
+        to: &'static str,
+        derive: fn(i64, &dyn TableLike) -> Value,
+    },
+}
+
+impl Change {
+    /// Applies the change to the options of `table`
+    fn apply(&self, table: &mut dyn TableLike) {
+        match self {
+            Change::Rename { from, to } => {
+                let (from_path, from_key) = split_path(from);
+                let Some(parent) = parent_table(table, &from_path, false) else {
+                    return;
+                };
+                // Comments above the option belong to its key
+                let decor = parent
+                    .get_key_value(from_key)
+                    .map(|(key, _)| key.leaf_decor().clone());
+                let Some(item) = parent.remove(from_key) else {
+                    return;
+                };
+                let (to_path, to_key) = split_path(to);
+                let Some(parent) = parent_table(table, &to_path, true) else {
+                    log::warn!("Dropping `{from}`, `{to}` is not a table");
+                    return;
+                };
+                if parent.contains_key(to_key) {
+                    log::warn!("Dropping `{from}`, `{to}` is already set");
+                } else {
+                    parent.insert(to_key, item);
+                    if let (Some((mut key, _)), Some(decor)) =
+                        (parent.get_key_value_mut(to_key), decor)
+                    {
+                        *key.leaf_decor_mut() = decor;
+                    }
+                }
+            }
+            Change::Convert { key, convert } => {
+                let (path, key) = split_path(key);
+                let Some(value) = parent_table(table, &path, false)
+                    .and_then(|parent| parent.get_mut(key))
+                    .and_then(Item::as_value_mut)
+                else {
+                    return;
+                };
+                if let Some(integer) = value.as_integer() {
+                    let decor = value.decor().clone();
+                    *value = convert(integer).into();
+                    *value.decor_mut() = decor;
+                }
+            }
+            Change::Derive { from, to, derive } => {
+                let (path, key) = split_path(from);
+                let Some(parent) = parent_table(table, &path, false) else {
+                    return;
+                };
+                let Some(integer) = parent.get(key).and_then(Item::as_integer) else {
+                    return;
+                };
+                let derived = derive(integer, parent);
+                if let Some(value) = parent.get_mut(key).and_then(Item::as_value_mut) {
+                    let decor = value.decor().clone();
+                    *value = derived;
+                    *value.decor_mut() = decor;
+                }
+                Change::Rename { from, to }.apply(table);
+            }
+        }
+    }
+}
+
+/// Splits a dotted path into the path of its table and its key
+fn split_path(path: &str) -> (Vec<&str>, &str) {
+    let mut segments = path.split('.').collect::<Vec<_>>();
+    let key = segments.pop().unwrap_or_default();
+    (segments, key)
+}
+
+/// Returns the table at `path` below `table`, creating the missing tables
+/// when `create` is set
+fn parent_table<'t>(
+    table: &'t mut dyn TableLike,
+    path: &[&str],
+    create: bool,
+) -> Option<&'t mut dyn TableLike> {
+    let Some((first, rest)) = path.split_first() else {
+        return Some(table);
+    };
+    if create && !table.contains_key(first) {
+        table.insert(first, Item::Table(Table::new()));
+    }
+    let child = table.get_mut(first)?.as_table_like_mut()?;
+    parent_table(child, rest, create)
+}
+
+/// Returns the version of a parsed configuration file
+fn version(document: &DocumentMut) -> i64 {
+    document
+        .get(VERSION_KEY)
+        .and_then(Item::as_integer)
+        .unwrap_or(1)
+}
+
+/// Applies the `migrations` newer than the version of `document` to it and
+/// to its profiles, returning whether it changed
+fn migrate(document: &mut DocumentMut, migrations: &[Migration]) -> bool {
+    let pending = migrations
+        .iter()
+        .filter(|migration| migration.version > version(document))
+        .collect::<Vec<_>>();
+    let Some(latest) = pending.last().map(|migration| migration.version) else {
+        return false;
+    };
+    for change in pending.iter().flat_map(|migration| migration.changes) {
+        change.apply(document.as_table_mut());
+        if let Some(profiles) = document
+            .get_mut("profile")
+            .and_then(Item::as_table_like_mut)
+        {
+            for (_, profile) in profiles.iter_mut() {
+                if let Some(profile) = profile.as_table_like_mut() {
+                    change.apply(profile);
+                }
+            }
+        }
+    }
+    document.insert(VERSION_KEY, value(latest));
+    true
+}
+
+/// Migrates the configuration file at `path` to the current version,
+/// keeping the previous file next to it
+fn migrate_file(path: &Path, migrations: &[Migration]) -> Result<()> {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    // Syntax errors are reported when the configuration is loaded
+    let Ok(mut document) = source.parse::<DocumentMut>() else {
+        return Ok(());
+    };
+    let version = version(&document);
+    if version > CONFIG_VERSION {
+        log::warn!(
+            "{} is version {version} from a newer release, options unknown to version {CONFIG_VERSION} are ignored",
+            path.display()
+        );
+        return Ok(());
+    }
+    if !migrate(&mut document, migrations) {
+        return Ok(());
+    }
+    let backup = path.with_extension(format!("toml.v{version}.bak"));
+    fs::copy(path, &backup)?;
+    fs::write(path, document.to_string())?;
+    log::info!(
+        "Migrated {} to version {}, the previous file is kept at {}",
+        path.display(),
+        self::version(&document),
+        backup.display()
+    );
+    Ok(())
+}
+
+/// Migrates the user configuration file to the current version
+pub fn migrate_config() -> Result<()> {
+    match config_path() {
+        Some(path) => migrate_file(&path, MIGRATIONS),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIGRATIONS: &[Migration] = &[
+        Migration {
+            version: 2,
+            changes: &[Change::Rename {
+                from: "hide_name",
+                to: "hide_file",
+            }],
+        },
+        Migration {
+            version: 3,
+            changes: &[
+                Change::Rename {
+                    from: "mqtt_host",
+                    to: "mqtt.host",
+                },
+                Change::Convert {
+                    key: "pomodoro.work",
+                    convert: |minutes| minutes * 60,
+                },
+            ],
+        },
+    ];
+
+    fn migrated(source: &str) -> Option<String> {
+        let mut document = source.parse::<DocumentMut>().unwrap();
+        migrate(&mut document, MIGRATIONS).then(|| document.to_string())
+    }
+
+    #[test]
+    fn renames_and_converts_options() {
+        let source = "# Private\nhide_name = true\nmqtt_host = \"broker\"\n\n[pomodoro]\nwork = 50 # minutes\n";
+        assert_eq!(
+            migrated(source).unwrap(),
+            "# Private\nhide_file = true\nconfig_version = 3\n\n[pomodoro]\nwork = 3000 # minutes\n\n[mqtt]\nhost = \"broker\"\n"
+        );
+    }
+
+    #[test]
+    fn applies_only_newer_migrations() {
+        let source = "config_version = 2\nhide_name = true\n";
+        assert_eq!(
+            migrated(source).unwrap(),
+            "config_version = 3\nhide_name = true\n"
+        );
+        assert_eq!(migrated("config_version = 3\nhide_name = true\n"), None);
+    }
+
+    #[test]
+    fn migrates_profiles() {
+        let source = "[profile.work]\nhide_name = true\n";
+        assert!(migrated(source)
+            .unwrap()
+            .contains("[profile.work]\nhide_file = true\n"));
+    }
+
+    #[test]
+    fn keeps_existing_options() {
+        let source = "hide_name = true\nhide_file = false\n";
+        assert!(migrated(source).unwrap().starts_with("hide_file = false\n"));
+    }
+
+    #[test]
+    fn replaces_check_cycle_with_interval() {
+        let mut document = "update_interval = 10\nxcode_check_cycle = 6 # cycles\n"
+            .parse::<DocumentMut>()
+            .unwrap();
+        assert!(migrate(&mut document, super::MIGRATIONS));
+        assert_eq!(
+            document.to_string(),
+            "update_interval = 10\nxcode_check_interval = \"60s\" # cycles\nconfig_version = 2\n"
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use std::{collections::HashMap, ffi::CString, sync::Mutex};
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+use crate::Result;
+
+/// Subsystem of the messages in the unified logging system, to filter them
+/// in Console.app or with `log stream --predicate 'subsystem == "…"'`
+pub const SUBSYSTEM: &str = "dev.n0va.xcode-discord-rpc";
+
+/// Category of the messages logged outside of a module
+const MAIN_CATEGORY: &str = "main";
+
+/// Returns the category of a log target, the module path without the crate name
+fn category(target: &str) -> &str {
+    target
+        .split_once("::")
+        .map_or(MAIN_CATEGORY, |(_, module)| module)
+}
+
+#[cfg(target_os = "macos")]
+mod sys {
+    use std::ffi::{c_char, c_void, CStr};
+
+    use log::Level;
+
+    /// `os_log_t`, an opaque log object
+    type OsLog = *mut c_void;
+
+    const OS_LOG_TYPE_DEFAULT: u8 = 0x00;
+    const OS_LOG_TYPE_INFO: u8 = 0x01;
+    const OS_LOG_TYPE_DEBUG: u8 = 0x02;
+    const OS_LOG_TYPE_ERROR: u8 = 0x10;
+
+    extern "C" {
+        /// Header of the executable, the format string is stored relative to it
+        #[allow(non_upper_case_globals)]
+        static __dso_handle: c_void;
+
+        fn os_log_create(subsystem: *const c_char, category: *const c_char) -> OsLog;
+
+        fn _os_log_impl(
+            dso: *const c_void,
+            log: OsLog,
+            log_type: u8,
+            format: *const c_char,
+            buf: *const u8,
+            size: u32,
+        );
+    }
+
+    /// Format of every message, placed where the `os_log` macro puts it so
+    /// that Console.app can read it from the executable
+    #[link_section = "__TEXT,__oslogstring,cstring_literals"]
+    static FORMAT: [u8; 11] = *b"%{public}s\0";
+
+    /// A log object of the subsystem and a category
+    pub struct Handle(OsLog);
+
+    // SAFETY: log objects are immutable and may be used from any thread
+    unsafe impl Send for Handle {}
+    unsafe impl Sync for Handle {}
+
+    impl Handle {
+        pub fn new(subsystem: &CStr, category: &CStr) -> Self {
+            // SAFETY: both strings are valid and NUL-terminated, the returned
+            // object is never released
+            Self(unsafe { os_log_create(subsystem.as_ptr(), category.as_ptr()) })
+        }
+
+        pub fn write(&self, level: Level, message: &CStr) {
+            let log_type = match level {
+                Level::Error => OS_LOG_TYPE_ERROR,
+                Level::Warn => OS_LOG_TYPE_DEFAULT,
+                Level::Info => OS_LOG_TYPE_INFO,
+                Level::Debug | Level::Trace => OS_LOG_TYPE_DEBUG,
+            };
+            // Arguments of the format as laid out by the `os_log` macro: a
+            // summary byte, the number of arguments, then the descriptor and
+            // size of each argument followed by its value
+            let mut buf = [0u8; 12];
+            buf[0] = 0x02; // has non-scalar arguments
+            buf[1] = 1;
+            buf[2] = 0x22; // public string
+            buf[3] = 8;
+            buf[4..].copy_from_slice(&(message.as_ptr() as u64).to_ne_bytes());
+            // SAFETY: the format and the buffer describe a single pointer to
+            // `message`, which outlives the call
+            unsafe {
+                _os_log_impl(
+                    &raw const __dso_handle,
+                    self.0,
+                    log_type,
+                    FORMAT.as_ptr().cast(),
+                    buf.as_ptr(),
+                    buf.len() as u32,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod sys {
+    use std::ffi::CStr;
+
+    use log::Level;
+
+    /// Stand-in for the unified logging system, writing to stderr
+    pub struct Handle;
+
+    impl Handle {
+        pub fn new(_subsystem: &CStr, _category: &CStr) -> Self {
+            Self
+        }
+
+        pub fn write(&self, level: Level, message: &CStr) {
+            eprintln!("{level:<5} {}", message.to_string_lossy());
+        }
+    }
+}
+
+/// Logger writing to Apple's unified logging system, with a category per
+/// module
+struct OsLogger {
+    level: LevelFilter,
+    handles: Mutex<HashMap<String, sys::Handle>>,
+}
+
+impl Log for OsLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let Ok(message) = CString::new(record.args().to_string().replace('\0', "")) else {
+            return;
+        };
+        let category = category(record.target());
+        let mut handles = self
+            .handles
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let handle = handles.entry(category.to_string()).or_insert_with(|| {
+            let subsystem = CString::new(SUBSYSTEM).expect("subsystem has no NUL");
+            let category = CString::new(category).unwrap_or_default();
+            sys::Handle::new(&subsystem, &category)
+        });
+        handle.write(record.level(), &message);
+    }
+
+    fn flush(&self) {}
+}
+
+/// Installs the unified logging system as the logger, logging up to `level`
+pub fn init(level: LevelFilter) -> Result<()> {
+    log::set_boxed_logger(Box::new(OsLogger {
+        level,
+        handles: Mutex::new(HashMap::new()),
+    }))?;
+    log::set_max_level(level);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use log::Level;
+
+    use super::*;
+
+    #[test]
+    fn categorizes_by_module() {
+        assert_eq!(category("xcode_discord_rpc"), "main");
+        assert_eq!(
+            category("xcode_discord_rpc::integrations::slack"),
+            "integrations::slack"
+        );
+    }
+
+    #[test]
+    fn checks_level() {
+        let logger = OsLogger {
+            level: LevelFilter::Info,
+            handles: Mutex::new(HashMap::new()),
+        };
+        let metadata = |level| Metadata::builder().level(level).build();
+        assert!(logger.enabled(&metadata(Level::Warn)));
+        assert!(!logger.enabled(&metadata(Level::Debug)));
+    }
+}
@@ -0,0 +1,58 @@
+use serde::Deserialize;
+
+use crate::locale::{Locale, Text};
+
+/// How the pair programming session started with the `pair` command is shown
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PairingConfig {
+    /// Who the session is with, shown as "👥 Pairing with {partner}"
+    pub partner: Option<String>,
+    /// Number of people in the session, shown as "(2 of 2)"
+    pub size: u32,
+}
+
+impl Default for PairingConfig {
+    fn default() -> Self {
+        Self {
+            partner: None,
+            size: 2,
+        }
+    }
+}
+
+impl PairingConfig {
+    /// Returns the second line of the presence while pairing
+    pub fn state(&self, locale: &Locale) -> String {
+        match self
+            .partner
+            .as_deref()
+            .filter(|partner| !partner.is_empty())
+        {
+            Some(partner) => locale.format(Text::PairingWith, &[("partner", partner)]),
+            None => locale.text(Text::Pairing).to_string(),
+        }
+    }
+
+    /// Returns the ID and the size of the Discord party of a session started
+    /// at `started_at`
+    pub fn party(&self, started_at: i64) -> (String, u32) {
+        let size = self.size.max(1);
+        (format!("xcode-discord-rpc-pairing-{started_at}"), size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mentions_partner() {
+        let locale = Locale::default();
+        let mut config = PairingConfig::default();
+        assert_eq!(config.state(&locale), "👥 Pairing");
+        config.partner = Some("Alex".to_string());
+        assert_eq!(config.state(&locale), "👥 Pairing with Alex");
+        assert_eq!(config.party(1).1, 2);
+    }
+}
@@ -0,0 +1,83 @@
+use serde::Deserialize;
+
+/// Lengths of the pomodoro intervals and how breaks are shown
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PomodoroConfig {
+    /// Length of a focus interval in minutes
+    pub work_minutes: u64,
+    /// Length of a break in minutes
+    pub break_minutes: u64,
+    /// Whether to leave the project out of the break card
+    pub clear_details_on_break: bool,
+}
+
+impl Default for PomodoroConfig {
+    fn default() -> Self {
+        Self {
+            work_minutes: 25,
+            break_minutes: 5,
+            clear_details_on_break: true,
+        }
+    }
+}
+
+/// Current interval of a running pomodoro with the seconds left in it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PomodoroPhase {
+    Work { remaining: u64 },
+    Break { remaining: u64 },
+}
+
+impl PomodoroConfig {
+    /// Returns the interval a pomodoro started at `started_at` is in at
+    /// `now`. Focus intervals and breaks alternate until the pomodoro is stopped.
+    pub fn phase(&self, started_at: i64, now: i64) -> PomodoroPhase {
+        let work = self.work_minutes.max(1) * 60;
+        let pause = self.break_minutes * 60;
+        let elapsed = (now - started_at).max(0) as u64 % (work + pause);
+        if elapsed < work {
+            PomodoroPhase::Work {
+                remaining: work - elapsed,
+            }
+        } else {
+            PomodoroPhase::Break {
+                remaining: work + pause - elapsed,
+            }
+        }
+    }
+}
+
+/// Formats seconds as a countdown such as "17:32"
+pub fn format_countdown(seconds: u64) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alternates_work_and_breaks() {
+        let config = PomodoroConfig::default();
+        assert_eq!(config.phase(0, 0), PomodoroPhase::Work { remaining: 1500 });
+        assert_eq!(
+            config.phase(0, 1500 - 1052),
+            PomodoroPhase::Work { remaining: 1052 }
+        );
+        assert_eq!(
+            config.phase(0, 1560),
+            PomodoroPhase::Break { remaining: 240 }
+        );
+        assert_eq!(
+            config.phase(0, 1800 + 60),
+            PomodoroPhase::Work { remaining: 1440 }
+        );
+    }
+
+    #[test]
+    fn formats_countdown() {
+        assert_eq!(format_countdown(1052), "17:32");
+        assert_eq!(format_countdown(59), "0:59");
+    }
+}
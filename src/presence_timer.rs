@@ -0,0 +1,234 @@
+use std::time::{Duration, Instant};
+
+use crate::utils::clock::Clock;
+
+/// Highest multiplier of the sleep between two checks while Xcode or
+/// Discord is not running
+const MAX_BACKOFF: u64 = 8;
+
+/// Elapsed time, idle detection and backoff of the presence loop, driven by
+/// a `Clock` so that they can be tested without waiting
+pub struct PresenceTimer {
+    clock: Box<dyn Clock>,
+    /// When the current session started, in seconds since the UNIX epoch
+    started_at: i64,
+    /// When Xcode was last frontmost, in seconds since the UNIX epoch
+    last_frontmost_at: i64,
+    /// When Xcode became frontmost again after the last absence
+    frontmost_since: i64,
+    /// When the current absence from Xcode started, if Xcode is not frontmost
+    away_since: Option<i64>,
+    /// Seconds the last absence lasted, counted once the next one starts
+    last_absence: i64,
+    /// Seconds of earlier absences counting toward idle
+    away_for: i64,
+    /// Multiplier used to progressively increase sleep duration when Xcode or
+    /// Discord is not running. This helps reduce CPU usage when idle.
+    backoff: u64,
+    /// Seconds the system was asleep during the last sleep, if it was
+    system_slept_for: Option<u64>,
+}
+
+impl PresenceTimer {
+    pub fn new(clock: Box<dyn Clock>) -> Self {
+        let now = clock.now();
+        Self {
+            clock,
+            started_at: now,
+            last_frontmost_at: now,
+            frontmost_since: now,
+            away_since: None,
+            last_absence: 0,
+            away_for: 0,
+            backoff: 1,
+            system_slept_for: None,
+        }
+    }
+
+    /// Returns the current time in seconds since the UNIX epoch
+    pub fn now(&self) -> i64 {
+        self.clock.now()
+    }
+
+    /// Returns the current time of the monotonic clock
+    pub fn instant(&self) -> Instant {
+        self.clock.instant()
+    }
+
+    /// Sleeps for `duration`, returning the number of seconds the Mac spent
+    /// asleep if it was. Waking up resets the backoff.
+    pub fn sleep(&mut self, duration: Duration) -> Option<u64> {
+        let slept_for = self.clock.sleep(duration)?;
+        log::info!("Woke up from system sleep after {}s", slept_for);
+        self.reset_backoff();
+        self.system_slept_for = Some(slept_for);
+        Some(slept_for)
+    }
+
+    /// When the current session started, in seconds since the UNIX epoch
+    pub fn started_at(&self) -> i64 {
+        self.started_at
+    }
+
+    /// Seconds since the current session started
+    pub fn elapsed(&self) -> u64 {
+        (self.now() - self.started_at).max(0) as u64
+    }
+
+    /// Starts a new session now
+    pub fn restart(&mut self) {
+        self.started_at = self.now();
+    }
+
+    /// Starts a new session when the Mac slept for more than `threshold`
+    /// seconds since the last call
+    pub fn restart_after_wake(&mut self, threshold: u64) {
+        if self
+            .system_slept_for
+            .take()
+            .is_some_and(|slept_for| slept_for > threshold)
+        {
+            self.restart();
+        }
+    }
+
+    /// Records that Xcode is frontmost now
+    pub fn mark_frontmost(&mut self) {
+        let now = self.now();
+        if let Some(since) = self.away_since.take() {
+            self.last_absence = now - since;
+            self.frontmost_since = now;
+        }
+        self.last_frontmost_at = now;
+    }
+
+    /// Records that Xcode is not frontmost now. The previous absence counts
+    /// toward idle unless it was shorter than `grace` seconds, and earlier
+    /// absences are forgotten once Xcode stayed frontmost for `grace`
+    /// seconds in between.
+    pub fn mark_away(&mut self, grace: i64) {
+        if self.away_since.is_some() {
+            return;
+        }
+        if self.last_frontmost_at - self.frontmost_since >= grace {
+            self.away_for = 0;
+        } else if self.last_absence >= grace {
+            self.away_for += self.last_absence;
+        }
+        self.last_absence = 0;
+        self.away_since = Some(self.last_frontmost_at);
+    }
+
+    /// Returns whether Xcode is not frontmost and the time away from it adds
+    /// up to more than `threshold` seconds
+    pub fn is_idle(&self, threshold: i64) -> bool {
+        self.away_since
+            .is_some_and(|since| self.away_for + self.now() - since > threshold)
+    }
+
+    /// Multiplier of the sleep between two checks
+    pub fn backoff(&self) -> u64 {
+        self.backoff
+    }
+
+    /// Increase the sleep multiplier using exponential backoff with a maximum
+    /// cap to avoid excessively frequent checks when Xcode or Discord are not
+    /// running.
+    pub fn increase_backoff(&mut self) {
+        self.backoff = (self.backoff * 2).min(MAX_BACKOFF);
+    }
+
+    /// Reset the sleep multiplier when Xcode and Discord are running again.
+    pub fn reset_backoff(&mut self) {
+        self.backoff = 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::clock::SimulatedClock;
+
+    fn timer() -> (PresenceTimer, SimulatedClock) {
+        let clock = SimulatedClock::new();
+        (PresenceTimer::new(Box::new(clock.clone())), clock)
+    }
+
+    #[test]
+    fn becomes_idle_after_threshold() {
+        let (mut timer, clock) = timer();
+        clock.advance(Duration::from_secs(20));
+        timer.mark_frontmost();
+        timer.sleep(Duration::from_secs(3));
+        timer.mark_away(0);
+        timer.sleep(Duration::from_secs(22));
+        assert!(!timer.is_idle(25));
+        timer.sleep(Duration::from_secs(1));
+        assert!(timer.is_idle(25));
+        timer.mark_frontmost();
+        assert!(!timer.is_idle(25));
+    }
+
+    #[test]
+    fn forgives_peeks_shorter_than_grace() {
+        let (mut timer, _) = timer();
+        for _ in 0..10 {
+            timer.mark_away(30);
+            timer.sleep(Duration::from_secs(20));
+            assert!(!timer.is_idle(25));
+            timer.mark_frontmost();
+            timer.sleep(Duration::from_secs(5));
+            timer.mark_frontmost();
+        }
+        timer.mark_away(30);
+        timer.sleep(Duration::from_secs(26));
+        assert!(timer.is_idle(25));
+    }
+
+    #[test]
+    fn adds_up_absences_until_back_for_grace() {
+        let (mut timer, _) = timer();
+        timer.mark_away(30);
+        timer.sleep(Duration::from_secs(20));
+        timer.sleep(Duration::from_secs(15));
+        timer.mark_frontmost();
+        timer.sleep(Duration::from_secs(10));
+        timer.mark_frontmost();
+        timer.mark_away(30);
+        assert!(timer.is_idle(25));
+        timer.mark_frontmost();
+
+        timer.sleep(Duration::from_secs(30));
+        timer.mark_frontmost();
+        timer.mark_away(30);
+        assert!(!timer.is_idle(25));
+    }
+
+    #[test]
+    fn caps_and_resets_backoff() {
+        let (mut timer, clock) = timer();
+        for _ in 0..5 {
+            timer.increase_backoff();
+        }
+        assert_eq!(timer.backoff(), MAX_BACKOFF);
+        clock.sleep_system(60);
+        assert_eq!(timer.sleep(Duration::from_secs(3)), Some(60));
+        assert_eq!(timer.backoff(), 1);
+    }
+
+    #[test]
+    fn restarts_after_long_system_sleep() {
+        let (mut timer, clock) = timer();
+        clock.sleep_system(300);
+        timer.sleep(Duration::from_secs(3));
+        timer.restart_after_wake(900);
+        assert_eq!(timer.started_at(), SimulatedClock::EPOCH);
+        assert_eq!(timer.elapsed(), 303);
+
+        clock.sleep_system(1000);
+        timer.sleep(Duration::from_secs(3));
+        timer.restart_after_wake(900);
+        assert_eq!(timer.started_at(), clock.now());
+        assert_eq!(timer.elapsed(), 0);
+    }
+}
@@ -0,0 +1,105 @@
+use std::path::{Path, PathBuf};
+
+use config::{Config, File, FileFormat};
+use serde::Deserialize;
+
+use crate::{config::ButtonConfig, utils::duration::deserialize_optional_seconds, Result};
+
+/// Name of the per-project configuration file looked up in the project root
+pub const PROJECT_CONFIG_FILE: &str = ".xcode-discord-rpc.toml";
+
+/// Per-project overrides read from `.xcode-discord-rpc.toml` in the project root.
+/// Any field left unset falls back to the global configuration.
+#[derive(Debug, Default, Deserialize)]
+pub struct ProjectConfig {
+    /// Overrides `hide_file` for this project
+    pub hide_file: Option<bool>,
+    /// Overrides `hide_project` for this project
+    pub hide_project: Option<bool>,
+    /// Overrides `details_template` for this project
+    pub details_template: Option<String>,
+    /// Overrides `state_template` for this project
+    pub state_template: Option<String>,
+    /// Overrides `buttons` for this project
+    pub buttons: Option<Vec<ButtonConfig>>,
+    /// Large image shown instead of the file language, a hosted image URL or
+    /// an asset key of the Discord application
+    pub large_image: Option<String>,
+    /// Hover text of the large image shown instead of the file language
+    pub large_text: Option<String>,
+    /// Overrides `idle_threshold` for this project
+    #[serde(default, deserialize_with = "deserialize_optional_seconds")]
+    pub idle_threshold: Option<i64>,
+    /// Overrides `xcode_update_interval` for this project
+    #[serde(default, deserialize_with = "deserialize_optional_seconds")]
+    pub xcode_update_interval: Option<u64>,
+}
+
+impl ProjectConfig {
+    /// Loads the per-project configuration from `project_root`, returning the
+    /// default (no overrides) when the file does not exist
+    pub fn load(project_root: &Path) -> Result<Self> {
+        let path = project_root.join(PROJECT_CONFIG_FILE);
+        let c = Config::builder()
+            .add_source(File::from(path).format(FileFormat::Toml).required(false))
+            .build()?;
+        Ok(c.try_deserialize()?)
+    }
+}
+
+/// Returns the project root for the path of an Xcode workspace document.
+/// `.xcodeproj`, `.xcworkspace` and `.playground` bundles as well as
+/// `Package.swift` live inside the project root, while Swift packages opened
+/// as a folder are their own root.
+pub fn project_root(document_path: &Path) -> PathBuf {
+    let is_manifest = document_path
+        .file_name()
+        .is_some_and(|name| name == "Package.swift");
+    let is_bundle = matches!(
+        document_path.extension().and_then(|ext| ext.to_str()),
+        Some("xcodeproj" | "xcworkspace" | "playground")
+    );
+    match document_path.parent() {
+        Some(parent) if is_manifest || is_bundle => parent.to_path_buf(),
+        _ => document_path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::testing::sandbox;
+
+    #[test]
+    fn finds_project_root() {
+        for document in [
+            "/Code/App/App.xcodeproj",
+            "/Code/App/App.xcworkspace",
+            "/Code/App/Sketch.playground",
+            "/Code/App/Package.swift",
+            "/Code/App",
+        ] {
+            assert_eq!(project_root(Path::new(document)), Path::new("/Code/App"));
+        }
+    }
+
+    #[test]
+    fn loads_overrides_from_project_root() {
+        let root = sandbox().join("Overridden");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join(PROJECT_CONFIG_FILE),
+            "hide_project = true\nidle_threshold = \"2m\"\n",
+        )
+        .unwrap();
+        let overrides = ProjectConfig::load(&root).unwrap();
+        assert_eq!(overrides.hide_project, Some(true));
+        assert_eq!(overrides.idle_threshold, Some(120));
+        assert_eq!(overrides.hide_file, None);
+        // Projects without the file have no overrides
+        let defaults = ProjectConfig::load(&sandbox().join("Unconfigured")).unwrap();
+        assert_eq!(defaults.hide_project, None);
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::BTreeMap;
+
+use chrono::{Days, Local, NaiveDate};
+use serde::Serialize;
+
+use crate::{
+    stats::Stats,
+    utils::duration::{format_duration, format_hours},
+    Result,
+};
+
+/// Width of the longest bar in the text report
+const BAR_WIDTH: u64 = 20;
+
+/// Days covered by a report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportPeriod {
+    Today,
+    /// The last 7 days, including today
+    Week,
+}
+
+/// Output format of a report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+/// Time spent on a project or language
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct ReportEntry {
+    pub name: String,
+    pub seconds: u64,
+}
+
+/// Active time between two days, broken down by project and language
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub total_seconds: u64,
+    /// Daily goal of a report of a single day
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub goal_seconds: Option<u64>,
+    pub projects: Vec<ReportEntry>,
+    pub languages: Vec<ReportEntry>,
+}
+
+/// Sorts the entries by time spent, longest first
+fn sorted_entries(totals: BTreeMap<String, u64>) -> Vec<ReportEntry> {
+    let mut entries = totals
+        .into_iter()
+        .map(|(name, seconds)| ReportEntry { name, seconds })
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| b.seconds.cmp(&a.seconds).then_with(|| a.name.cmp(&b.name)));
+    entries
+}
+
+impl Report {
+    /// Sums the statistics of the days from `from` to `to`, both inclusive
+    pub fn new(stats: &Stats, from: NaiveDate, to: NaiveDate) -> Self {
+        let mut total_seconds = 0;
+        let mut projects = BTreeMap::new();
+        let mut languages = BTreeMap::new();
+        let days = stats.days.iter().filter(|(date, _)| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .is_ok_and(|date| (from..=to).contains(&date))
+        });
+        for (_, day) in days {
+            total_seconds += day.active_seconds;
+            for (project, seconds) in &day.projects {
+                *projects.entry(project.clone()).or_default() += seconds;
+            }
+            for (language, seconds) in &day.languages {
+                *languages.entry(language.clone()).or_default() += seconds;
+            }
+        }
+        Report {
+            from,
+            to,
+            total_seconds,
+            goal_seconds: None,
+            projects: sorted_entries(projects),
+            languages: sorted_entries(languages),
+        }
+    }
+
+    /// Renders the report with a bar chart for the terminal
+    pub fn to_text(&self) -> String {
+        let mut text = if self.from == self.to {
+            format!("{}: {}\n", self.from, format_duration(self.total_seconds))
+        } else {
+            format!(
+                "{} to {}: {}\n",
+                self.from,
+                self.to,
+                format_duration(self.total_seconds)
+            )
+        };
+        if let Some(goal) = self.goal_seconds {
+            text.push_str(&goal_progress(self.total_seconds, goal));
+        }
+        for (title, entries) in [("Projects", &self.projects), ("Languages", &self.languages)] {
+            if entries.is_empty() {
+                continue;
+            }
+            text.push_str(&format!("\n{title}\n"));
+            let name_width = entries.iter().map(|entry| entry.name.chars().count()).max();
+            let longest = entries.first().map_or(1, |entry| entry.seconds.max(1));
+            for entry in entries {
+                let bar = (entry.seconds * BAR_WIDTH / longest).max(1) as usize;
+                text.push_str(&format!(
+                    "  {:name_width$}  {:>7}  {}\n",
+                    entry.name,
+                    format_duration(entry.seconds),
+                    "█".repeat(bar),
+                    name_width = name_width.unwrap_or_default(),
+                ));
+            }
+        }
+        text
+    }
+
+    /// Renders the report as CSV with one row per project and language
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("kind,name,seconds\n");
+        csv.push_str(&format!("total,,{}\n", self.total_seconds));
+        for (kind, entries) in [("project", &self.projects), ("language", &self.languages)] {
+            for entry in entries {
+                csv.push_str(&format!(
+                    "{kind},{},{}\n",
+                    csv_field(&entry.name),
+                    entry.seconds
+                ));
+            }
+        }
+        csv
+    }
+}
+
+/// Describes the progress toward the daily goal
+fn goal_progress(seconds: u64, goal: u64) -> String {
+    format!(
+        "Goal: {} / {} ({}%)\n",
+        format_hours(seconds),
+        format_hours(goal),
+        seconds * 100 / goal.max(1)
+    )
+}
+
+/// Describes the running `instance`, the time spent today and the progress
+/// toward `daily_goal`
+fn status_text(instance: &str, today: u64, daily_goal: u64) -> String {
+    let mut text = format!("{instance}\nToday: {}\n", format_duration(today));
+    if daily_goal > 0 {
+        text.push_str(&goal_progress(today, daily_goal));
+    }
+    text
+}
+
+/// Prints the status of the running `instance` with the time spent today
+pub fn print_status(instance: &str, daily_goal: u64) -> Result<()> {
+    let today = Local::now().date_naive();
+    let report = Report::new(&Stats::load()?, today, today);
+    print!(
+        "{}",
+        status_text(instance, report.total_seconds, daily_goal)
+    );
+    Ok(())
+}
+
+/// Quotes a CSV field when it contains a separator, quote or line break
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Prints the report of `period` in `format`, with the progress toward
+/// `daily_goal` for today
+pub fn print_report(period: ReportPeriod, format: ReportFormat, daily_goal: u64) -> Result<()> {
+    let to = Local::now().date_naive();
+    let from = match period {
+        ReportPeriod::Today => to,
+        ReportPeriod::Week => to.checked_sub_days(Days::new(6)).unwrap_or(to),
+    };
+    let mut report = Report::new(&Stats::load()?, from, to);
+    if period == ReportPeriod::Today && daily_goal > 0 {
+        report.goal_seconds = Some(daily_goal);
+    }
+    match format {
+        ReportFormat::Text => print!("{}", report.to_text()),
+        ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        ReportFormat::Csv => print!("{}", report.to_csv()),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::DayStats;
+
+    fn day(project: &str, language: &str, seconds: u64) -> DayStats {
+        DayStats {
+            active_seconds: seconds,
+            projects: BTreeMap::from([(project.to_string(), seconds)]),
+            languages: BTreeMap::from([(language.to_string(), seconds)]),
+        }
+    }
+
+    fn date(date: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn sums_days_in_range() {
+        let stats = Stats {
+            days: BTreeMap::from([
+                (String::from("2026-10-01"), day("Old", "Swift", 600)),
+                (String::from("2026-10-10"), day("MyApp", "Swift", 3600)),
+                (String::from("2026-10-11"), day("Other, Inc", "C", 1800)),
+            ]),
+            ..Default::default()
+        };
+        let report = Report::new(&stats, date("2026-10-09"), date("2026-10-15"));
+        assert_eq!(report.total_seconds, 5400);
+        assert_eq!(report.projects[0].name, "MyApp");
+        assert_eq!(
+            report.to_csv(),
+            "kind,name,seconds\ntotal,,5400\nproject,MyApp,3600\nproject,\"Other, Inc\",1800\n\
+             language,Swift,3600\nlanguage,C,1800\n"
+        );
+    }
+
+    #[test]
+    fn scales_bars_to_longest_entry() {
+        let stats = Stats {
+            days: BTreeMap::from([(String::from("2026-10-15"), day("MyApp", "Swift", 7200))]),
+            ..Default::default()
+        };
+        let mut report = Report::new(&stats, date("2026-10-15"), date("2026-10-15"));
+        report.goal_seconds = Some(5 * 3600);
+        let text = report.to_text();
+        assert!(text.starts_with("2026-10-15: 2h 0m\nGoal: 2h / 5h (40%)\n"));
+        assert!(text.contains(&format!("  MyApp    2h 0m  {}\n", "█".repeat(20))));
+    }
+
+    #[test]
+    fn shows_goal_progress_in_status() {
+        assert_eq!(
+            status_text("Not running", 11_520, 5 * 3600),
+            "Not running\nToday: 3h 12m\nGoal: 3.2h / 5h (64%)\n"
+        );
+        assert_eq!(
+            status_text("Running, paused", 600, 0),
+            "Running, paused\nToday: 10m\n"
+        );
+    }
+}
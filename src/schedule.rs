@@ -0,0 +1,154 @@
+use chrono::{DateTime, Datelike, Local, NaiveTime, Weekday};
+use serde::Deserialize;
+
+/// Time window during which presence is shown. Outside of it presence is
+/// cleared and suppressed.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "RawSchedule")]
+pub struct Schedule {
+    /// Days of the week on which the window applies
+    days: Vec<Weekday>,
+    /// Start of the window (inclusive)
+    start: NaiveTime,
+    /// End of the window (exclusive), may be before `start` for overnight windows
+    end: NaiveTime,
+}
+
+/// Schedule as written in the configuration file
+#[derive(Deserialize)]
+struct RawSchedule {
+    #[serde(default = "all_days")]
+    days: Vec<String>,
+    start: String,
+    end: String,
+}
+
+fn all_days() -> Vec<String> {
+    ["mon", "tue", "wed", "thu", "fri", "sat", "sun"]
+        .iter()
+        .map(|day| day.to_string())
+        .collect()
+}
+
+impl TryFrom<RawSchedule> for Schedule {
+    type Error = String;
+
+    fn try_from(raw: RawSchedule) -> std::result::Result<Self, Self::Error> {
+        let days = raw
+            .days
+            .iter()
+            .map(|day| {
+                day.parse::<Weekday>()
+                    .map_err(|_| format!("invalid day in schedule: {day}"))
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let parse_time = |time: &str| {
+            NaiveTime::parse_from_str(time, "%H:%M")
+                .map_err(|_| format!("invalid time in schedule, expected HH:MM: {time}"))
+        };
+        Ok(Self {
+            days,
+            start: parse_time(&raw.start)?,
+            end: parse_time(&raw.end)?,
+        })
+    }
+}
+
+impl Schedule {
+    /// Returns whether `now` falls inside the scheduled window
+    pub fn contains(&self, now: DateTime<Local>) -> bool {
+        let time = now.time();
+        if self.start <= self.end {
+            self.days.contains(&now.weekday()) && self.start <= time && time < self.end
+        } else if time >= self.start {
+            // overnight window, before midnight
+            self.days.contains(&now.weekday())
+        } else if time < self.end {
+            // overnight window, after midnight it belongs to the previous day
+            self.days.contains(&now.weekday().pred())
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use config::{Config, File, FileFormat};
+
+    use super::*;
+
+    fn schedule(source: &str) -> std::result::Result<Schedule, String> {
+        Config::builder()
+            .add_source(File::from_str(source, FileFormat::Toml))
+            .build()
+            .and_then(|c| c.try_deserialize())
+            .map_err(|err| err.to_string())
+    }
+
+    /// 2024-01-01 is a Monday
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2024, 1, day, hour, minute, 0)
+            .single()
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_days_and_times() {
+        let weekdays = schedule(
+            "days = [\"mon\", \"Tuesday\", \"FRI\"]\nstart = \"09:00\"\nend = \"17:30\"\n",
+        )
+        .unwrap();
+        assert_eq!(weekdays.days, [Weekday::Mon, Weekday::Tue, Weekday::Fri]);
+        assert_eq!(weekdays.start, NaiveTime::from_hms_opt(9, 0, 0).unwrap());
+        assert_eq!(weekdays.end, NaiveTime::from_hms_opt(17, 30, 0).unwrap());
+        let daily = schedule("start = \"09:00\"\nend = \"17:00\"\n").unwrap();
+        assert_eq!(daily.days.len(), 7);
+    }
+
+    #[test]
+    fn rejects_invalid_days_and_times() {
+        let invalid_day = schedule("days = [\"someday\"]\nstart = \"09:00\"\nend = \"17:00\"\n");
+        assert!(invalid_day
+            .unwrap_err()
+            .contains("invalid day in schedule: someday"));
+        for time in ["9am", "25:00", "09:00:00"] {
+            let invalid_time = schedule(&format!("start = \"{time}\"\nend = \"17:00\"\n"));
+            assert!(invalid_time
+                .unwrap_err()
+                .contains(&format!("expected HH:MM: {time}")));
+        }
+    }
+
+    #[test]
+    fn contains_times_within_window() {
+        let weekdays = schedule(
+            "days = [\"mon\", \"tue\", \"wed\", \"thu\", \"fri\"]\n\
+             start = \"09:00\"\nend = \"17:00\"\n",
+        )
+        .unwrap();
+        assert!(weekdays.contains(at(1, 9, 0)));
+        assert!(weekdays.contains(at(5, 16, 59)));
+        assert!(!weekdays.contains(at(1, 8, 59)));
+        // The end is exclusive
+        assert!(!weekdays.contains(at(1, 17, 0)));
+        // Saturday
+        assert!(!weekdays.contains(at(6, 12, 0)));
+    }
+
+    #[test]
+    fn contains_overnight_window_of_previous_day() {
+        let friday_night =
+            schedule("days = [\"fri\"]\nstart = \"22:00\"\nend = \"02:00\"\n").unwrap();
+        assert!(friday_night.contains(at(5, 23, 0)));
+        // Early Saturday still belongs to Friday night
+        assert!(friday_night.contains(at(6, 1, 59)));
+        assert!(!friday_night.contains(at(6, 2, 0)));
+        assert!(!friday_night.contains(at(6, 23, 0)));
+        // Early Friday belongs to Thursday night
+        assert!(!friday_night.contains(at(5, 1, 0)));
+        assert!(!friday_night.contains(at(5, 12, 0)));
+    }
+}
@@ -0,0 +1,1371 @@
+use std::{fmt, fs, ops::Range, path::Path};
+
+use chrono::NaiveTime;
+use config::{Config, File, FileFormat};
+use serde_json::{json, Map, Value};
+use toml_edit::{ImDocument, Item, TableLike};
+
+use crate::{
+    config::{config_path, AppConfig, DEFAULT_CONFIG},
+    migration::{CONFIG_VERSION, VERSION_KEY},
+    utils::duration::parse_duration,
+    Error, Result,
+};
+
+/// Type and allowed values of a configuration key
+#[derive(Debug)]
+pub enum Kind {
+    Bool,
+    /// An integer within `min..=max`
+    Integer {
+        min: i64,
+        max: i64,
+    },
+    String,
+    /// A string with `{placeholder}`s, lists the placeholders that are replaced
+    Template(&'static [&'static str]),
+    /// A duration of at least `min` seconds, written as a string such as
+    /// `"30s"` or `"1h30m"` or as a number of seconds
+    Duration {
+        min: u64,
+    },
+    /// A time of day written as `HH:MM`
+    Time,
+    /// One of the listed strings
+    Enum(&'static [&'static str]),
+    Array(&'static Kind),
+    /// A table with the listed keys
+    Table(&'static [Key]),
+    /// A table with arbitrary keys, `key` is `Kind::String` or `Kind::Enum`
+    Map {
+        key: &'static Kind,
+        value: &'static Kind,
+    },
+    /// A table with the keys of the configuration file, except `profile`
+    Profile,
+}
+
+/// A documented configuration key
+#[derive(Debug)]
+pub struct Key {
+    pub name: &'static str,
+    pub kind: Kind,
+    pub required: bool,
+    pub description: &'static str,
+}
+
+const fn optional(name: &'static str, kind: Kind, description: &'static str) -> Key {
+    Key {
+        name,
+        kind,
+        required: false,
+        description,
+    }
+}
+
+const fn required(name: &'static str, kind: Kind, description: &'static str) -> Key {
+    Key {
+        name,
+        kind,
+        required: true,
+        description,
+    }
+}
+
+/// An integer of at least `min`
+const fn at_least(min: i64) -> Kind {
+    Kind::Integer { min, max: i64::MAX }
+}
+
+/// A duration of at least `min` seconds
+const fn seconds(min: u64) -> Kind {
+    Kind::Duration { min }
+}
+
+const PORT: Kind = Kind::Integer { min: 1, max: 65535 };
+
+/// Placeholders available in every presence template
+const PROJECT_PLACEHOLDERS: [&str; 20] = [
+    "emoji",
+    "project_type",
+    "today",
+    "open_files",
+    "top_language",
+    "loc",
+    "project_kind",
+    "platform",
+    "scheme",
+    "configuration",
+    "errors",
+    "warnings",
+    "xcode_version",
+    "swift_version",
+    "macos_version",
+    "machine",
+    "app_name",
+    "bundle_id",
+    "elapsed",
+    "goal",
+];
+
+/// Concatenates `extra` and the placeholders available in every template.
+/// `N` has to be the total, which fails to compile otherwise.
+const fn with_project_placeholders<const N: usize>(extra: &[&'static str]) -> [&'static str; N] {
+    let mut placeholders = [""; N];
+    let mut i = 0;
+    while i < extra.len() {
+        placeholders[i] = extra[i];
+        i += 1;
+    }
+    while i < N {
+        placeholders[i] = PROJECT_PLACEHOLDERS[i - extra.len()];
+        i += 1;
+    }
+    assert!(i - extra.len() == PROJECT_PLACEHOLDERS.len());
+    placeholders
+}
+
+const FILE_PLACEHOLDERS: [&str; 21] = with_project_placeholders(&["file"]);
+const STATE_PLACEHOLDERS: [&str; 21] = with_project_placeholders(&["project"]);
+const LARGE_TEXT_PLACEHOLDERS: [&str; 23] =
+    with_project_placeholders(&["language", "file", "project"]);
+
+/// Keys of the presence texts that can be translated
+pub const TEXTS: [&str; 60] = [
+    "idle",
+    "details_template",
+    "state_template",
+    "browsing_template",
+    "a_file",
+    "a_project",
+    "project_codename",
+    "coding_in_xcode",
+    "reading_documentation",
+    "committing",
+    "reviewing",
+    "designing",
+    "switching_files",
+    "editing_playground",
+    "editing_playground_page",
+    "running_playground",
+    "running_playground_page",
+    "composing_scene",
+    "training_model",
+    "testing_in_simulator",
+    "testing_in_simulator_on",
+    "running_on_device",
+    "a_device",
+    "archiving",
+    "building",
+    "building_for",
+    "indexing",
+    "resolving_packages",
+    "building_from_cli",
+    "build_succeeded",
+    "build_failed",
+    "build_failed_error",
+    "build_failed_errors",
+    "build_cancelled",
+    "running_tests",
+    "running_tests_count",
+    "tests_passed",
+    "tests_failed",
+    "error",
+    "errors",
+    "warning",
+    "warnings",
+    "on_break",
+    "focus",
+    "pairing",
+    "pairing_with",
+    "writing",
+    "on_branch",
+    "session",
+    "session_summary",
+    "daily_goal",
+    "working_tree_clean",
+    "file_changed",
+    "files_changed",
+    "ticket",
+    "pull_request_open",
+    "view_repository",
+    "open_ticket",
+    "other_project",
+    "other_projects",
+];
+
+const DAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+const BUTTON_KEYS: [Key; 2] = [
+    required("label", Kind::String, "Text displayed on the button"),
+    required("url", Kind::String, "URL opened when the button is clicked"),
+];
+
+const SCHEDULE_KEYS: [Key; 3] = [
+    optional(
+        "days",
+        Kind::Array(&Kind::Enum(&DAYS)),
+        "Days of the week on which the window applies",
+    ),
+    required("start", Kind::Time, "Start of the window, inclusive"),
+    required(
+        "end",
+        Kind::Time,
+        "End of the window, exclusive, may be before `start` for overnight windows",
+    ),
+];
+
+const POMODORO_KEYS: [Key; 3] = [
+    optional("work_minutes", at_least(1), "Length of a focus interval"),
+    optional("break_minutes", at_least(1), "Length of a break"),
+    optional(
+        "clear_details_on_break",
+        Kind::Bool,
+        "Whether to hide the file during breaks",
+    ),
+];
+
+const PAIRING_KEYS: [Key; 2] = [
+    optional(
+        "partner",
+        Kind::String,
+        "Who the session is with, shown as \"👥 Pairing with {partner}\"",
+    ),
+    optional(
+        "size",
+        at_least(1),
+        "Number of people in the session, shown as \"(2 of 2)\"",
+    ),
+];
+
+const ACTIVITYWATCH_KEYS: [Key; 1] = [optional(
+    "url",
+    Kind::String,
+    "URL of the ActivityWatch server",
+)];
+
+const TIME_TRACKING_KEYS: [Key; 3] = [
+    required(
+        "service",
+        Kind::Enum(&["toggl", "clockify"]),
+        "Service receiving the time entries",
+    ),
+    required("workspace_id", Kind::String, "ID of the workspace"),
+    optional(
+        "projects",
+        Kind::Map {
+            key: &Kind::String,
+            value: &Kind::String,
+        },
+        "Project IDs of the service, keyed by the Xcode project name",
+    ),
+];
+
+const SLACK_KEYS: [Key; 3] = [
+    optional(
+        "status_template",
+        Kind::Template(&["project"]),
+        "Status text during working sessions",
+    ),
+    optional(
+        "status_emoji",
+        Kind::String,
+        "Status emoji during working sessions",
+    ),
+    optional("api_url", Kind::String, "Base URL of the Slack Web API"),
+];
+
+const WEBHOOK_KEYS: [Key; 2] = [
+    required("url", Kind::String, "URL receiving the JSON POST requests"),
+    optional(
+        "events",
+        Kind::Array(&Kind::Enum(&[
+            "session_start",
+            "session_end",
+            "project_switch",
+            "build_finished",
+        ])),
+        "Events sent to the URL, all by default",
+    ),
+];
+
+const MQTT_KEYS: [Key; 4] = [
+    optional("host", Kind::String, "Host of the MQTT broker"),
+    optional("port", PORT, "Port of the MQTT broker"),
+    optional("topic", Kind::String, "Topic receiving the presence"),
+    optional(
+        "username",
+        Kind::String,
+        "User name, the password is read from the `mqtt` Keychain secret",
+    ),
+];
+
+const OVERLAY_KEYS: [Key; 2] = [
+    required("path", Kind::String, "File receiving the presence"),
+    optional(
+        "format",
+        Kind::Enum(&["text", "json"]),
+        "Format of the file",
+    ),
+];
+
+const WEBSOCKET_KEYS: [Key; 2] = [
+    optional("port", PORT, "Port listened on localhost"),
+    optional(
+        "allowed_origins",
+        Kind::Array(&Kind::String),
+        "Origins of the web pages allowed to connect",
+    ),
+];
+
+/// Every key of the configuration file
+pub const KEYS: &[Key] = &[
+    optional(
+        VERSION_KEY,
+        Kind::Integer {
+            min: 1,
+            max: CONFIG_VERSION,
+        },
+        "Version of the configuration file, set when it is migrated",
+    ),
+    optional(
+        "application_id",
+        Kind::String,
+        "ID of the Discord application whose name and art assets are shown",
+    ),
+    optional(
+        "update_interval",
+        seconds(1),
+        "Interval in seconds for checking status for Discord and Xcode",
+    ),
+    optional(
+        "xcode_update_interval",
+        seconds(1),
+        "Interval in seconds for checking updates in Xcode",
+    ),
+    optional(
+        "xcode_check_interval",
+        seconds(1),
+        "Interval in seconds between two checks whether Xcode was launched",
+    ),
+    optional(
+        "frontmost_check_interval",
+        seconds(1),
+        "Interval in seconds between two checks whether Xcode is frontmost",
+    ),
+    optional(
+        "wake_reset_threshold",
+        seconds(0),
+        "System sleep in seconds after which the elapsed time is reset on wake",
+    ),
+    optional(
+        "idle_threshold",
+        seconds(1),
+        "Threshold in seconds for considering the user idle status",
+    ),
+    optional(
+        "frontmost_grace",
+        seconds(0),
+        "Seconds under which an absence from Xcode does not count toward idle_threshold",
+    ),
+    optional(
+        "battery_interval_multiplier",
+        at_least(1),
+        "Multiplier applied to the update intervals while on battery or in Low Power Mode",
+    ),
+    optional(
+        "require_frontmost",
+        Kind::Bool,
+        "Whether presence requires Xcode to be the frontmost application",
+    ),
+    optional(
+        "idle_action",
+        Kind::Enum(&["idle", "clear"]),
+        "What to do with presence once Xcode has not been frontmost for `idle_threshold`",
+    ),
+    optional(
+        "activity_type",
+        Kind::Enum(&["playing", "listening", "watching", "competing"]),
+        "Verb shown before the application name, such as \"Playing\" or \"Watching\"",
+    ),
+    optional(
+        "build_result_duration",
+        seconds(0),
+        "Duration in seconds the result of a finished build is shown, 0 to disable",
+    ),
+    optional(
+        "show_build_phase",
+        Kind::Bool,
+        "Whether to show when Xcode is building or indexing",
+    ),
+    optional(
+        "show_test_progress",
+        Kind::Bool,
+        "Whether to show the progress and the result of test runs",
+    ),
+    optional(
+        "detect_cli_builds",
+        Kind::Bool,
+        "Whether to show when `xcodebuild` or `swift build` runs in the project",
+    ),
+    optional(
+        "detect_source_control",
+        Kind::Bool,
+        "Whether to detect Xcode's commit sheet, requires the Accessibility permission",
+    ),
+    optional(
+        "detect_swiftui_previews",
+        Kind::Bool,
+        "Whether to detect the focused SwiftUI preview canvas, requires the Accessibility permission",
+    ),
+    optional(
+        "detect_device_runs",
+        Kind::Bool,
+        "Whether to show when the app runs on a physical device, requires the Accessibility permission",
+    ),
+    optional(
+        "redact_device_name",
+        Kind::Bool,
+        "Whether to show the model of the device instead of its name",
+    ),
+    optional(
+        "simulator_threshold",
+        seconds(0),
+        "Duration in seconds Simulator has to be frontmost before showing testing in it, 0 to disable",
+    ),
+    optional(
+        "companion_apps",
+        Kind::Array(&Kind::Enum(&["reality_composer_pro", "create_ml"])),
+        "Developer tools besides Xcode shown in the presence while frontmost",
+    ),
+    optional(
+        "streamer_mode",
+        Kind::Bool,
+        "Whether to switch to a generic presence while streaming or recording the screen",
+    ),
+    optional(
+        "streamer_apps",
+        Kind::Array(&Kind::String),
+        "Process names that indicate the screen is being streamed or recorded",
+    ),
+    optional(
+        "hide_file",
+        Kind::Bool,
+        "Whether to hide the file name in Discord Rich Presence",
+    ),
+    optional(
+        "hidden_files",
+        Kind::Array(&Kind::String),
+        "Globs of file names hidden even while hide_file is disabled",
+    ),
+    optional(
+        "ignore_marker",
+        Kind::String,
+        "Name of the file marking a project as confidential, empty to disable",
+    ),
+    optional(
+        "hide_project",
+        Kind::Bool,
+        "Whether to hide the project name in Discord Rich Presence",
+    ),
+    optional(
+        "pseudonymize",
+        Kind::Bool,
+        "Whether to show a codename derived from the project name instead of the name",
+    ),
+    optional(
+        "file_display",
+        Kind::Enum(&["name", "name_no_ext", "parent/name", "relative_path"]),
+        "How the focused file is shown in `{file}`",
+    ),
+    optional(
+        "unsaved_indicator",
+        Kind::Bool,
+        "Whether to append \"●\" to the file while it has unsaved changes",
+    ),
+    optional(
+        "file_switch_threshold",
+        at_least(0),
+        "Number of files focused within file_switch_window shown as switching between them, 0 to disable",
+    ),
+    optional(
+        "file_switch_window",
+        seconds(1),
+        "Duration in seconds during which a focused file counts toward file_switch_threshold",
+    ),
+    optional(
+        "show_other_projects",
+        Kind::Bool,
+        "Whether to mention the other open projects in the state line",
+    ),
+    optional(
+        "emoji",
+        Kind::Bool,
+        "Whether to prefix the details and state with an emoji of the activity",
+    ),
+    optional(
+        "events",
+        Kind::Bool,
+        "Whether to print presence changes as newline-delimited JSON instead of logs",
+    ),
+    optional(
+        "session_summary",
+        Kind::Bool,
+        "Whether to show a notification with the time worked on the project when a session ends",
+    ),
+    optional(
+        "daily_goal",
+        seconds(0),
+        "Coding time in seconds to reach each day, shown by {goal}, 0 to disable",
+    ),
+    optional(
+        "details_template",
+        Kind::Template(&FILE_PLACEHOLDERS),
+        "Template for the details line",
+    ),
+    optional(
+        "state_template",
+        Kind::Template(&STATE_PLACEHOLDERS),
+        "Template for the state line",
+    ),
+    optional(
+        "browsing_template",
+        Kind::Template(&STATE_PLACEHOLDERS),
+        "Template for the details line when no file is focused",
+    ),
+    optional(
+        "language",
+        Kind::String,
+        "Language code of the presence texts, the system locale when unset",
+    ),
+    optional(
+        "translations",
+        Kind::Map {
+            key: &Kind::String,
+            value: &Kind::Map {
+                key: &Kind::Enum(&TEXTS),
+                value: &Kind::String,
+            },
+        },
+        "User translations of the presence texts, keyed by language code",
+    ),
+    optional(
+        "rotation",
+        Kind::Array(&Kind::Enum(&[
+            "file",
+            "language",
+            "branch",
+            "elapsed",
+            "pull_request",
+            "ticket",
+            "changes",
+        ])),
+        "Facts the details line cycles through, empty to always show the file",
+    ),
+    optional(
+        "rotation_interval",
+        seconds(1),
+        "Interval in seconds between two facts of `rotation`",
+    ),
+    optional(
+        "buttons",
+        Kind::Array(&Kind::Table(&BUTTON_KEYS)),
+        "Buttons shown in Discord Rich Presence, Discord displays at most 2",
+    ),
+    optional(
+        "repository_button",
+        Kind::Bool,
+        "Whether to add a button linking to the project's public GitHub or GitLab repository",
+    ),
+    optional(
+        "github_token",
+        Kind::String,
+        "Deprecated, token used for GitHub API requests when the `github` Keychain secret is not set",
+    ),
+    optional(
+        "github_api_url",
+        Kind::String,
+        "Base URL of the GitHub REST API the pull requests are looked up with",
+    ),
+    optional(
+        "pull_request_interval",
+        seconds(60),
+        "Interval in seconds between two checks for an open pull request",
+    ),
+    optional(
+        "language_stats",
+        Kind::Bool,
+        "Whether to count the lines of code of the project with tokei",
+    ),
+    optional(
+        "language_stats_interval",
+        seconds(60),
+        "Interval in seconds between two tokei scans of the project",
+    ),
+    optional(
+        "update_check",
+        Kind::Bool,
+        "Whether to notify when a newer release is available",
+    ),
+    optional(
+        "update_check_interval",
+        seconds(3600),
+        "Interval in seconds between two checks for a newer release",
+    ),
+    optional(
+        "icon_url_template",
+        Kind::Template(&["project"]),
+        "URL of a hosted project icon shown as the large image",
+    ),
+    optional(
+        "project_type_icon",
+        Kind::Bool,
+        "Whether to show the project type as the small image",
+    ),
+    optional(
+        "small_text",
+        Kind::Template(&PROJECT_PLACEHOLDERS),
+        "Template for the hover text of the small image",
+    ),
+    optional(
+        "large_text_template",
+        Kind::Template(&LARGE_TEXT_PLACEHOLDERS),
+        "Template for the hover text of the large image, empty to show none",
+    ),
+    optional(
+        "git_status_interval",
+        seconds(1),
+        "Interval in seconds between two counts of uncommitted changes",
+    ),
+    optional(
+        "ticket_prefixes",
+        Kind::Array(&Kind::String),
+        "Ticket keys recognized in branch names in any case",
+    ),
+    optional(
+        "ticket_url_template",
+        Kind::Template(&["ticket"]),
+        "URL of a ticket",
+    ),
+    optional(
+        "project_aliases",
+        Kind::Map {
+            key: &Kind::String,
+            value: &Kind::String,
+        },
+        "Display names used in Discord Rich Presence, keyed by the real project name",
+    ),
+    optional(
+        "focus_modes",
+        Kind::Map {
+            key: &Kind::String,
+            value: &Kind::Enum(&["full", "hidden", "off"]),
+        },
+        "Presence behavior for each macOS Focus mode, keyed by Focus name",
+    ),
+    optional(
+        "schedule",
+        Kind::Table(&SCHEDULE_KEYS),
+        "Time window outside of which presence is suppressed",
+    ),
+    optional(
+        "pomodoro",
+        Kind::Table(&POMODORO_KEYS),
+        "Interval lengths of the pomodoro started with the `pomodoro` command",
+    ),
+    optional(
+        "pairing",
+        Kind::Table(&PAIRING_KEYS),
+        "How the pair programming session started with the `pair` command is shown",
+    ),
+    optional(
+        "activitywatch",
+        Kind::Table(&ACTIVITYWATCH_KEYS),
+        "ActivityWatch server receiving editor activity",
+    ),
+    optional(
+        "time_tracking",
+        Kind::Table(&TIME_TRACKING_KEYS),
+        "Toggl or Clockify workspace receiving a time entry per working session",
+    ),
+    optional(
+        "slack",
+        Kind::Table(&SLACK_KEYS),
+        "Slack status set during working sessions",
+    ),
+    optional(
+        "webhooks",
+        Kind::Array(&Kind::Table(&WEBHOOK_KEYS)),
+        "URLs receiving a JSON POST on session and build events",
+    ),
+    optional(
+        "mqtt",
+        Kind::Table(&MQTT_KEYS),
+        "MQTT topic receiving the presence",
+    ),
+    optional(
+        "overlay",
+        Kind::Table(&OVERLAY_KEYS),
+        "File receiving the presence for stream overlays",
+    ),
+    optional(
+        "websocket",
+        Kind::Table(&WEBSOCKET_KEYS),
+        "Localhost WebSocket server pushing the presence",
+    ),
+    optional(
+        "profile",
+        Kind::Map {
+            key: &Kind::String,
+            value: &Kind::Profile,
+        },
+        "Overrides selected with `--profile`, keyed by profile name",
+    ),
+];
+
+/// Returns the dotted paths of the keys holding a list of strings, which are
+/// split on commas when set through environment variables
+pub fn list_keys() -> Vec<String> {
+    fn collect(keys: &[Key], path: &str, list_keys: &mut Vec<String>) {
+        for key in keys {
+            match &key.kind {
+                Kind::Array(Kind::String | Kind::Enum(_)) => list_keys.push(join(path, key.name)),
+                Kind::Table(keys) => collect(keys, &join(path, key.name), list_keys),
+                _ => {}
+            }
+        }
+    }
+    let mut list_keys = Vec::new();
+    collect(KEYS, "", &mut list_keys);
+    list_keys
+}
+
+/// Returns the schema of the configuration file as a JSON Schema
+pub fn json_schema() -> Value {
+    let mut schema = table_schema(KEYS);
+    if let Value::Object(object) = &mut schema {
+        object.insert(
+            "$schema".to_string(),
+            json!("https://json-schema.org/draft/2020-12/schema"),
+        );
+        object.insert(
+            "title".to_string(),
+            json!("xcode-discord-rpc configuration"),
+        );
+    }
+    schema
+}
+
+fn table_schema(keys: &[Key]) -> Value {
+    let properties = keys
+        .iter()
+        .map(|key| {
+            let mut schema = kind_schema(&key.kind);
+            if let Value::Object(object) = &mut schema {
+                object.insert("description".to_string(), json!(key.description));
+            }
+            (key.name.to_string(), schema)
+        })
+        .collect::<Map<_, _>>();
+    let required = keys
+        .iter()
+        .filter(|key| key.required)
+        .map(|key| key.name)
+        .collect::<Vec<_>>();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+fn kind_schema(kind: &Kind) -> Value {
+    match kind {
+        Kind::Bool => json!({ "type": "boolean" }),
+        Kind::Integer { min, max } => json!({ "type": "integer", "minimum": min, "maximum": max }),
+        Kind::Duration { min } => json!({
+            "oneOf": [
+                { "type": "integer", "minimum": min },
+                { "type": "string", "pattern": "^[0-9]+$|^([0-9]+[smhd])+$" },
+            ],
+        }),
+        Kind::String | Kind::Template(_) => json!({ "type": "string" }),
+        Kind::Time => json!({ "type": "string", "pattern": "^[0-2][0-9]:[0-5][0-9]$" }),
+        Kind::Enum(values) => json!({ "type": "string", "enum": values }),
+        Kind::Array(kind) => json!({ "type": "array", "items": kind_schema(kind) }),
+        Kind::Table(keys) => table_schema(keys),
+        Kind::Map { key, value } => json!({
+            "type": "object",
+            "propertyNames": kind_schema(key),
+            "additionalProperties": kind_schema(value),
+        }),
+        Kind::Profile => json!({ "$ref": "#" }),
+    }
+}
+
+/// How serious a problem found in the configuration file is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The configuration cannot be loaded or a value is rejected
+    Error,
+    /// The configuration loads but part of it has no effect
+    Warning,
+}
+
+/// A problem found in the configuration file
+#[derive(Debug, PartialEq, Eq)]
+pub struct Issue {
+    /// Line of the problem starting at 1, if it can be located
+    pub line: Option<usize>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        match self.line {
+            Some(line) => write!(f, "{line}: {severity}: {}", self.message),
+            None => write!(f, "{severity}: {}", self.message),
+        }
+    }
+}
+
+/// Walks a parsed configuration file, collecting the issues
+struct Validator<'s> {
+    source: &'s str,
+    issues: Vec<Issue>,
+}
+
+impl Validator<'_> {
+    /// Returns the line of the byte offset at the start of `span`
+    fn line(&self, span: Option<Range<usize>>) -> Option<usize> {
+        let start = span?.start.min(self.source.len());
+        Some(self.source[..start].matches('\n').count() + 1)
+    }
+
+    fn push(&mut self, line: Option<usize>, severity: Severity, message: String) {
+        self.issues.push(Issue {
+            line,
+            severity,
+            message,
+        });
+    }
+
+    fn check_table(
+        &mut self,
+        table: &dyn TableLike,
+        keys: &[Key],
+        path: &str,
+        line: Option<usize>,
+    ) {
+        for (name, item) in table.iter() {
+            let key_path = join(path, name);
+            let line = self.entry_line(table, name, item).or(line);
+            match keys.iter().find(|key| key.name == name) {
+                Some(key) => self.check_item(item, &key.kind, &key_path, line),
+                None => self.push(line, Severity::Error, format!("unknown key `{key_path}`")),
+            }
+        }
+        for key in keys.iter().filter(|key| key.required) {
+            if table.get(key.name).is_none() {
+                self.push(
+                    line,
+                    Severity::Error,
+                    format!("missing key `{}`", join(path, key.name)),
+                );
+            }
+        }
+    }
+
+    /// Returns the line of the key `name` of `table`, or of its value
+    fn entry_line(&self, table: &dyn TableLike, name: &str, item: &Item) -> Option<usize> {
+        let key_span = table.get_key_value(name).and_then(|(key, _)| key.span());
+        self.line(key_span.or_else(|| item.span()))
+    }
+
+    fn check_item(&mut self, item: &Item, kind: &Kind, path: &str, line: Option<usize>) {
+        let line = self.line(item.span()).or(line);
+        let invalid = |expected: &str| format!("`{path}` must be {expected}");
+        match kind {
+            Kind::Bool => {
+                if item.as_bool().is_none() {
+                    self.push(line, Severity::Error, invalid("true or false"));
+                }
+            }
+            Kind::Integer { min, max } => match item.as_integer() {
+                Some(value) if value < *min => self.push(
+                    line,
+                    Severity::Error,
+                    invalid(&format!("at least {min}, found {value}")),
+                ),
+                Some(value) if value > *max => self.push(
+                    line,
+                    Severity::Error,
+                    invalid(&format!("at most {max}, found {value}")),
+                ),
+                Some(_) => {}
+                None => self.push(line, Severity::Error, invalid("an integer")),
+            },
+            Kind::Duration { min } => {
+                let seconds = match item.as_integer() {
+                    Some(seconds) => u64::try_from(seconds).ok(),
+                    None => item.as_str().and_then(|text| parse_duration(text).ok()),
+                };
+                match seconds {
+                    Some(seconds) if seconds < *min => self.push(
+                        line,
+                        Severity::Error,
+                        invalid(&format!("at least {min}s, found {seconds}s")),
+                    ),
+                    Some(_) => {}
+                    None => self.push(
+                        line,
+                        Severity::Error,
+                        invalid("a duration such as \"30s\" or \"2m\""),
+                    ),
+                }
+            }
+            Kind::String => {
+                if item.as_str().is_none() {
+                    self.push(line, Severity::Error, invalid("a string"));
+                }
+            }
+            Kind::Template(placeholders) => match item.as_str() {
+                Some(template) => {
+                    for placeholder in unknown_placeholders(template, placeholders) {
+                        self.push(
+                            line,
+                            Severity::Warning,
+                            format!("`{path}` has the unknown placeholder {{{placeholder}}}, it is shown as is"),
+                        );
+                    }
+                }
+                None => self.push(line, Severity::Error, invalid("a string")),
+            },
+            Kind::Time => {
+                let time = item.as_str();
+                if time
+                    .and_then(|time| NaiveTime::parse_from_str(time, "%H:%M").ok())
+                    .is_none()
+                {
+                    self.push(line, Severity::Error, invalid("a time written as HH:MM"));
+                }
+            }
+            Kind::Enum(values) => {
+                if !item.as_str().is_some_and(|value| values.contains(&value)) {
+                    self.push(
+                        line,
+                        Severity::Error,
+                        invalid(&format!("one of {}", quote_all(values))),
+                    );
+                }
+            }
+            Kind::Array(kind) => {
+                if let Some(array) = item.as_array() {
+                    for (i, value) in array.iter().enumerate() {
+                        let element = Item::Value(value.clone());
+                        self.check_item(&element, kind, &format!("{path}[{i}]"), line);
+                    }
+                } else if let Some(tables) = item.as_array_of_tables() {
+                    for (i, table) in tables.iter().enumerate() {
+                        let element = Item::Table(table.clone());
+                        self.check_item(&element, kind, &format!("{path}[{i}]"), line);
+                    }
+                } else {
+                    self.push(line, Severity::Error, invalid("an array"));
+                }
+            }
+            Kind::Table(keys) => match item.as_table_like() {
+                Some(table) => self.check_table(table, keys, path, line),
+                None => self.push(line, Severity::Error, invalid("a table")),
+            },
+            Kind::Map { key, value } => match item.as_table_like() {
+                Some(table) => {
+                    for (name, item) in table.iter() {
+                        let entry_path = join(path, name);
+                        let line = self.entry_line(table, name, item).or(line);
+                        if let Kind::Enum(names) = key {
+                            if !names.contains(&name) {
+                                self.push(
+                                    line,
+                                    Severity::Error,
+                                    format!("unknown key `{entry_path}`"),
+                                );
+                                continue;
+                            }
+                        }
+                        self.check_item(item, value, &entry_path, line);
+                    }
+                }
+                None => self.push(line, Severity::Error, invalid("a table")),
+            },
+            Kind::Profile => match item.as_table_like() {
+                Some(table) => {
+                    if table.contains_key("profile") {
+                        self.push(
+                            line,
+                            Severity::Error,
+                            format!("`{path}` cannot contain profiles"),
+                        );
+                    }
+                    self.check_table(table, KEYS, path, line);
+                    self.check_conflicts(table);
+                }
+                None => self.push(line, Severity::Error, invalid("a table")),
+            },
+        }
+    }
+
+    /// Reports options that have no effect because of other options
+    fn check_conflicts(&mut self, table: &dyn TableLike) {
+        let bool_of = |name: &str| table.get(name).and_then(Item::as_bool);
+        let line_of = |name: &str| table.get_key_value(name).and_then(|(key, _)| key.span());
+
+        if bool_of("hide_file") == Some(true) {
+            for name in ["file_display", "unsaved_indicator", "hidden_files"] {
+                if table.contains_key(name) {
+                    self.push(
+                        self.line(line_of(name)),
+                        Severity::Warning,
+                        format!("`{name}` has no effect while `hide_file` is true"),
+                    );
+                }
+            }
+        }
+        if bool_of("hide_project") == Some(true) {
+            for name in [
+                "icon_url_template",
+                "repository_button",
+                "project_aliases",
+                "pseudonymize",
+            ] {
+                if table.contains_key(name) {
+                    self.push(
+                        self.line(line_of(name)),
+                        Severity::Warning,
+                        format!("`{name}` has no effect while `hide_project` is true"),
+                    );
+                }
+            }
+        }
+        if bool_of("language_stats") != Some(true) {
+            for name in ["details_template", "state_template", "browsing_template"] {
+                let Some(template) = table.get(name).and_then(Item::as_str) else {
+                    continue;
+                };
+                if template.contains("{loc}") || template.contains("{top_language}") {
+                    self.push(
+                        self.line(line_of(name)),
+                        Severity::Warning,
+                        format!("`{name}` uses {{loc}} or {{top_language}} but `language_stats` is not enabled"),
+                    );
+                }
+            }
+        }
+        if let Some(buttons) = table.get("buttons") {
+            let count = buttons
+                .as_array()
+                .map(|array| array.len())
+                .or_else(|| buttons.as_array_of_tables().map(|tables| tables.len()))
+                .unwrap_or_default();
+            if count > 2 {
+                self.push(
+                    self.line(line_of("buttons")),
+                    Severity::Warning,
+                    format!("Discord displays at most 2 buttons, {count} are configured"),
+                );
+            }
+        }
+        if let Some(schedule) = table.get("schedule").and_then(Item::as_table_like) {
+            let start = schedule.get("start").and_then(Item::as_str);
+            if start.is_some() && start == schedule.get("end").and_then(Item::as_str) {
+                self.push(
+                    self.line(line_of("schedule")),
+                    Severity::Warning,
+                    "`schedule.start` and `schedule.end` are equal, presence is never shown"
+                        .to_string(),
+                );
+            }
+        }
+        let pull_requests =
+            table
+                .get("rotation")
+                .and_then(Item::as_array)
+                .is_some_and(|rotation| {
+                    rotation
+                        .iter()
+                        .any(|item| item.as_str() == Some("pull_request"))
+                });
+        if pull_requests && bool_of("hide_project") == Some(true) {
+            self.push(
+                self.line(line_of("rotation")),
+                Severity::Warning,
+                "`pull_request` in `rotation` is skipped while `hide_project` is true".to_string(),
+            );
+        }
+    }
+}
+
+/// Joins a key to the path of its table
+fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{path}.{name}")
+    }
+}
+
+/// Formats `values` as a list of quoted strings
+fn quote_all(values: &[&str]) -> String {
+    values
+        .iter()
+        .map(|value| format!("\"{value}\""))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Returns the `{placeholder}`s of `template` that are not in `known`
+fn unknown_placeholders<'t>(template: &'t str, known: &[&str]) -> Vec<&'t str> {
+    let mut unknown = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rest = &rest[start + 1..];
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        let name = &rest[..end];
+        let is_placeholder =
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if is_placeholder && !known.contains(&name) {
+            unknown.push(name);
+        }
+        rest = &rest[end..];
+    }
+    unknown
+}
+
+/// Checks the content of a configuration file against the schema, returning
+/// the issues ordered by line
+pub fn validate(source: &str) -> Vec<Issue> {
+    let mut validator = Validator {
+        source,
+        issues: Vec::new(),
+    };
+    let document = match ImDocument::parse(source) {
+        Ok(document) => document,
+        Err(err) => {
+            let line = validator.line(err.span());
+            validator.push(line, Severity::Error, err.message().trim().to_string());
+            return validator.issues;
+        }
+    };
+    validator.check_table(document.as_table(), KEYS, "", None);
+    validator.check_conflicts(document.as_table());
+
+    // Catches what the schema cannot express, e.g. a value overriding a
+    // default of another type
+    if !validator
+        .issues
+        .iter()
+        .any(|issue| issue.severity == Severity::Error)
+    {
+        let loaded = Config::builder()
+            .add_source(File::from_str(DEFAULT_CONFIG, FileFormat::Toml))
+            .add_source(File::from_str(source, FileFormat::Toml))
+            .build()
+            .and_then(|config| config.try_deserialize::<AppConfig>());
+        if let Err(err) = loaded {
+            validator.push(None, Severity::Error, err.to_string());
+        }
+    }
+
+    validator.issues.sort_by_key(|issue| issue.line);
+    validator.issues
+}
+
+/// Validates the configuration file at `path` and prints its issues, failing
+/// when any of them is an error
+pub fn validate_file(path: &Path) -> Result<()> {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            println!("{} does not exist, the defaults are used", path.display());
+            return Ok(());
+        }
+        Err(err) => return Err(err.into()),
+    };
+    let issues = validate(&source);
+    for issue in &issues {
+        println!("{}:{issue}", path.display());
+    }
+    let errors = issues
+        .iter()
+        .filter(|issue| issue.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        return Err(Error::InvalidConfig(format!(
+            "{errors} error(s) in {}",
+            path.display()
+        )));
+    }
+    if issues.is_empty() {
+        println!("{} is valid", path.display());
+    }
+    Ok(())
+}
+
+/// Validates the user configuration file
+pub fn validate_config() -> Result<()> {
+    let path =
+        config_path().ok_or_else(|| Error::InvalidConfig("no home directory found".to_string()))?;
+    validate_file(&path)
+}
+
+/// Prints the JSON Schema of the configuration file
+pub fn print_schema() -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(&json_schema())?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::locale::Text;
+
+    fn messages(source: &str) -> Vec<(Option<usize>, Severity, String)> {
+        validate(source)
+            .into_iter()
+            .map(|issue| (issue.line, issue.severity, issue.message))
+            .collect()
+    }
+
+    #[test]
+    fn accepts_default_config() {
+        assert_eq!(validate(DEFAULT_CONFIG), vec![]);
+    }
+
+    #[test]
+    fn reports_unknown_keys_with_line() {
+        let source = "hide_file = true\nhide_fiel = true\n\n[mqtt]\nhots = \"localhost\"\n";
+        assert_eq!(
+            messages(source),
+            vec![
+                (
+                    Some(2),
+                    Severity::Error,
+                    "unknown key `hide_fiel`".to_string()
+                ),
+                (
+                    Some(5),
+                    Severity::Error,
+                    "unknown key `mqtt.hots`".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_out_of_range_integers() {
+        assert_eq!(
+            messages("[pomodoro]\nwork_minutes = 0\n"),
+            vec![(
+                Some(2),
+                Severity::Error,
+                "`pomodoro.work_minutes` must be at least 1, found 0".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn checks_durations() {
+        assert!(messages("update_interval = \"1m30s\"\nidle_threshold = 45\n").is_empty());
+        assert_eq!(
+            messages("update_interval = \"0s\"\nidle_threshold = \"soon\"\n"),
+            vec![
+                (
+                    Some(1),
+                    Severity::Error,
+                    "`update_interval` must be at least 1s, found 0s".to_string()
+                ),
+                (
+                    Some(2),
+                    Severity::Error,
+                    "`idle_threshold` must be a duration such as \"30s\" or \"2m\"".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_invalid_values() {
+        let source = "idle_action = \"sleep\"\n[schedule]\nstart = \"9am\"\nend = \"17:00\"\n";
+        let issues = messages(source);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].0, Some(1));
+        assert_eq!(issues[1].0, Some(3));
+        assert!(issues[1].2.contains("HH:MM"));
+    }
+
+    #[test]
+    fn reports_missing_required_keys() {
+        let issues = messages("[[buttons]]\nlabel = \"Website\"\n");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].2, "missing key `buttons[0].url`");
+    }
+
+    #[test]
+    fn warns_about_conflicts() {
+        let issues = messages("hide_file = true\nfile_display = \"parent/name\"\n");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].0, Some(2));
+        assert_eq!(issues[0].1, Severity::Warning);
+    }
+
+    #[test]
+    fn validates_profiles() {
+        let source = "[profile.work]\nhide_project = true\nhide_fiel = true\n[profile.work.mqtt]\nport = 0\n";
+        let issues = messages(source);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].2, "unknown key `profile.work.hide_fiel`");
+        assert_eq!(issues[1].0, Some(5));
+    }
+
+    #[test]
+    fn reports_syntax_errors() {
+        let issues = messages("hide_file = true\nhide_project =\n");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].0, Some(2));
+    }
+
+    #[test]
+    fn finds_unknown_placeholders() {
+        assert_eq!(
+            unknown_placeholders("Editing {file} in {projct} {}", &["file", "project"]),
+            vec!["projct"]
+        );
+    }
+
+    #[test]
+    fn finds_list_keys() {
+        let list_keys = list_keys();
+        assert!(list_keys.contains(&"streamer_apps".to_string()));
+        assert!(list_keys.contains(&"schedule.days".to_string()));
+        assert!(!list_keys.contains(&"buttons".to_string()));
+    }
+
+    #[test]
+    fn translatable_texts_exist() {
+        for text in TEXTS {
+            let deserializer =
+                serde::de::value::StrDeserializer::<serde::de::value::Error>::new(text);
+            assert!(Text::deserialize(deserializer).is_ok(), "{text}");
+        }
+    }
+}
@@ -0,0 +1,432 @@
+use std::{
+    cell::Cell,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use config::{Config, File, FileFormat};
+use serde::Deserialize;
+
+use crate::{
+    utils::{
+        duration::deserialize_seconds,
+        osascript_parser::{
+            classify_title, parse_project, FocusedFile, FrontApp, FrontWindow, ProjectInfo,
+        },
+    },
+    xcode::Xcode,
+    Error, Result,
+};
+
+/// What a running Xcode shows
+#[derive(Debug, Clone)]
+pub struct Scene {
+    pub project: Option<ProjectInfo>,
+    /// Version of Xcode, e.g. `Xcode 16.2`
+    pub version: Option<String>,
+    /// Version of the Swift toolchain, e.g. `Swift 6.0`
+    pub swift_version: Option<String>,
+    /// Name of the active scheme
+    pub scheme: Option<String>,
+    pub file: FocusedFile,
+    /// Path of the focused document
+    pub file_path: Option<PathBuf>,
+    pub unsaved: bool,
+    /// Number of source documents open in the front window
+    pub open_files: usize,
+    /// Number of projects open in Xcode windows, including `project`
+    pub open_projects: usize,
+    pub frontmost: bool,
+    pub commit_sheet: bool,
+    /// Whether the SwiftUI preview canvas has the focus
+    pub preview_focused: bool,
+    /// Whether a playground is executing
+    pub playground_running: bool,
+    /// Application in front of Xcode, if any
+    pub front_app: Option<FrontApp>,
+    /// Status of the activity view in the toolbar
+    pub activity_status: String,
+    /// Names of the physical devices connected to the Mac
+    pub connected_devices: Vec<String>,
+    /// Applications running besides Xcode, e.g. `OBS`
+    pub running_apps: Vec<String>,
+    /// Whether the Mac is on battery or in Low Power Mode
+    pub power_saving: bool,
+}
+
+impl Scene {
+    /// Returns the front window showing the focused document
+    pub fn front_window(&self) -> FrontWindow {
+        FrontWindow {
+            file: self.file.clone(),
+            unsaved: self.unsaved,
+            open_files: self.open_files,
+        }
+    }
+
+    /// Returns the frontmost application, Xcode unless another application
+    /// is in front
+    pub fn front_app(&self) -> FrontApp {
+        match &self.front_app {
+            Some(app) => app.clone(),
+            None => FrontApp {
+                name: if self.frontmost { "Xcode" } else { "Finder" }.to_string(),
+                window_title: String::new(),
+            },
+        }
+    }
+
+    /// Returns the path of the document called `name` if it is focused
+    pub fn file_path(&self, name: &str) -> Option<PathBuf> {
+        match &self.file {
+            FocusedFile::Document(file) if file == name => self.file_path.clone(),
+            _ => None,
+        }
+    }
+}
+
+/// A step of a scenario file, lasting `duration`
+#[derive(Debug, Deserialize)]
+struct Step {
+    #[serde(deserialize_with = "deserialize_seconds")]
+    duration: u64,
+    #[serde(default = "default_true")]
+    running: bool,
+    /// Name of the workspace document, e.g. `MyApp.xcodeproj`
+    project: Option<String>,
+    /// Path of the workspace document
+    project_path: Option<String>,
+    /// Version of Xcode, e.g. `Xcode 16.2`
+    version: Option<String>,
+    /// Version of the Swift toolchain, e.g. `Swift 6.0`
+    swift_version: Option<String>,
+    /// Name of the active scheme
+    scheme: Option<String>,
+    /// Name of the focused document, `Developer Documentation` for the
+    /// documentation window or `Comparison` for the comparison editor
+    file: Option<String>,
+    file_path: Option<PathBuf>,
+    #[serde(default)]
+    unsaved: bool,
+    /// Number of source documents open, 1 when `file` is a document
+    open_files: Option<usize>,
+    /// Number of projects open, 1 when `project` is set
+    open_projects: Option<usize>,
+    #[serde(default = "default_true")]
+    frontmost: bool,
+    #[serde(default)]
+    commit_sheet: bool,
+    #[serde(default)]
+    preview_focused: bool,
+    #[serde(default)]
+    playground_running: bool,
+    /// Application in front of Xcode, e.g. `Reality Composer Pro`
+    front_app: Option<String>,
+    /// Title of the front window of `front_app`
+    #[serde(default)]
+    front_window: String,
+    /// Status of the activity view, e.g. `Running MyApp on Hamza's iPhone`
+    #[serde(default)]
+    activity_status: String,
+    #[serde(default)]
+    connected_devices: Vec<String>,
+    /// Applications running besides Xcode, e.g. `OBS`
+    #[serde(default)]
+    running_apps: Vec<String>,
+    #[serde(default)]
+    power_saving: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Step {
+    /// Returns the scene of the step, `None` when Xcode is not running
+    fn scene(self) -> Option<Scene> {
+        let file = match self.file {
+            Some(file) => classify_title(&file).unwrap_or(FocusedFile::Document(file)),
+            None => FocusedFile::NoDocument,
+        };
+        let open_files = self
+            .open_files
+            .unwrap_or(usize::from(matches!(file, FocusedFile::Document(_))));
+        let project = self
+            .project
+            .as_deref()
+            .and_then(|name| parse_project(name, self.project_path.as_deref().unwrap_or_default()));
+        let open_projects = self.open_projects.unwrap_or(usize::from(project.is_some()));
+        self.running.then(|| Scene {
+            project,
+            version: self.version,
+            swift_version: self.swift_version,
+            scheme: self.scheme,
+            file,
+            file_path: self.file_path,
+            unsaved: self.unsaved,
+            open_files,
+            open_projects,
+            frontmost: self.frontmost,
+            commit_sheet: self.commit_sheet,
+            preview_focused: self.preview_focused,
+            playground_running: self.playground_running,
+            front_app: self.front_app.map(|name| FrontApp {
+                name,
+                window_title: self.front_window,
+            }),
+            activity_status: self.activity_status,
+            connected_devices: self.connected_devices,
+            running_apps: self.running_apps,
+            power_saving: self.power_saving,
+        })
+    }
+}
+
+/// A scenario file passed with `--simulate`
+#[derive(Debug, Deserialize)]
+struct Scenario {
+    /// Whether to start over after the last step instead of staying on it
+    #[serde(default)]
+    repeat: bool,
+    step: Vec<Step>,
+}
+
+/// Xcode going through the steps of a scenario file as time passes, so that
+/// the presence loop can run without a Mac. The timeline starts when the
+/// scenario is loaded and is shared by the clones of the simulation.
+#[derive(Debug, Clone)]
+pub struct SimulatedXcode {
+    steps: Vec<(Duration, Option<Scene>)>,
+    repeat: bool,
+    started: Instant,
+    /// Index of the step logged last
+    current: Cell<Option<usize>>,
+}
+
+impl SimulatedXcode {
+    /// Loads the scenario file at `path`
+    pub fn load(path: &Path) -> Result<Self> {
+        let scenario = Config::builder()
+            .add_source(File::from(path).format(FileFormat::Toml))
+            .build()?
+            .try_deserialize()?;
+        Self::new(scenario)
+    }
+
+    fn new(scenario: Scenario) -> Result<Self> {
+        if scenario.step.is_empty() {
+            return Err(Error::Scenario("the scenario has no [[step]]".to_string()));
+        }
+        if let Some(index) = scenario.step.iter().position(|step| step.duration == 0) {
+            return Err(Error::Scenario(format!(
+                "step {} lasts 0s, steps last at least 1s",
+                index + 1
+            )));
+        }
+        Ok(Self {
+            steps: scenario
+                .step
+                .into_iter()
+                .map(|step| (Duration::from_secs(step.duration), step.scene()))
+                .collect(),
+            repeat: scenario.repeat,
+            started: Instant::now(),
+            current: Cell::new(None),
+        })
+    }
+
+    /// Returns the index of the step shown `elapsed` after the start
+    fn step_at(&self, elapsed: Duration) -> usize {
+        let total: Duration = self.steps.iter().map(|(duration, _)| *duration).sum();
+        let mut elapsed = if self.repeat {
+            Duration::from_nanos((elapsed.as_nanos() % total.as_nanos()) as u64)
+        } else {
+            elapsed
+        };
+        for (index, (duration, _)) in self.steps.iter().enumerate() {
+            if elapsed < *duration {
+                return index;
+            }
+            elapsed -= *duration;
+        }
+        self.steps.len() - 1
+    }
+
+    /// Returns the current scene, `None` while Xcode is not running
+    fn current_scene(&self) -> Option<&Scene> {
+        let index = self.step_at(self.started.elapsed());
+        if self.current.replace(Some(index)) != Some(index) {
+            log::info!("Simulating step {} of {}", index + 1, self.steps.len());
+        }
+        self.steps[index].1.as_ref()
+    }
+
+    /// Returns a value of the current scene
+    fn scene<T>(&self, value: impl FnOnce(&Scene) -> T) -> Result<T> {
+        self.current_scene()
+            .map(value)
+            .ok_or_else(|| Error::Oascript("Xcode is not running".to_string()))
+    }
+}
+
+impl Xcode for SimulatedXcode {
+    fn is_running(&self) -> Result<bool> {
+        Ok(self.current_scene().is_some())
+    }
+
+    fn is_frontmost(&self) -> Result<bool> {
+        self.scene(|scene| scene.frontmost && scene.front_app.is_none())
+    }
+
+    fn front_app(&self) -> Result<FrontApp> {
+        self.scene(Scene::front_app)
+    }
+
+    fn booted_simulators(&self) -> Result<Vec<String>> {
+        // The device is read from the title of the Simulator window instead
+        self.scene(|_| Vec::new())
+    }
+
+    fn current_project(&self) -> Result<Option<ProjectInfo>> {
+        self.scene(|scene| scene.project.clone())
+    }
+
+    fn version(&self) -> Result<Option<String>> {
+        self.scene(|scene| scene.version.clone())
+    }
+
+    fn swift_version(&self) -> Result<Option<String>> {
+        self.scene(|scene| scene.swift_version.clone())
+    }
+
+    fn active_scheme(&self) -> Result<Option<String>> {
+        self.scene(|scene| scene.scheme.clone())
+    }
+
+    fn front_window(&self) -> Result<FrontWindow> {
+        self.scene(Scene::front_window)
+    }
+
+    fn file_path(&self, name: &str) -> Result<Option<PathBuf>> {
+        self.scene(|scene| scene.file_path(name))
+    }
+
+    fn is_commit_sheet_open(&self) -> Result<bool> {
+        self.scene(|scene| scene.commit_sheet)
+    }
+
+    fn is_preview_focused(&self) -> Result<bool> {
+        self.scene(|scene| scene.preview_focused)
+    }
+
+    fn activity_status(&self) -> Result<String> {
+        self.scene(|scene| scene.activity_status.clone())
+    }
+
+    fn connected_devices(&self) -> Result<Vec<String>> {
+        self.scene(|scene| scene.connected_devices.clone())
+    }
+
+    fn is_playground_running(&self) -> Result<bool> {
+        self.scene(|scene| scene.playground_running)
+    }
+
+    fn open_project_count(&self) -> Result<usize> {
+        self.scene(|scene| scene.open_projects)
+    }
+
+    fn is_any_app_running(&self, names: &[String]) -> Result<bool> {
+        self.scene(|scene| scene.running_apps.iter().any(|app| names.contains(app)))
+    }
+
+    fn is_power_saving(&self) -> Result<bool> {
+        self.scene(|scene| scene.power_saving)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::osascript_parser::ProjectKind;
+
+    fn simulation(scenario: &str) -> Result<SimulatedXcode> {
+        let scenario = Config::builder()
+            .add_source(File::from_str(scenario, FileFormat::Toml))
+            .build()?
+            .try_deserialize()?;
+        SimulatedXcode::new(scenario)
+    }
+
+    const SCENARIO: &str = r#"
+        [[step]]
+        duration = "1m"
+        project = "Demo.xcodeproj"
+        project_path = "/Users/me/Demo/Demo.xcodeproj"
+        file = "main.swift"
+        unsaved = true
+
+        [[step]]
+        duration = 30
+        project = "Demo.xcodeproj"
+        file = "Developer Documentation"
+        frontmost = false
+
+        [[step]]
+        duration = "2m"
+        running = false
+    "#;
+
+    #[test]
+    fn reads_scenario() {
+        let simulation = simulation(SCENARIO).unwrap();
+        let durations: Vec<_> = simulation.steps.iter().map(|(d, _)| d.as_secs()).collect();
+        assert_eq!(durations, [60, 30, 120]);
+
+        let editing = simulation.steps[0].1.as_ref().unwrap();
+        let project = editing.project.as_ref().unwrap();
+        assert_eq!(project.name, "Demo");
+        assert_eq!(project.kind, ProjectKind::Project);
+        assert_eq!(
+            editing.front_window(),
+            FrontWindow {
+                file: FocusedFile::Document("main.swift".to_string()),
+                unsaved: true,
+                open_files: 1,
+            }
+        );
+        assert!(editing.frontmost);
+
+        let browsing = simulation.steps[1].1.as_ref().unwrap();
+        assert_eq!(browsing.file, FocusedFile::Documentation);
+        assert!(!browsing.frontmost);
+        assert!(simulation.steps[2].1.is_none());
+    }
+
+    #[test]
+    fn follows_timeline() {
+        let mut simulation = simulation(SCENARIO).unwrap();
+        let at = |secs| Duration::from_secs(secs);
+        assert_eq!(simulation.step_at(at(0)), 0);
+        assert_eq!(simulation.step_at(at(59)), 0);
+        assert_eq!(simulation.step_at(at(60)), 1);
+        assert_eq!(simulation.step_at(at(90)), 2);
+        assert_eq!(simulation.step_at(at(1000)), 2);
+
+        simulation.repeat = true;
+        assert_eq!(simulation.step_at(at(210)), 0);
+        assert_eq!(simulation.step_at(at(275)), 1);
+    }
+
+    #[test]
+    fn rejects_invalid_scenarios() {
+        assert!(matches!(simulation("step = []"), Err(Error::Scenario(_))));
+        assert!(matches!(
+            simulation("[[step]]\nduration = 0"),
+            Err(Error::Scenario(_))
+        ));
+        assert!(matches!(
+            simulation("[[step]]\nduration = \"soon\""),
+            Err(Error::Config(_))
+        ));
+    }
+}
@@ -0,0 +1,303 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::PathBuf,
+};
+
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+
+use crate::{utils::data_dir, Result};
+
+/// Name of the file in the data directory holding the statistics
+const STATS_FILE: &str = "stats.json";
+/// Interval in seconds between two writes of the statistics
+const SAVE_INTERVAL: i64 = 60;
+/// Break in seconds after which working on the same project starts a new session
+const SESSION_GAP: i64 = 30 * 60;
+
+/// Active time of one day
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct DayStats {
+    /// Seconds spent working in Xcode, excluding idle and hidden time
+    pub active_seconds: u64,
+    /// Active seconds per project name
+    #[serde(default)]
+    pub projects: BTreeMap<String, u64>,
+    /// Active seconds per language of the focused file
+    #[serde(default)]
+    pub languages: BTreeMap<String, u64>,
+}
+
+/// Uninterrupted stretch of work on one project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    /// UNIX time of the first update of the session
+    pub start: i64,
+    /// UNIX time of the last update of the session
+    pub end: i64,
+    pub project: String,
+    /// Number of distinct files focused, hidden files are not counted
+    pub files: usize,
+    /// Active seconds per language of the focused file
+    #[serde(default)]
+    pub languages: BTreeMap<String, u64>,
+    /// Seconds spent idle or hidden between the start and the end
+    pub idle_seconds: u64,
+}
+
+/// Active time per day, keyed by local date (`YYYY-MM-DD`), and the sessions
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Stats {
+    #[serde(default)]
+    pub days: BTreeMap<String, DayStats>,
+    #[serde(default)]
+    pub sessions: Vec<Session>,
+}
+
+impl Stats {
+    /// Reads the statistics from the data directory
+    pub fn load() -> Result<Self> {
+        let path = stats_path()?;
+        if !path.exists() {
+            return Ok(Stats::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Writes the statistics to the data directory
+    pub fn save(&self) -> Result<()> {
+        fs::write(stats_path()?, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Path of the statistics file
+fn stats_path() -> Result<PathBuf> {
+    Ok(data_dir()?.join(STATS_FILE))
+}
+
+/// Local date of the UNIX time `time` as used for the keys of `Stats::days`
+fn day_key(time: i64) -> String {
+    Local
+        .timestamp_opt(time, 0)
+        .single()
+        .unwrap_or_else(Local::now)
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+/// Accumulates the time the working presence is shown
+#[derive(Debug, Default)]
+pub struct StatsTracker {
+    stats: Stats,
+    /// Time of the last tick, `None` while not working
+    last_tick: Option<i64>,
+    /// When counting was paused, to add the pause to the session's idle time
+    paused_at: Option<i64>,
+    /// Whether the last of `stats.sessions` is still being extended
+    session_open: bool,
+    /// Files focused in the open session
+    session_files: HashSet<String>,
+    saved_at: i64,
+}
+
+impl StatsTracker {
+    /// Creates a tracker continuing from the saved statistics at UNIX time `now`
+    pub fn load(now: i64) -> Self {
+        let stats = Stats::load().unwrap_or_else(|err| {
+            log::warn!("Failed to load stats: {}", err);
+            Stats::default()
+        });
+        StatsTracker {
+            stats,
+            saved_at: now,
+            ..Default::default()
+        }
+    }
+
+    /// Adds the time since the previous tick to the active time of `project`
+    /// and `language` on the day of `now`, and extends the session with
+    /// `file`. Gaps longer than `max_gap` seconds, e.g. after the Mac slept,
+    /// are not counted.
+    pub fn tick(
+        &mut self,
+        now: i64,
+        project: &str,
+        file: Option<&str>,
+        language: Option<&str>,
+        max_gap: i64,
+    ) {
+        let session = self.update_session(project, file, now);
+        if let Some(last_tick) = self.last_tick {
+            let elapsed = now - last_tick;
+            if (0..=max_gap).contains(&elapsed) {
+                let elapsed = elapsed as u64;
+                let day = self.stats.days.entry(day_key(now)).or_default();
+                day.active_seconds += elapsed;
+                *day.projects.entry(project.to_string()).or_default() += elapsed;
+                if let Some(language) = language {
+                    *day.languages.entry(language.to_string()).or_default() += elapsed;
+                    let session = &mut self.stats.sessions[session];
+                    *session.languages.entry(language.to_string()).or_default() += elapsed;
+                }
+            }
+        }
+        self.last_tick = Some(now);
+        if now - self.saved_at >= SAVE_INTERVAL {
+            self.save(now);
+        }
+    }
+
+    /// Extends the open session, or starts a new one when the project changed
+    /// or the last pause was longer than `SESSION_GAP`. Returns its index.
+    fn update_session(&mut self, project: &str, file: Option<&str>, now: i64) -> usize {
+        let paused_for = self.paused_at.take().map(|paused_at| now - paused_at);
+        let continues = self.session_open
+            && self
+                .stats
+                .sessions
+                .last()
+                .is_some_and(|session| session.project == project)
+            && paused_for.is_none_or(|paused_for| paused_for <= SESSION_GAP);
+        if !continues {
+            self.session_files.clear();
+            self.stats.sessions.push(Session {
+                start: now,
+                end: now,
+                project: project.to_string(),
+                files: 0,
+                languages: BTreeMap::new(),
+                idle_seconds: 0,
+            });
+            self.session_open = true;
+        }
+        if let Some(file) = file {
+            self.session_files.insert(file.to_string());
+        }
+        let index = self.stats.sessions.len() - 1;
+        let session = &mut self.stats.sessions[index];
+        if continues {
+            session.idle_seconds += paused_for.unwrap_or_default().max(0) as u64;
+        }
+        session.end = now;
+        session.files = self.session_files.len();
+        index
+    }
+
+    /// Stops counting until the next tick, e.g. while idle or hidden
+    pub fn pause(&mut self, now: i64) {
+        if self.last_tick.take().is_some() {
+            self.paused_at = Some(now);
+            self.save(now);
+        }
+    }
+
+    /// Returns the recorded sessions, the last one possibly still open
+    #[cfg(test)]
+    pub fn sessions(&self) -> &[Session] {
+        &self.stats.sessions
+    }
+
+    /// Returns the active time on `project` on the day of `now` in seconds
+    pub fn today_on(&self, now: i64, project: &str) -> u64 {
+        self.stats
+            .days
+            .get(&day_key(now))
+            .and_then(|day| day.projects.get(project))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the active time on the day of `now` in seconds
+    pub fn today(&self, now: i64) -> u64 {
+        self.stats
+            .days
+            .get(&day_key(now))
+            .map_or(0, |day| day.active_seconds)
+    }
+
+    fn save(&mut self, now: i64) {
+        self.saved_at = now;
+        if let Err(err) = self.stats.save() {
+            log::warn!("Failed to save stats: {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    /// Tracker starting at `NOW` that is never saved, as the statistics file
+    /// is shared by the tests
+    fn tracker() -> StatsTracker {
+        StatsTracker {
+            saved_at: i64::MAX,
+            ..Default::default()
+        }
+    }
+
+    /// Pauses like `StatsTracker::pause`, without saving
+    fn pause(tracker: &mut StatsTracker, now: i64) {
+        tracker.last_tick = None;
+        tracker.paused_at = Some(now);
+    }
+
+    #[test]
+    fn counts_active_time_per_project_and_language() {
+        let mut tracker = tracker();
+        tracker.tick(NOW, "App", Some("main.swift"), Some("Swift"), 60);
+        tracker.tick(NOW + 10, "App", Some("main.swift"), Some("Swift"), 60);
+        tracker.tick(NOW + 15, "Kit", None, None, 60);
+        // The Mac slept in between
+        tracker.tick(NOW + 1000, "Kit", None, None, 60);
+        assert_eq!(tracker.today(NOW), 15);
+        assert_eq!(tracker.today_on(NOW, "App"), 10);
+        assert_eq!(tracker.today_on(NOW, "Kit"), 5);
+        let day = &tracker.stats.days[&day_key(NOW)];
+        assert_eq!(day.languages, BTreeMap::from([("Swift".to_string(), 10)]));
+    }
+
+    #[test]
+    fn starts_new_session_for_other_project_or_after_long_break() {
+        let mut tracker = tracker();
+        tracker.tick(NOW, "App", Some("main.swift"), Some("Swift"), 60);
+        tracker.tick(NOW + 10, "App", Some("App.swift"), Some("Swift"), 60);
+        pause(&mut tracker, NOW + 10);
+        tracker.tick(NOW + 70, "App", Some("main.swift"), Some("Swift"), 60);
+        tracker.tick(NOW + 80, "Kit", None, None, 60);
+        pause(&mut tracker, NOW + 80);
+        tracker.tick(NOW + 80 + SESSION_GAP + 1, "Kit", None, None, 60);
+        let sessions = tracker
+            .sessions()
+            .iter()
+            .map(|session| {
+                let project = session.project.as_str();
+                (
+                    project,
+                    session.start,
+                    session.end,
+                    session.files,
+                    session.idle_seconds,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sessions,
+            [
+                ("App", NOW, NOW + 70, 2, 60),
+                ("Kit", NOW + 80, NOW + 80, 0, 0),
+                (
+                    "Kit",
+                    NOW + 80 + SESSION_GAP + 1,
+                    NOW + 80 + SESSION_GAP + 1,
+                    0,
+                    0
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    time::SystemTime,
+};
+
+use serde_json::Value;
+
+use crate::{
+    derived_data::project_dirs,
+    locale::{Locale, Text},
+    watchdog::WatchedCommand,
+    Error, Result,
+};
+
+/// Folder of a result bundle that Xcode fills while the tests are running
+const STAGING_DIR: &str = "Staging";
+/// How deep the staging folder is searched for the output of the tests
+const MAX_STAGING_DEPTH: usize = 6;
+
+/// Tests running in Xcode, with the number of finished tests and the number
+/// of tests of the previous run of the project
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TestProgress {
+    pub finished: u32,
+    pub total: Option<u32>,
+}
+
+impl TestProgress {
+    /// Returns the text shown in Discord Rich Presence while tests are running
+    pub fn localized_summary(&self, locale: &Locale) -> String {
+        let finished = self.finished.to_string();
+        match self.total.filter(|total| *total >= self.finished) {
+            Some(total) => locale.format(
+                Text::RunningTests,
+                &[("finished", &finished), ("total", &total.to_string())],
+            ),
+            None => locale.format(Text::RunningTestsCount, &[("finished", &finished)]),
+        }
+    }
+}
+
+/// Outcome of the most recent test run of a project
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TestResult {
+    pub passed: u32,
+    pub failed: u32,
+    pub skipped: u32,
+}
+
+impl TestResult {
+    /// Returns the number of tests of the run
+    pub fn total(&self) -> u32 {
+        self.passed + self.failed + self.skipped
+    }
+
+    /// Returns the text shown in Discord Rich Presence for the test result
+    pub fn localized_summary(&self, locale: &Locale) -> String {
+        if self.failed == 0 {
+            locale.format(Text::TestsPassed, &[("passed", &self.passed.to_string())])
+        } else {
+            locale.format(
+                Text::TestsFailed,
+                &[
+                    ("failed", &self.failed.to_string()),
+                    ("total", &self.total().to_string()),
+                ],
+            )
+        }
+    }
+}
+
+/// What the test logs of a project show
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestRun {
+    Running(TestProgress),
+    Finished(TestResult),
+}
+
+/// Counts the tests that passed or failed in the output of a test runner,
+/// from the lines XCTest and Swift Testing print after each test
+pub fn count_finished_tests(output: &str) -> u32 {
+    output
+        .lines()
+        .filter(|line| {
+            let xctest = line.contains("Test Case '")
+                && (line.contains("' passed (") || line.contains("' failed ("));
+            let swift_testing = (line.starts_with("✔ Test ") || line.starts_with("✘ Test "))
+                && (line.contains(" passed after ") || line.contains(" failed after "));
+            xctest || swift_testing
+        })
+        .count() as u32
+}
+
+/// Parses the summary printed by `xcresulttool get test-results summary`
+pub fn parse_summary(output: &str) -> Result<TestResult> {
+    let summary: Value =
+        serde_json::from_str(output).map_err(|err| Error::BuildLog(err.to_string()))?;
+    let count = |key: &str| {
+        summary[key]
+            .as_u64()
+            .map(|count| count as u32)
+            .ok_or_else(|| Error::BuildLog(format!("{key} not found in the test summary")))
+    };
+    Ok(TestResult {
+        passed: count("passedTests")?,
+        failed: count("failedTests")?,
+        skipped: count("skippedTests").unwrap_or(0),
+    })
+}
+
+/// Reads the summary of the finished result bundle at `path`
+fn read_summary(path: &Path) -> Result<TestResult> {
+    let output = Command::new("xcrun")
+        .args(["xcresulttool", "get", "test-results", "summary", "--path"])
+        .arg(path)
+        .watched_output()
+        .map_err(|err| Error::BuildLog(err.to_string()))?;
+    if !output.status.success() {
+        return Err(Error::BuildLog(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    parse_summary(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Counts the finished tests in the output files below the staging folder
+/// `dir` of a running result bundle
+fn count_staged_tests(dir: &Path, depth: usize) -> u32 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| {
+            let path = entry.path();
+            if path.is_dir() && depth < MAX_STAGING_DEPTH {
+                count_staged_tests(&path, depth + 1)
+            } else if path.extension().is_some_and(|ext| ext == "txt") {
+                fs::read_to_string(&path)
+                    .map(|output| count_finished_tests(&output))
+                    .unwrap_or(0)
+            } else {
+                0
+            }
+        })
+        .sum()
+}
+
+/// Watches the DerivedData test logs of a project for running and finished
+/// test runs
+pub struct TestLogWatcher {
+    /// When the watcher was created, older runs are not reported
+    started: SystemTime,
+    /// Modification time of the newest result bundle already reported
+    last_seen: SystemTime,
+    /// Number of tests of the last finished run
+    last_total: Option<u32>,
+}
+
+impl TestLogWatcher {
+    /// Creates a watcher that only reports test runs from now on
+    pub fn new() -> Self {
+        let now = SystemTime::now();
+        Self {
+            started: now,
+            last_seen: now,
+            last_total: None,
+        }
+    }
+
+    /// Returns the progress of the tests of `project` while they run, or
+    /// their result once if they finished since the last call
+    pub fn poll(&mut self, project: &str) -> Option<TestRun> {
+        let (path, modified) = newest_result_bundle(project)?;
+        let staging = path.join(STAGING_DIR);
+        if staging.is_dir() {
+            return (modified >= self.started).then(|| {
+                TestRun::Running(TestProgress {
+                    finished: count_staged_tests(&staging, 0),
+                    total: self.last_total,
+                })
+            });
+        }
+        if modified <= self.last_seen {
+            return None;
+        }
+        self.last_seen = modified;
+        match read_summary(&path) {
+            Ok(result) => {
+                log::debug!("Tests finished: {:?}", result);
+                self.last_total = Some(result.total());
+                Some(TestRun::Finished(result))
+            }
+            Err(err) => {
+                log::debug!("Failed to read test results {}: {}", path.display(), err);
+                None
+            }
+        }
+    }
+}
+
+/// Finds the newest `.xcresult` in the DerivedData folders of `project`
+fn newest_result_bundle(project: &str) -> Option<(PathBuf, SystemTime)> {
+    project_dirs(project)
+        .into_iter()
+        .filter_map(|dir| fs::read_dir(dir.join("Logs/Test")).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .is_some_and(|ext| ext == "xcresult")
+        })
+        .filter_map(|entry| Some((entry.path(), entry.metadata().ok()?.modified().ok()?)))
+        .max_by_key(|(_, modified)| *modified)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_finished_tests() {
+        let output = "\
+Test Suite 'All tests' started at 2024-05-01 10:00:00.000.
+Test Case '-[MyAppTests testLogin]' started.
+Test Case '-[MyAppTests testLogin]' passed (0.012 seconds).
+Test Case '-[MyAppTests testLogout]' failed (0.020 seconds).
+✔ Test parsesInput() passed after 0.001 seconds.
+✘ Test rejectsInput() failed after 0.002 seconds with 1 issue.
+";
+        assert_eq!(count_finished_tests(output), 4);
+    }
+
+    #[test]
+    fn parses_summary() {
+        let output = r#"{
+            "result": "Failed",
+            "totalTestCount": 120,
+            "passedTests": 117,
+            "failedTests": 2,
+            "skippedTests": 1
+        }"#;
+        let result = parse_summary(output).unwrap();
+        assert_eq!(result.total(), 120);
+        assert_eq!(
+            result.localized_summary(&Locale::default()),
+            "Tests failed ❌ (2 of 120)"
+        );
+        assert!(parse_summary("{}").is_err());
+    }
+
+    #[test]
+    fn summarizes_progress() {
+        let locale = Locale::default();
+        let progress = TestProgress {
+            finished: 42,
+            total: Some(120),
+        };
+        assert_eq!(
+            progress.localized_summary(&locale),
+            "Running tests (42/120)…"
+        );
+        let progress = TestProgress {
+            finished: 130,
+            ..progress
+        };
+        assert_eq!(progress.localized_summary(&locale), "Running tests (130)…");
+    }
+}
@@ -0,0 +1,126 @@
+use std::{
+    fs,
+    io::{self, Read, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    sync::{Mutex, MutexGuard},
+    thread::{self, JoinHandle},
+};
+
+use serde_json::{json, Value};
+
+use super::sandbox;
+
+/// Opcodes of the frames of the Discord IPC protocol
+const HANDSHAKE: u32 = 0;
+const FRAME: u32 = 1;
+const CLOSE: u32 = 2;
+
+/// Client ID of the connection telling the server to stop
+const STOP_CLIENT_ID: &str = "stop";
+
+/// Tests sharing the socket of the sandbox run one after the other
+static SOCKET: Mutex<()> = Mutex::new(());
+
+/// A Discord client listening on the IPC socket of the sandbox, recording
+/// the activities it receives
+pub struct FakeDiscord {
+    server: JoinHandle<Vec<Value>>,
+    _socket: MutexGuard<'static, ()>,
+}
+
+impl FakeDiscord {
+    /// Starts listening on `discord-ipc-0` in the runtime directory of the
+    /// sandbox
+    pub fn start() -> Self {
+        let socket = SOCKET.lock().unwrap_or_else(|err| err.into_inner());
+        let path = sandbox().join("discord-ipc-0");
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).expect("Discord socket can be bound");
+        let server = thread::spawn(move || {
+            let mut activities = Vec::new();
+            // Connections are served in the order they were made, so the
+            // stop connection comes after every connection of the client
+            for stream in listener.incoming() {
+                match serve(stream.expect("connection is accepted"), &mut activities) {
+                    Ok(true) => break,
+                    Ok(false) => {}
+                    Err(err) => panic!("invalid Discord IPC frame: {err}"),
+                }
+            }
+            activities
+        });
+        Self {
+            server,
+            _socket: socket,
+        }
+    }
+
+    /// Stops the server once the connections of the client are closed,
+    /// returning the activities it received, `null` for a cleared activity
+    pub fn stop(self) -> Vec<Value> {
+        let mut stream =
+            UnixStream::connect(sandbox().join("discord-ipc-0")).expect("server is listening");
+        write_frame(
+            &mut stream,
+            HANDSHAKE,
+            &json!({ "v": 1, "client_id": STOP_CLIENT_ID }),
+        )
+        .expect("stop frame can be written");
+        self.server.join().expect("server did not panic")
+    }
+}
+
+/// Serves a connection until the client closes it, returning whether it asked
+/// the server to stop
+fn serve(mut stream: UnixStream, activities: &mut Vec<Value>) -> io::Result<bool> {
+    let (opcode, handshake) = read_frame(&mut stream)?.ok_or(io::ErrorKind::UnexpectedEof)?;
+    if opcode != HANDSHAKE {
+        return Err(io::Error::other(format!(
+            "expected a handshake, got {opcode}"
+        )));
+    }
+    if handshake["client_id"] == STOP_CLIENT_ID {
+        return Ok(true);
+    }
+    write_frame(
+        &mut stream,
+        FRAME,
+        &json!({
+            "cmd": "DISPATCH",
+            "evt": "READY",
+            "data": { "v": 1, "user": { "id": "0", "username": "test" } },
+        }),
+    )?;
+    while let Some((opcode, payload)) = read_frame(&mut stream)? {
+        match opcode {
+            FRAME if payload["cmd"] == "SET_ACTIVITY" => {
+                activities.push(payload["args"]["activity"].clone());
+            }
+            CLOSE => break,
+            _ => {}
+        }
+    }
+    Ok(false)
+}
+
+/// Reads a frame, `None` once the client closed the connection
+fn read_frame(stream: &mut UnixStream) -> io::Result<Option<(u32, Value)>> {
+    let mut header = [0; 8];
+    match stream.read_exact(&mut header) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err),
+    }
+    let opcode = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+    let length = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+    let mut payload = vec![0; length as usize];
+    stream.read_exact(&mut payload)?;
+    Ok(Some((opcode, serde_json::from_slice(&payload)?)))
+}
+
+fn write_frame(stream: &mut UnixStream, opcode: u32, payload: &Value) -> io::Result<()> {
+    let payload = payload.to_string();
+    stream.write_all(&opcode.to_le_bytes())?;
+    stream.write_all(&(payload.len() as u32).to_le_bytes())?;
+    stream.write_all(payload.as_bytes())
+}
@@ -0,0 +1,124 @@
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// A request received by `FakeHttp`
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    pub method: String,
+    /// Path with the query string
+    pub path: String,
+    /// Header lines, e.g. `Authorization: Bearer token`
+    pub headers: Vec<String>,
+    pub body: String,
+}
+
+/// An HTTP server on a free port of localhost answering every request with
+/// 200 and the same body, recording the requests it receives
+pub struct FakeHttp {
+    port: u16,
+    stopped: Arc<AtomicBool>,
+    server: JoinHandle<Vec<HttpRequest>>,
+}
+
+impl FakeHttp {
+    /// Starts answering requests with `body`
+    pub fn start(body: &str) -> Self {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).expect("HTTP port can be bound");
+        listener
+            .set_nonblocking(true)
+            .expect("HTTP listener can be non-blocking");
+        let port = listener.local_addr().expect("HTTP port is known").port();
+        let stopped = Arc::new(AtomicBool::new(false));
+        let body = body.to_string();
+        let server = thread::spawn({
+            let stopped = stopped.clone();
+            move || {
+                let mut requests = Vec::new();
+                loop {
+                    match listener.accept() {
+                        Ok((stream, _)) => match serve(stream, &body) {
+                            Ok(request) => requests.push(request),
+                            Err(err) => panic!("invalid HTTP request: {err}"),
+                        },
+                        Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                            if stopped.load(Ordering::SeqCst) {
+                                break;
+                            }
+                            thread::sleep(Duration::from_millis(10));
+                        }
+                        Err(err) => panic!("connection is not accepted: {err}"),
+                    }
+                }
+                requests
+            }
+        });
+        Self {
+            port,
+            stopped,
+            server,
+        }
+    }
+
+    /// Base URL of the server, without a trailing slash
+    pub fn url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+
+    /// Stops the server once no connection is pending, returning the
+    /// requests it received
+    pub fn stop(self) -> Vec<HttpRequest> {
+        self.stopped.store(true, Ordering::SeqCst);
+        self.server.join().expect("server did not panic")
+    }
+}
+
+/// Reads a request and answers it with `body`
+fn serve(stream: TcpStream, body: &str) -> io::Result<HttpRequest> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(&stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+    let mut headers = Vec::new();
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().unwrap_or_default();
+            }
+        }
+        headers.push(header.to_string());
+    }
+    let mut request_body = vec![0; length];
+    reader.read_exact(&mut request_body)?;
+    write!(
+        &stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        body.len(),
+        if method == "HEAD" { "" } else { body }
+    )?;
+    Ok(HttpRequest {
+        method,
+        path,
+        headers,
+        body: String::from_utf8_lossy(&request_body).to_string(),
+    })
+}
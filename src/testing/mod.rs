@@ -0,0 +1,78 @@
+//! Helpers of the end-to-end tests of the presence loop
+
+use std::{env, fs, iter, os::unix::fs::PermissionsExt, path::PathBuf, sync::OnceLock};
+
+use config::{Config, File, FileFormat};
+
+use crate::config::{AppConfig, DEFAULT_CONFIG};
+
+pub mod discord_server;
+pub mod http_server;
+pub mod scripted_xcode;
+
+/// Scripts standing in for the command-line tools of the integrations:
+/// `security` reads every secret as `<account>-token`, `mosquitto_pub`
+/// appends each message to `<topic>.mqtt`, its arguments to `<topic>.args`
+/// and its options file to `<topic>.options` in the sandbox, and `osascript`
+/// appends each script to `osascript.log` in the sandbox
+const FAKE_TOOLS: [(&str, &str); 3] = [
+    (
+        "security",
+        "#!/bin/sh\n[ \"$1\" = find-generic-password ] && echo \"$5-token\"\n",
+    ),
+    (
+        "mosquitto_pub",
+        "#!/bin/sh\nargs=\"$*\"\n\
+         while [ $# -gt 0 ]; do [ \"$1\" = -t ] && topic=$2; shift; done\n\
+         echo \"$args\" >> \"$HOME/$topic.args\"\n\
+         cat \"$XDG_CONFIG_HOME/mosquitto_pub\" >> \"$HOME/$topic.options\" 2> /dev/null\n\
+         cat >> \"$HOME/$topic.mqtt\" && echo >> \"$HOME/$topic.mqtt\"\n",
+    ),
+    (
+        "osascript",
+        "#!/bin/sh\n[ \"$1\" = -e ] && echo \"$2\" >> \"$HOME/osascript.log\"\n",
+    ),
+];
+
+/// Points the home, data and runtime directories of the test process to a
+/// directory of its own, so that tests never touch the files, the Keychain,
+/// the notifications, the MQTT broker or the Discord socket of the user
+/// running them. Returns that directory.
+pub fn sandbox() -> &'static PathBuf {
+    static SANDBOX: OnceLock<PathBuf> = OnceLock::new();
+    SANDBOX.get_or_init(|| {
+        let dir = env::temp_dir().join(format!("xcode-discord-rpc-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("sandbox directory can be created");
+        for key in [
+            "HOME",
+            "XDG_CACHE_HOME",
+            "XDG_CONFIG_HOME",
+            "XDG_DATA_HOME",
+            "XDG_RUNTIME_DIR",
+        ] {
+            env::set_var(key, &dir);
+        }
+        let bin = dir.join("tools");
+        fs::create_dir_all(&bin).expect("sandbox directory can be created");
+        for (name, script) in FAKE_TOOLS {
+            let tool = bin.join(name);
+            fs::write(&tool, script).expect("fake tool can be written");
+            fs::set_permissions(&tool, fs::Permissions::from_mode(0o755))
+                .expect("fake tool can be made executable");
+        }
+        let path = env::var_os("PATH").unwrap_or_default();
+        let paths = iter::once(bin).chain(env::split_paths(&path));
+        env::set_var("PATH", env::join_paths(paths).expect("PATH can be joined"));
+        dir
+    })
+}
+
+/// The default configuration with `overrides` written in TOML
+pub fn config(overrides: &str) -> AppConfig {
+    Config::builder()
+        .add_source(File::from_str(DEFAULT_CONFIG, FileFormat::Toml))
+        .add_source(File::from_str(overrides, FileFormat::Toml))
+        .build()
+        .and_then(Config::try_deserialize)
+        .expect("test configuration is valid")
+}
@@ -0,0 +1,146 @@
+use std::{cell::RefCell, collections::VecDeque, path::PathBuf};
+
+use crate::{
+    simulation::Scene,
+    utils::osascript_parser::{FocusedFile, FrontApp, FrontWindow, ProjectInfo, ProjectKind},
+    xcode::Xcode,
+    Error, Result,
+};
+
+impl Scene {
+    /// Editing `file` in the frontmost window of the project `project`
+    pub fn editing(project: &str, file: &str) -> Self {
+        Self {
+            project: Some(ProjectInfo {
+                name: project.to_string(),
+                path: None,
+                kind: ProjectKind::Project,
+            }),
+            version: None,
+            swift_version: None,
+            scheme: Some(project.to_string()),
+            file: FocusedFile::Document(file.to_string()),
+            file_path: None,
+            unsaved: false,
+            open_files: 1,
+            open_projects: 1,
+            frontmost: true,
+            commit_sheet: false,
+            preview_focused: false,
+            playground_running: false,
+            front_app: None,
+            activity_status: String::new(),
+            connected_devices: Vec::new(),
+            running_apps: Vec::new(),
+            power_saving: false,
+        }
+    }
+}
+
+/// Xcode going through a scripted sequence of scenes, `None` while it is
+/// not running. The presence loop moves to the next scene each time it
+/// checks whether Xcode is running, and fails once the script is over.
+pub struct ScriptedXcode {
+    script: RefCell<VecDeque<Option<Scene>>>,
+    scene: RefCell<Option<Scene>>,
+}
+
+impl ScriptedXcode {
+    pub fn new(script: impl IntoIterator<Item = Option<Scene>>) -> Self {
+        Self {
+            script: RefCell::new(script.into_iter().collect()),
+            scene: RefCell::new(None),
+        }
+    }
+
+    /// Returns a value of the current scene
+    fn scene<T>(&self, value: impl FnOnce(&Scene) -> T) -> Result<T> {
+        self.scene
+            .borrow()
+            .as_ref()
+            .map(value)
+            .ok_or_else(|| Error::Oascript("Xcode is not running".to_string()))
+    }
+}
+
+impl Xcode for ScriptedXcode {
+    fn is_running(&self) -> Result<bool> {
+        let scene = self
+            .script
+            .borrow_mut()
+            .pop_front()
+            .ok_or_else(|| Error::Oascript("the script is over".to_string()))?;
+        let running = scene.is_some();
+        *self.scene.borrow_mut() = scene;
+        Ok(running)
+    }
+
+    fn is_frontmost(&self) -> Result<bool> {
+        self.scene(|scene| scene.frontmost && scene.front_app.is_none())
+    }
+
+    fn front_app(&self) -> Result<FrontApp> {
+        self.scene(Scene::front_app)
+    }
+
+    fn booted_simulators(&self) -> Result<Vec<String>> {
+        // The device is read from the title of the Simulator window instead
+        self.scene(|_| Vec::new())
+    }
+
+    fn current_project(&self) -> Result<Option<ProjectInfo>> {
+        self.scene(|scene| scene.project.clone())
+    }
+
+    fn version(&self) -> Result<Option<String>> {
+        self.scene(|scene| scene.version.clone())
+    }
+
+    fn swift_version(&self) -> Result<Option<String>> {
+        self.scene(|scene| scene.swift_version.clone())
+    }
+
+    fn active_scheme(&self) -> Result<Option<String>> {
+        self.scene(|scene| scene.scheme.clone())
+    }
+
+    fn front_window(&self) -> Result<FrontWindow> {
+        self.scene(Scene::front_window)
+    }
+
+    fn file_path(&self, name: &str) -> Result<Option<PathBuf>> {
+        self.scene(|scene| scene.file_path(name))
+    }
+
+    fn is_commit_sheet_open(&self) -> Result<bool> {
+        self.scene(|scene| scene.commit_sheet)
+    }
+
+    fn is_preview_focused(&self) -> Result<bool> {
+        self.scene(|scene| scene.preview_focused)
+    }
+
+    fn activity_status(&self) -> Result<String> {
+        self.scene(|scene| scene.activity_status.clone())
+    }
+
+    fn connected_devices(&self) -> Result<Vec<String>> {
+        self.scene(|scene| scene.connected_devices.clone())
+    }
+
+    fn is_playground_running(&self) -> Result<bool> {
+        self.scene(|scene| scene.playground_running)
+    }
+
+    fn open_project_count(&self) -> Result<usize> {
+        self.scene(|scene| scene.open_projects)
+    }
+
+    fn is_any_app_running(&self, names: &[String]) -> Result<bool> {
+        self.scene(|scene| scene.running_apps.iter().any(|app| names.contains(app)))
+    }
+
+    fn is_power_saving(&self) -> Result<bool> {
+        self.scene(|scene| scene.power_saving)
+    }
+}
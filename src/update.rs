@@ -0,0 +1,278 @@
+use std::{
+    fs,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::Command,
+    thread,
+    time::Duration,
+};
+
+use serde::Deserialize;
+
+use crate::{
+    integrations::http::request,
+    utils::{github::github_slug, osascript::run_osascript},
+    Error, Result,
+};
+
+/// Name of the universal archive attached to every release
+const UNIVERSAL_ASSET: &str = "xcode-discord-rpc.tar.gz";
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReleaseAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+impl Release {
+    /// Finds the asset called `name`
+    fn asset(&self, name: &str) -> Option<&ReleaseAsset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+}
+
+/// Parses a version such as `v1.2.0` into its numeric components
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let mut parts = version
+        .trim()
+        .trim_start_matches('v')
+        .split(['.', '-', '+'])
+        .map(|part| part.parse::<u64>().ok());
+    Some((
+        parts.next()??,
+        parts.next()??,
+        parts.next().flatten().unwrap_or(0),
+    ))
+}
+
+/// Returns whether `latest` is a newer version than `current`
+fn is_newer(latest: &str, current: &str) -> bool {
+    match (parse_version(latest), parse_version(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
+}
+
+/// Name of the archive built for the architecture of the running binary
+fn arch_asset() -> String {
+    format!(
+        "xcode-discord-rpc-{}-apple-darwin.tar.gz",
+        std::env::consts::ARCH
+    )
+}
+
+/// Fetches the latest release from GitHub Releases
+fn latest_release() -> Result<Release> {
+    let slug = github_slug(env!("CARGO_PKG_REPOSITORY"))
+        .ok_or_else(|| Error::Update("the repository is not on GitHub".to_string()))?;
+    let body = request(
+        "GET",
+        &format!("https://api.github.com/repos/{slug}/releases/latest"),
+        &["Accept: application/vnd.github+json".to_string()],
+        None::<&()>,
+    )?;
+    Ok(serde_json::from_str(&body)?)
+}
+
+/// Downloads `url` to `path`
+fn download(url: &str, path: &Path) -> Result<()> {
+    let status = Command::new("curl")
+        .args(["-sfL", "-m", "300", "-o"])
+        .arg(path)
+        .arg(url)
+        .status()
+        .map_err(|err| Error::Update(err.to_string()))?;
+    if !status.success() {
+        return Err(Error::Update(format!("download of {url} failed")));
+    }
+    Ok(())
+}
+
+/// Computes the SHA-256 of the file at `path` with `shasum`
+fn sha256(path: &Path) -> Result<String> {
+    let output = Command::new("shasum")
+        .args(["-a", "256"])
+        .arg(path)
+        .output()
+        .map_err(|err| Error::Update(err.to_string()))?;
+    if !output.status.success() {
+        return Err(Error::Update(format!(
+            "shasum failed with {}",
+            output.status
+        )));
+    }
+    checksum(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| Error::Update("shasum printed no checksum".to_string()))
+}
+
+/// Reads the checksum from a `shasum` line such as `<hex>  <file>`
+fn checksum(line: &str) -> Option<String> {
+    line.split_whitespace()
+        .next()
+        .filter(|hash| hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()))
+        .map(str::to_ascii_lowercase)
+}
+
+/// Checks that the SHA-256 of `archive` is the one in `checksum_file`
+fn verify_checksum(archive: &Path, checksum_file: &Path) -> Result<()> {
+    let name = |path: &Path| {
+        path.file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned()
+    };
+    let expected = checksum(&fs::read_to_string(checksum_file)?)
+        .ok_or_else(|| Error::Update(format!("{} is malformed", name(checksum_file))))?;
+    if sha256(archive)? != expected {
+        return Err(Error::Update(format!(
+            "checksum of {} does not match",
+            name(archive)
+        )));
+    }
+    Ok(())
+}
+
+/// Downloads the archive of `release` for this architecture, verifies it
+/// against its `.sha256` asset and returns the path of the extracted binary
+fn download_binary(release: &Release, dir: &Path) -> Result<PathBuf> {
+    let arch_asset = arch_asset();
+    let asset = release
+        .asset(&arch_asset)
+        .or_else(|| release.asset(UNIVERSAL_ASSET))
+        .ok_or_else(|| Error::Update(format!("{} has no binary for macOS", release.tag_name)))?;
+    let checksum_asset = release
+        .asset(&format!("{}.sha256", asset.name))
+        .ok_or_else(|| Error::Update(format!("{} has no checksum", asset.name)))?;
+
+    let archive = dir.join(&asset.name);
+    let checksum_file = dir.join(&checksum_asset.name);
+    download(&asset.browser_download_url, &archive)?;
+    download(&checksum_asset.browser_download_url, &checksum_file)?;
+    verify_checksum(&archive, &checksum_file)?;
+
+    let status = Command::new("tar")
+        .arg("-xzf")
+        .arg(&archive)
+        .arg("-C")
+        .arg(dir)
+        .status()
+        .map_err(|err| Error::Update(err.to_string()))?;
+    if !status.success() {
+        return Err(Error::Update(format!("failed to extract {}", asset.name)));
+    }
+    Ok(dir.join(env!("CARGO_PKG_NAME")))
+}
+
+/// Replaces the running executable with `binary`. The new file is moved into
+/// place with a rename, so the running instance keeps its old copy.
+fn replace_executable(binary: &Path) -> Result<()> {
+    let executable = std::env::current_exe()?.canonicalize()?;
+    let staged = executable.with_extension("new");
+    fs::copy(binary, &staged)?;
+    fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))?;
+    fs::rename(&staged, &executable)?;
+    Ok(())
+}
+
+/// Updates the executable to the latest release, if it is newer
+pub fn update() -> Result<()> {
+    let release = latest_release()?;
+    let current = env!("CARGO_PKG_VERSION");
+    if !is_newer(&release.tag_name, current) {
+        println!("xcode-discord-rpc {current} is up to date");
+        return Ok(());
+    }
+    println!(
+        "Updating xcode-discord-rpc {current} to {}",
+        release.tag_name
+    );
+    let dir = std::env::temp_dir().join(format!("xcode-discord-rpc-{}", release.tag_name));
+    fs::create_dir_all(&dir)?;
+    let result = download_binary(&release, &dir).and_then(|binary| replace_executable(&binary));
+    let _ = fs::remove_dir_all(&dir);
+    result?;
+    println!(
+        "Updated to {}, restart the running instance to use it",
+        release.tag_name
+    );
+    Ok(())
+}
+
+/// Checks for a newer release every `interval` seconds in a background thread
+/// and shows a notification once per new version
+pub fn spawn_update_check(interval: u64) {
+    thread::spawn(move || {
+        let mut notified = None;
+        loop {
+            match latest_release() {
+                Ok(release)
+                    if is_newer(&release.tag_name, env!("CARGO_PKG_VERSION"))
+                        && notified.as_ref() != Some(&release.tag_name) =>
+                {
+                    log::info!("xcode-discord-rpc {} is available", release.tag_name);
+                    let _ = run_osascript(&format!(
+                        r#"display notification "Run xcode-discord-rpc update to install it." with title "xcode-discord-rpc {} is available""#,
+                        release.tag_name.replace('"', "")
+                    ));
+                    notified = Some(release.tag_name);
+                }
+                Ok(_) => {}
+                Err(err) => log::debug!("Failed to check for updates: {}", err),
+            }
+            thread::sleep(Duration::from_secs(interval.max(60)));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_versions() {
+        assert!(is_newer("v1.3.0", "1.2.0"));
+        assert!(is_newer("v2.0", "1.9.9"));
+        assert!(!is_newer("v1.2.0", "1.2.0"));
+        assert!(!is_newer("v1.1.9", "1.2.0"));
+        assert!(!is_newer("nightly", "1.2.0"));
+    }
+
+    #[test]
+    fn reads_shasum_output() {
+        let hash = "A".repeat(64);
+        assert_eq!(
+            checksum(&format!("{hash}  xcode-discord-rpc.tar.gz\n")),
+            Some("a".repeat(64))
+        );
+        assert_eq!(checksum("not a checksum"), None);
+    }
+
+    #[test]
+    fn verifies_downloaded_archive() {
+        let dir =
+            std::env::temp_dir().join(format!("xcode-discord-rpc-update-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let archive = dir.join(UNIVERSAL_ASSET);
+        fs::write(&archive, "release").unwrap();
+        let checksum_file = dir.join(format!("{UNIVERSAL_ASSET}.sha256"));
+        // The SHA-256 of "release"
+        let hash = "a4d451ec23463726f72c43d64c710968f6b602cd653b4de8adee1b556240a829";
+        fs::write(&checksum_file, format!("{hash}  {UNIVERSAL_ASSET}\n")).unwrap();
+        verify_checksum(&archive, &checksum_file).unwrap();
+
+        fs::write(&archive, "tampered").unwrap();
+        let mismatch = verify_checksum(&archive, &checksum_file).unwrap_err();
+        assert_eq!(
+            mismatch.to_string(),
+            Error::Update(format!("checksum of {UNIVERSAL_ASSET} does not match")).to_string()
+        );
+        fs::write(&checksum_file, "").unwrap();
+        assert!(verify_checksum(&archive, &checksum_file).is_err());
+    }
+}
@@ -0,0 +1,168 @@
+use std::{collections::HashMap, fs, path::Path};
+
+/// Name and bundle identifier of the app built by an Xcode project, for
+/// `{app_name}` and `{bundle_id}`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AppInfo {
+    /// Name shown below the app icon, e.g. "Cool Notes"
+    pub name: Option<String>,
+    /// Bundle identifier, e.g. `com.example.notes`
+    pub bundle_id: Option<String>,
+}
+
+/// Reads the build settings blocks of a `project.pbxproj`, one map per
+/// build configuration
+fn build_settings(pbxproj: &str) -> Vec<HashMap<&str, &str>> {
+    let mut blocks = Vec::new();
+    let mut current: Option<HashMap<&str, &str>> = None;
+    for line in pbxproj.lines().map(str::trim) {
+        if line.starts_with("buildSettings = {") {
+            current = Some(HashMap::new());
+        } else if line.starts_with("};") {
+            blocks.extend(current.take());
+        } else if let Some(settings) = current.as_mut() {
+            if let Some((key, value)) = line.split_once(" = ") {
+                let value = value.trim_end_matches(';').trim_matches('"');
+                settings.insert(key.trim_matches('"'), value);
+            }
+        }
+    }
+    blocks
+}
+
+/// Returns `value` unless it is empty or refers to another build setting,
+/// like `$(TARGET_NAME)`
+fn literal(value: &str) -> Option<&str> {
+    (!value.is_empty() && !value.contains("$(")).then_some(value)
+}
+
+/// Returns the string value of `key` in the XML property list `plist`
+fn plist_string(plist: &str, key: &str) -> Option<String> {
+    let (_, rest) = plist.split_once(&format!("<key>{key}</key>"))?;
+    let rest = rest.trim_start().strip_prefix("<string>")?;
+    let (value, _) = rest.split_once("</string>")?;
+    literal(value.trim()).map(str::to_string)
+}
+
+/// Finds the app of a `project.pbxproj`, reading the Info.plist files it
+/// refers to with `read_plist`. The first build configuration setting a
+/// display name wins, as test targets have none.
+pub fn parse_app_info(pbxproj: &str, read_plist: impl Fn(&str) -> Option<String>) -> AppInfo {
+    let blocks = build_settings(pbxproj);
+    let display_name = |settings: &HashMap<&str, &str>| {
+        let plist = settings
+            .get("INFOPLIST_FILE")
+            .copied()
+            .and_then(literal)
+            .and_then(&read_plist);
+        [
+            "INFOPLIST_KEY_CFBundleDisplayName",
+            "INFOPLIST_KEY_CFBundleName",
+        ]
+        .iter()
+        .find_map(|key| settings.get(key).copied().and_then(literal))
+        .map(str::to_string)
+        .or_else(|| {
+            let plist = plist.as_deref()?;
+            plist_string(plist, "CFBundleDisplayName")
+                .or_else(|| plist_string(plist, "CFBundleName"))
+        })
+    };
+    let bundle_id = |settings: &HashMap<&str, &str>| {
+        settings
+            .get("PRODUCT_BUNDLE_IDENTIFIER")
+            .copied()
+            .and_then(literal)
+            .map(str::to_string)
+    };
+    if let Some((settings, name)) = blocks
+        .iter()
+        .find_map(|settings| Some((settings, display_name(settings)?)))
+    {
+        return AppInfo {
+            name: Some(name),
+            bundle_id: bundle_id(settings),
+        };
+    }
+    AppInfo {
+        name: None,
+        bundle_id: blocks
+            .iter()
+            .filter_map(bundle_id)
+            .find(|id| !id.ends_with("Tests")),
+    }
+}
+
+/// Reads the app of the Xcode project in `root`. Swift packages without an
+/// Xcode project have no app.
+pub fn detect_app_info(root: &Path) -> AppInfo {
+    let pbxproj = fs::read_dir(root)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "xcodeproj"))
+        .find_map(|path| fs::read_to_string(path.join("project.pbxproj")).ok());
+    match pbxproj {
+        Some(pbxproj) => {
+            parse_app_info(&pbxproj, |plist| fs::read_to_string(root.join(plist)).ok())
+        }
+        None => AppInfo::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PBXPROJ: &str = r#"
+        8A1 /* Debug */ = {
+            isa = XCBuildConfiguration;
+            buildSettings = {
+                PRODUCT_BUNDLE_IDENTIFIER = com.example.notesTests;
+                PRODUCT_NAME = "$(TARGET_NAME)";
+            };
+            name = Debug;
+        };
+        8A2 /* Debug */ = {
+            isa = XCBuildConfiguration;
+            buildSettings = {
+                INFOPLIST_KEY_CFBundleDisplayName = "Cool Notes";
+                PRODUCT_BUNDLE_IDENTIFIER = com.example.notes;
+                PRODUCT_NAME = "$(TARGET_NAME)";
+            };
+            name = Debug;
+        };
+    "#;
+
+    #[test]
+    fn reads_generated_info_plist_keys() {
+        let app = parse_app_info(PBXPROJ, |_| None);
+        assert_eq!(app.name.as_deref(), Some("Cool Notes"));
+        assert_eq!(app.bundle_id.as_deref(), Some("com.example.notes"));
+    }
+
+    #[test]
+    fn reads_info_plist_files() {
+        let pbxproj = r#"
+            buildSettings = {
+                INFOPLIST_FILE = "Notes/Info.plist";
+                PRODUCT_BUNDLE_IDENTIFIER = com.example.notes;
+            };
+        "#;
+        let plist =
+            "<dict>\n\t<key>CFBundleDisplayName</key>\n\t<string>Cool Notes</string>\n</dict>";
+        let app = parse_app_info(pbxproj, |path| {
+            (path == "Notes/Info.plist").then(|| plist.to_string())
+        });
+        assert_eq!(app.name.as_deref(), Some("Cool Notes"));
+    }
+
+    #[test]
+    fn skips_test_bundles_without_name() {
+        let pbxproj = PBXPROJ.replace("INFOPLIST_KEY_CFBundleDisplayName = \"Cool Notes\";", "");
+        let app = parse_app_info(&pbxproj, |_| None);
+        assert_eq!(app.name, None);
+        assert_eq!(app.bundle_id.as_deref(), Some("com.example.notes"));
+    }
+}
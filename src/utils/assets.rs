@@ -0,0 +1,48 @@
+/// Represents the assets shown for activities other than editing a file
+#[derive(Debug)]
+pub enum ActivityAsset {
+    /// Reading the developer documentation
+    Documentation,
+    /// Committing changes with source control
+    SourceControl,
+    /// Reviewing changes in the comparison editor
+    Review,
+    /// Designing in Interface Builder or the SwiftUI preview canvas
+    Design,
+    /// Composing a scene in Reality Composer Pro
+    RealityComposerPro,
+    /// Training a model in Create ML
+    CreateMl,
+    /// Testing the app in the Simulator
+    Simulator,
+    /// Running the app on a physical device
+    Device,
+}
+
+impl ActivityAsset {
+    /// Every activity asset
+    pub const ALL: [ActivityAsset; 8] = [
+        ActivityAsset::Documentation,
+        ActivityAsset::SourceControl,
+        ActivityAsset::Review,
+        ActivityAsset::Design,
+        ActivityAsset::RealityComposerPro,
+        ActivityAsset::CreateMl,
+        ActivityAsset::Simulator,
+        ActivityAsset::Device,
+    ];
+
+    /// Returns the asset key and text for the `ActivityAsset` as (text, image)
+    pub fn get_asset_keys(&self) -> (&'static str, &'static str) {
+        match self {
+            ActivityAsset::Documentation => ("Developer Documentation", "docs"),
+            ActivityAsset::SourceControl => ("Source Control", "git"),
+            ActivityAsset::Review => ("Code Review", "review"),
+            ActivityAsset::Design => ("Interface Builder", "design"),
+            ActivityAsset::RealityComposerPro => ("Reality Composer Pro", "reality_composer_pro"),
+            ActivityAsset::CreateMl => ("Create ML", "create_ml"),
+            ActivityAsset::Simulator => ("Simulator", "simulator"),
+            ActivityAsset::Device => ("Physical Device", "device"),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::{
+    fmt::Debug,
+    time::{Duration, Instant},
+};
+
+#[cfg(test)]
+use std::sync::{Arc, Mutex};
+
+use super::{current_time, sleep};
+
+/// Source of time and sleep of the presence loop, shared with the control
+/// socket thread
+pub trait Clock: Debug + Send + Sync {
+    /// Returns the current time in seconds since the UNIX epoch
+    fn now(&self) -> i64;
+
+    /// Returns the current time of the monotonic clock, used for deadlines
+    fn instant(&self) -> Instant;
+
+    /// Sleeps for `duration`, returning the number of seconds the Mac spent
+    /// asleep if it was
+    fn sleep(&self, duration: Duration) -> Option<u64>;
+}
+
+/// The clock of the system
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> i64 {
+        current_time()
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> Option<u64> {
+        sleep(duration)
+    }
+}
+
+/// A clock that only advances when slept on, or when told to. Clones share
+/// the same time.
+#[cfg(test)]
+#[derive(Debug, Clone)]
+pub struct SimulatedClock {
+    start: Instant,
+    /// Time passed since `start`, shared by both clocks
+    elapsed: Arc<Mutex<Duration>>,
+    /// Seconds the system sleeps during the next sleep
+    system_sleep: Arc<Mutex<Option<u64>>>,
+}
+
+#[cfg(test)]
+impl SimulatedClock {
+    /// UNIX time the clock starts at
+    pub const EPOCH: i64 = 1_700_000_000;
+
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: Arc::new(Mutex::new(Duration::ZERO)),
+            system_sleep: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Moves the clock forward by `duration`
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap() += duration;
+    }
+
+    /// Makes the system sleep for `seconds` during the next sleep
+    pub fn sleep_system(&self, seconds: u64) {
+        *self.system_sleep.lock().unwrap() = Some(seconds);
+    }
+}
+
+#[cfg(test)]
+impl Clock for SimulatedClock {
+    fn now(&self) -> i64 {
+        Self::EPOCH + self.elapsed.lock().unwrap().as_secs() as i64
+    }
+
+    fn instant(&self) -> Instant {
+        self.start + *self.elapsed.lock().unwrap()
+    }
+
+    fn sleep(&self, duration: Duration) -> Option<u64> {
+        // Both clocks advance during the system sleep, unlike `Instant`
+        let slept = self.system_sleep.lock().unwrap().take();
+        self.advance(duration + Duration::from_secs(slept.unwrap_or(0)));
+        slept
+    }
+}
@@ -0,0 +1,92 @@
+/// Words a project name is mapped to by `codename`
+const CODENAMES: [&str; 64] = [
+    "Albatross",
+    "Amber",
+    "Anchor",
+    "Aurora",
+    "Badger",
+    "Basalt",
+    "Beacon",
+    "Bison",
+    "Cedar",
+    "Comet",
+    "Condor",
+    "Coral",
+    "Cypress",
+    "Delta",
+    "Ember",
+    "Falcon",
+    "Fjord",
+    "Glacier",
+    "Granite",
+    "Harbor",
+    "Hawk",
+    "Heron",
+    "Horizon",
+    "Ibis",
+    "Indigo",
+    "Jaguar",
+    "Juniper",
+    "Kestrel",
+    "Lagoon",
+    "Lantern",
+    "Lynx",
+    "Magnolia",
+    "Maple",
+    "Marlin",
+    "Meteor",
+    "Mistral",
+    "Nebula",
+    "Nimbus",
+    "Obsidian",
+    "Orca",
+    "Osprey",
+    "Otter",
+    "Pelican",
+    "Phoenix",
+    "Quartz",
+    "Raven",
+    "Redwood",
+    "Sable",
+    "Saffron",
+    "Sequoia",
+    "Sierra",
+    "Sparrow",
+    "Summit",
+    "Tempest",
+    "Thistle",
+    "Tundra",
+    "Umber",
+    "Vesper",
+    "Viper",
+    "Walrus",
+    "Willow",
+    "Yarrow",
+    "Zephyr",
+    "Zenith",
+];
+
+/// Returns a codename for `project`, e.g. "Falcon", the same on every run
+/// and every Mac so that viewers can tell projects apart without learning
+/// their names
+pub fn codename(project: &str) -> &'static str {
+    // FNV-1a, as the hash of the standard library may change between releases
+    let hash = project
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    CODENAMES[(hash % CODENAMES.len() as u64) as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derives_stable_codenames() {
+        assert_eq!(codename("MyApp"), codename("MyApp"));
+        assert_eq!(codename("MyApp"), "Obsidian");
+        assert_ne!(codename("MyApp"), codename("MyApp2"));
+    }
+}
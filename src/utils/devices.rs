@@ -0,0 +1,172 @@
+use std::process::Command;
+
+use serde_json::Value;
+
+use crate::{watchdog::WatchedCommand, Error, Result};
+
+/// Separator between the device name and its OS version in the title of a
+/// Simulator window
+const SIMULATOR_TITLE_SEPARATOR: &str = " – ";
+/// Index of the state column of `devicectl list devices`
+const DEVICECTL_STATE_COLUMN: usize = 3;
+
+/// Run `xcrun` with the given arguments and return its output as a String
+fn run_xcrun(args: &[&str]) -> Result<String> {
+    let output = Command::new("xcrun")
+        .args(args)
+        .watched_output()
+        .map_err(|err| Error::Process(err.to_string()))?;
+    if !output.status.success() {
+        return Err(Error::Process(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Get the names of the booted simulators
+pub fn booted_simulators() -> Result<Vec<String>> {
+    let output = run_xcrun(&["simctl", "list", "devices", "booted", "--json"])?;
+    parse_booted_simulators(&output)
+}
+
+/// Parses the output of `simctl list devices --json`, returning the names of
+/// the booted devices of every runtime
+fn parse_booted_simulators(output: &str) -> Result<Vec<String>> {
+    let list: Value =
+        serde_json::from_str(output).map_err(|err| Error::Process(err.to_string()))?;
+    let runtimes = list["devices"]
+        .as_object()
+        .into_iter()
+        .flat_map(|runtimes| runtimes.values());
+    Ok(runtimes
+        .filter_map(Value::as_array)
+        .flatten()
+        .filter(|device| device["state"] == "Booted")
+        .filter_map(|device| device["name"].as_str())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Get the names of the physical devices paired with the Mac that are
+/// connected or available over the network
+pub fn connected_devices() -> Result<Vec<String>> {
+    let output = run_xcrun(&["devicectl", "list", "devices"])?;
+    Ok(parse_connected_devices(&output))
+}
+
+/// Parses the table printed by `devicectl list devices`, whose columns are
+/// aligned below a line of dashes
+fn parse_connected_devices(output: &str) -> Vec<String> {
+    let mut lines = output.lines();
+    let Some(dashes) = lines.by_ref().find(|line| line.starts_with('-')) else {
+        return Vec::new();
+    };
+    // Names contain spaces, so the columns are split where the dashes start
+    let columns: Vec<usize> = dashes
+        .char_indices()
+        .filter(|&(index, c)| c == '-' && (index == 0 || dashes[..index].ends_with(' ')))
+        .map(|(index, _)| dashes[..index].chars().count())
+        .collect();
+    let column = |line: &[char], index: usize| -> String {
+        let start = columns
+            .get(index)
+            .copied()
+            .unwrap_or(line.len())
+            .min(line.len());
+        let end = columns
+            .get(index + 1)
+            .copied()
+            .unwrap_or(line.len())
+            .min(line.len());
+        line[start..end]
+            .iter()
+            .collect::<String>()
+            .trim()
+            .to_string()
+    };
+    lines
+        .map(|line| line.chars().collect::<Vec<_>>())
+        .filter(|line| {
+            let state = column(line, DEVICECTL_STATE_COLUMN);
+            state == "connected" || state.starts_with("available")
+        })
+        .map(|line| column(&line, 0))
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Returns the model part of a device name such as "Hamza's iPhone", so the
+/// name of its owner is not shown
+pub fn redact_device_name(name: &str) -> Option<&str> {
+    ["'s ", "’s "]
+        .iter()
+        .find_map(|possessive| name.split_once(possessive))
+        .map(|(_, model)| model.trim())
+        .filter(|model| !model.is_empty())
+}
+
+/// Returns the name of the device shown in the Simulator window titled
+/// `window_title`, e.g. "iPhone 15" for "iPhone 15 – iOS 17.0"
+pub fn simulator_device(window_title: &str) -> Option<&str> {
+    let device = window_title
+        .split(SIMULATOR_TITLE_SEPARATOR)
+        .next()
+        .unwrap_or_default()
+        .trim();
+    (!device.is_empty()).then_some(device)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_booted_simulators() {
+        let output = r#"{
+            "devices": {
+                "com.apple.CoreSimulator.SimRuntime.iOS-17-0": [
+                    { "name": "iPhone 15", "state": "Booted", "udid": "A" },
+                    { "name": "iPad Air", "state": "Shutdown", "udid": "B" }
+                ],
+                "com.apple.CoreSimulator.SimRuntime.watchOS-10-0": []
+            }
+        }"#;
+        assert_eq!(parse_booted_simulators(output).unwrap(), ["iPhone 15"]);
+        assert!(parse_booted_simulators("not json").is_err());
+    }
+
+    #[test]
+    fn parses_connected_devices() {
+        let output = "\
+Devices:
+Name             Hostname                         Identifier   State                Model
+--------------   ------------------------------   ----------   ------------------   -------------
+Hamza’s iPhone   Hamzas-iPhone.coredevice.local   1A2B         connected            iPhone 15 Pro
+Home iPad        Home-iPad.coredevice.local       3C4D         available (paired)   iPad Air
+Old iPhone       Old-iPhone.coredevice.local      5E6F         unavailable          iPhone X
+";
+        assert_eq!(
+            parse_connected_devices(output),
+            ["Hamza’s iPhone", "Home iPad"]
+        );
+        assert!(parse_connected_devices("No devices found.").is_empty());
+    }
+
+    #[test]
+    fn redacts_owner() {
+        assert_eq!(redact_device_name("Hamza's iPhone"), Some("iPhone"));
+        assert_eq!(redact_device_name("Hamza’s iPad Pro"), Some("iPad Pro"));
+        assert_eq!(redact_device_name("Test Device"), None);
+    }
+
+    #[test]
+    fn reads_device_from_window_title() {
+        assert_eq!(simulator_device("iPhone 15 – iOS 17.0"), Some("iPhone 15"));
+        assert_eq!(
+            simulator_device("Apple Vision Pro"),
+            Some("Apple Vision Pro")
+        );
+        assert_eq!(simulator_device(""), None);
+    }
+}
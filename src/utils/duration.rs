@@ -0,0 +1,96 @@
+use serde::{de::Error as _, Deserialize, Deserializer};
+
+use crate::{Error, Result};
+
+/// Parses a duration such as `90s`, `30m`, `1h` or `1h30m` into seconds.
+/// A bare number is read as seconds.
+pub fn parse_duration(input: &str) -> Result<u64> {
+    let input = input.trim();
+    let invalid = || Error::Duration(input.to_string());
+    if input.is_empty() {
+        return Err(invalid());
+    }
+    if let Ok(seconds) = input.parse::<u64>() {
+        return Ok(seconds);
+    }
+
+    let mut total: u64 = 0;
+    let mut number = String::new();
+    for c in input.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            _ => return Err(invalid()),
+        };
+        let value: u64 = number.parse().map_err(|_| invalid())?;
+        total = value
+            .checked_mul(unit)
+            .and_then(|seconds| total.checked_add(seconds))
+            .ok_or_else(invalid)?;
+        number.clear();
+    }
+    if !number.is_empty() {
+        return Err(invalid());
+    }
+    Ok(total)
+}
+
+/// Deserializes a duration written as a string such as `"30s"` or `"2m"`,
+/// or as a number of seconds, into seconds
+pub fn deserialize_seconds<'de, D, T>(deserializer: D) -> std::result::Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TryFrom<u64>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Seconds {
+        Number(u64),
+        Text(String),
+    }
+
+    let seconds = match Seconds::deserialize(deserializer)? {
+        Seconds::Number(seconds) => seconds,
+        Seconds::Text(text) => parse_duration(&text).map_err(D::Error::custom)?,
+    };
+    T::try_from(seconds)
+        .map_err(|_| D::Error::custom(format!("duration of {seconds}s is too long")))
+}
+
+/// Deserializes an optional duration like `deserialize_seconds`, for fields
+/// marked `#[serde(default)]`
+pub fn deserialize_optional_seconds<'de, D, T>(
+    deserializer: D,
+) -> std::result::Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TryFrom<u64>,
+{
+    deserialize_seconds(deserializer).map(Some)
+}
+
+/// Formats seconds as hours rounded to one decimal, such as `3.2h` or `5h`
+pub fn format_hours(seconds: u64) -> String {
+    let tenths = (seconds + 180) / 360;
+    match tenths % 10 {
+        0 => format!("{}h", tenths / 10),
+        decimal => format!("{}.{decimal}h", tenths / 10),
+    }
+}
+
+/// Formats seconds as a human readable duration such as `1h 23m` or `5m`
+pub fn format_duration(seconds: u64) -> String {
+    let hours = seconds / 3600;
+    let minutes = seconds % 3600 / 60;
+    if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else {
+        format!("{minutes}m")
+    }
+}
@@ -0,0 +1,85 @@
+/// Extensions of Interface Builder files
+const INTERFACE_BUILDER_EXTENSIONS: [&str; 2] = ["storyboard", "xib"];
+
+/// What is being done in Xcode, shown as an emoji in the presence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmojiContext {
+    #[default]
+    Editing,
+    Debugging,
+    Designing,
+    Idle,
+}
+
+impl EmojiContext {
+    /// Returns the emoji of the context
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            EmojiContext::Editing => "🛠",
+            EmojiContext::Debugging => "🐞",
+            EmojiContext::Designing => "🎨",
+            EmojiContext::Idle => "💤",
+        }
+    }
+
+    /// Picks the context for a focused file with `extension`. A running
+    /// debugger takes precedence over the kind of file.
+    pub fn for_file(extension: Option<&str>, debugging: bool) -> Self {
+        if debugging {
+            EmojiContext::Debugging
+        } else if extension.is_some_and(|extension| {
+            INTERFACE_BUILDER_EXTENSIONS
+                .iter()
+                .any(|ib| extension.eq_ignore_ascii_case(ib))
+        }) {
+            EmojiContext::Designing
+        } else {
+            EmojiContext::Editing
+        }
+    }
+}
+
+/// Prefixes `text` with `emoji` and a space, unless a template already put
+/// the emoji in front
+pub fn decorate(text: &str, emoji: &str) -> String {
+    if text.starts_with(emoji) {
+        text.to_string()
+    } else {
+        format!("{emoji} {text}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_context_for_file() {
+        assert_eq!(
+            EmojiContext::for_file(Some("storyboard"), false),
+            EmojiContext::Designing
+        );
+        assert_eq!(
+            EmojiContext::for_file(Some("XIB"), false),
+            EmojiContext::Designing
+        );
+        assert_eq!(
+            EmojiContext::for_file(Some("swift"), false),
+            EmojiContext::Editing
+        );
+        assert_eq!(
+            EmojiContext::for_file(Some("storyboard"), true),
+            EmojiContext::Debugging
+        );
+        assert_eq!(EmojiContext::for_file(None, false), EmojiContext::Editing);
+    }
+
+    #[test]
+    fn decorates_once() {
+        assert_eq!(decorate("Idle", "💤"), "💤 Idle");
+        assert_eq!(
+            decorate("🛠 Working on main.swift", "🛠"),
+            "🛠 Working on main.swift"
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+/// How the focused file is shown in `{file}`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum FileDisplay {
+    /// The file name, e.g. `ContentView.swift`
+    #[default]
+    #[serde(rename = "name")]
+    Name,
+    /// The file name without extension, e.g. `ContentView`
+    #[serde(rename = "name_no_ext")]
+    NameNoExt,
+    /// The parent folder and the file name, e.g. `Views/ContentView.swift`
+    #[serde(rename = "parent/name")]
+    ParentName,
+    /// The path relative to the project root, e.g. `MyApp/Views/ContentView.swift`
+    #[serde(rename = "relative_path")]
+    RelativePath,
+}
+
+impl FileDisplay {
+    /// Returns whether the path of the file is needed to display it
+    pub fn needs_path(&self) -> bool {
+        matches!(self, FileDisplay::ParentName | FileDisplay::RelativePath)
+    }
+
+    /// Formats the file called `name` at `path` in the project at `root`,
+    /// falling back to the name when the path is unknown or outside the project
+    pub fn format(&self, name: &str, path: Option<&Path>, root: Option<&Path>) -> String {
+        let formatted = match self {
+            FileDisplay::Name => None,
+            FileDisplay::NameNoExt => Path::new(name)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string()),
+            FileDisplay::ParentName => path
+                .and_then(Path::parent)
+                .and_then(Path::file_name)
+                .map(|parent| format!("{}/{name}", parent.to_string_lossy())),
+            FileDisplay::RelativePath => path
+                .zip(root)
+                .and_then(|(path, root)| path.strip_prefix(root).ok())
+                .map(|relative| relative.to_string_lossy().to_string()),
+        };
+        formatted.unwrap_or_else(|| name.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATH: &str = "/Users/me/MyApp/MyApp/Views/ContentView.swift";
+    const ROOT: &str = "/Users/me/MyApp";
+
+    fn format(display: FileDisplay, path: Option<&str>) -> String {
+        display.format(
+            "ContentView.swift",
+            path.map(Path::new),
+            Some(Path::new(ROOT)),
+        )
+    }
+
+    #[test]
+    fn formats_file_names() {
+        assert_eq!(format(FileDisplay::Name, Some(PATH)), "ContentView.swift");
+        assert_eq!(format(FileDisplay::NameNoExt, None), "ContentView");
+        assert_eq!(
+            format(FileDisplay::ParentName, Some(PATH)),
+            "Views/ContentView.swift"
+        );
+        assert_eq!(
+            format(FileDisplay::RelativePath, Some(PATH)),
+            "MyApp/Views/ContentView.swift"
+        );
+    }
+
+    #[test]
+    fn falls_back_to_name() {
+        assert_eq!(format(FileDisplay::ParentName, None), "ContentView.swift");
+        assert_eq!(
+            format(FileDisplay::RelativePath, Some("/tmp/ContentView.swift")),
+            "ContentView.swift"
+        );
+    }
+}
@@ -22,6 +22,19 @@ pub enum FileLanguage {
 }
 
 impl FileLanguage {
+    /// Every file language, in declaration order
+    pub const ALL: [FileLanguage; 9] = [
+        FileLanguage::Swift,
+        FileLanguage::Cpp,
+        FileLanguage::C,
+        FileLanguage::Ruby,
+        FileLanguage::Java,
+        FileLanguage::Json,
+        FileLanguage::Metal,
+        FileLanguage::ObjectiveC,
+        FileLanguage::Unknown,
+    ];
+
     /// Returns the asset key and text for the `FileLanguage` as (text, image)
     pub fn get_asset_keys(&self) -> (&'static str, &'static str) {
         match self {
@@ -41,11 +54,6 @@ impl FileLanguage {
     pub fn get_text_asset_key(&self) -> &'static str {
         self.get_asset_keys().0
     }
-
-    /// Returns the asset key for the `FileLanguage`
-    pub fn get_image_asset_key(&self) -> &'static str {
-        self.get_asset_keys().1
-    }
 }
 
 /// Trait for converting types to `FileLanguage`
@@ -79,8 +87,63 @@ pub trait FileExtention {
 
 /// Implementation of `FileExtention` for `str`
 impl FileExtention for str {
-    /// Returns the file extension of the string
+    /// Returns the lowercase file extension of the string, empty when the
+    /// name has none
     fn get_file_extension(&self) -> String {
-        self.split('.').next_back().unwrap_or("").trim().to_string()
+        self.rsplit_once('.')
+            .map_or("", |(_, extension)| extension)
+            .trim()
+            .to_ascii_lowercase()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn language(file: &str) -> &'static str {
+        file.get_file_extension()
+            .to_file_language()
+            .get_text_asset_key()
+    }
+
+    /// Pseudo-random file names made of pieces that are easy to get wrong:
+    /// dots, known extensions in any case, spaces and non-ASCII characters
+    fn file_name() -> impl Strategy<Value = String> {
+        const PIECES: [&str; 16] = [
+            "main", "Info", ".", "..", " ", "swift", "SWIFT", "Json", "m", "H", "cXX", "é", "ß",
+            "İ", "🦀", "\u{301}",
+        ];
+        prop::collection::vec(prop::sample::select(&PIECES[..]), 0..7)
+            .prop_map(|pieces| pieces.concat())
+    }
+
+    #[test]
+    fn detects_known_extensions() {
+        assert_eq!(language("AppDelegate.swift"), "Swift");
+        assert_eq!(language("View.Controller.MM"), "Objective-C");
+        assert_eq!(language("package.json "), "Json");
+        assert_eq!(language("Makefile"), "Xcode");
+        assert_eq!(language("c"), "Xcode");
+        assert_eq!(language("main.swift."), "Xcode");
+    }
+
+    proptest! {
+        #[test]
+        fn casing_does_not_change_language(name in file_name()) {
+            prop_assert_eq!(language(&name), language(&name.to_ascii_uppercase()));
+            prop_assert_eq!(language(&name), language(&name.to_ascii_lowercase()));
+        }
+
+        #[test]
+        fn uses_last_extension(name in file_name()) {
+            prop_assert_eq!(language(&format!("{name}.swift")), "Swift");
+            prop_assert_eq!(language(&format!("{name}.")), "Xcode");
+            if !name.contains('.') {
+                prop_assert_eq!(language(&name), "Xcode");
+            }
+        }
     }
 }
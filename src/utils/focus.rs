@@ -0,0 +1,103 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+
+use crate::Result;
+
+/// Directory where macOS stores the Focus (Do Not Disturb) database
+fn do_not_disturb_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/DoNotDisturb/DB"))
+}
+
+/// Get the name of the active macOS Focus mode, or `None` when no Focus is active.
+/// Reading the Focus database requires Full Disk Access.
+pub fn current_focus() -> Result<Option<String>> {
+    match do_not_disturb_dir() {
+        Some(dir) => focus_in(&dir),
+        None => Ok(None),
+    }
+}
+
+/// Reads the active Focus mode from the Focus database in `dir`
+fn focus_in(dir: &Path) -> Result<Option<String>> {
+    let assertions: Value =
+        serde_json::from_str(&fs::read_to_string(dir.join("Assertions.json"))?)?;
+    let Some(mode_identifier) = assertions["data"][0]["storeAssertionRecords"]
+        .as_array()
+        .and_then(|records| records.first())
+        .and_then(|record| record["assertionDetails"]["assertionDetailsModeIdentifier"].as_str())
+        .map(String::from)
+    else {
+        return Ok(None);
+    };
+
+    let configurations: Value =
+        serde_json::from_str(&fs::read_to_string(dir.join("ModeConfigurations.json"))?)?;
+    let name = configurations["data"][0]["modeConfigurations"][&mode_identifier]["mode"]["name"]
+        .as_str()
+        .map(String::from)
+        .unwrap_or(mode_identifier);
+    Ok(Some(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// Directory holding the Focus databases written by the tests
+    fn test_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("xcode-discord-rpc-focus-{}", std::process::id()))
+    }
+
+    fn database(name: &str, mode_identifier: Option<&str>, mode_name: Option<&str>) -> PathBuf {
+        let dir = test_dir().join(name);
+        fs::create_dir_all(&dir).unwrap();
+        let records = match mode_identifier {
+            Some(identifier) => json!([{
+                "assertionDetails": { "assertionDetailsModeIdentifier": identifier },
+            }]),
+            None => json!([]),
+        };
+        let assertions = json!({ "data": [{ "storeAssertionRecords": records }] });
+        let mut configurations = json!({});
+        if let (Some(identifier), Some(name)) = (mode_identifier, mode_name) {
+            configurations[identifier] = json!({ "mode": { "name": name } });
+        }
+        let configurations = json!({ "data": [{ "modeConfigurations": configurations }] });
+        fs::write(dir.join("Assertions.json"), assertions.to_string()).unwrap();
+        fs::write(
+            dir.join("ModeConfigurations.json"),
+            configurations.to_string(),
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn reads_name_of_active_focus() {
+        let dir = database("Named", Some("com.apple.focus.work"), Some("Work"));
+        assert_eq!(focus_in(&dir).unwrap().as_deref(), Some("Work"));
+        // Modes missing from the configurations are named by their identifier
+        let dir = database("Unnamed", Some("com.apple.focus.gaming"), None);
+        assert_eq!(
+            focus_in(&dir).unwrap().as_deref(),
+            Some("com.apple.focus.gaming")
+        );
+    }
+
+    #[test]
+    fn reads_no_focus_when_none_is_active() {
+        let dir = database("Inactive", None, None);
+        assert_eq!(focus_in(&dir).unwrap(), None);
+    }
+
+    #[test]
+    fn fails_without_access_to_database() {
+        assert!(focus_in(&test_dir().join("Missing")).is_err());
+    }
+}
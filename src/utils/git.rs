@@ -0,0 +1,43 @@
+use std::{path::Path, process::Command};
+
+use crate::{watchdog::WatchedCommand, Error, Result};
+
+/// Run `git` with the given arguments in `repo` and return its output as a String
+fn run_git(repo: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .watched_output()
+        .map_err(|err| Error::Git(err.to_string()))?;
+    if !output.status.success() {
+        return Err(Error::Git(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Get the URL of the `origin` remote, or `None` when there is no such remote
+pub fn origin_url(repo: &Path) -> Result<Option<String>> {
+    let remotes = run_git(repo, &["remote"])?;
+    if !remotes.lines().any(|remote| remote == "origin") {
+        return Ok(None);
+    }
+    let url = run_git(repo, &["remote", "get-url", "origin"])?;
+    Ok(Some(url).filter(|url| !url.is_empty()))
+}
+
+/// Count the files with uncommitted changes, including untracked files
+pub fn changed_files(repo: &Path) -> Result<usize> {
+    Ok(run_git(repo, &["status", "--porcelain"])?.lines().count())
+}
+
+/// Get the name of the checked out branch, or `None` for a detached HEAD
+pub fn current_branch(repo: &Path) -> Result<Option<String>> {
+    let branch = run_git(repo, &["rev-parse", "--abbrev-ref", "HEAD"])?;
+    if branch.is_empty() || branch == "HEAD" {
+        return Ok(None);
+    }
+    Ok(Some(branch))
+}
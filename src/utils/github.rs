@@ -0,0 +1,76 @@
+use serde::Deserialize;
+
+use crate::{integrations::http::request, utils::text::percent_encode, Result};
+
+/// Prefix of the web URLs of GitHub repositories
+const GITHUB_URL_PREFIX: &str = "https://github.com/";
+
+#[derive(Deserialize)]
+struct PullRequest {
+    number: u64,
+}
+
+/// Returns the `owner/name` of a GitHub repository from its web URL
+pub fn github_slug(repo_url: &str) -> Option<&str> {
+    repo_url
+        .strip_prefix(GITHUB_URL_PREFIX)
+        .map(|slug| slug.trim_end_matches('/'))
+}
+
+/// Get the number of the open pull request of `branch` in the GitHub
+/// repository at `repo_url`, asking the REST API at `api_url` with `token`
+/// if any
+pub fn open_pull_request(
+    api_url: &str,
+    repo_url: &str,
+    branch: &str,
+    token: Option<&str>,
+) -> Result<Option<u64>> {
+    let Some(slug) = github_slug(repo_url) else {
+        return Ok(None);
+    };
+    let owner = slug.split('/').next().unwrap_or_default();
+    let url = format!(
+        "{}/repos/{slug}/pulls?state=open&head={}",
+        api_url.trim_end_matches('/'),
+        percent_encode(&format!("{owner}:{branch}"))
+    );
+    let mut headers = vec!["Accept: application/vnd.github+json".to_string()];
+    if let Some(token) = token {
+        headers.push(format!("Authorization: Bearer {token}"));
+    }
+    let body = request("GET", &url, &headers, None::<&()>)?;
+    let pull_requests: Vec<PullRequest> = serde_json::from_str(&body)?;
+    Ok(pull_requests
+        .first()
+        .map(|pull_request| pull_request.number))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_slug_of_github_repository() {
+        assert_eq!(
+            github_slug("https://github.com/N0va2/xcode-discord-rpc/"),
+            Some("N0va2/xcode-discord-rpc")
+        );
+        assert_eq!(
+            github_slug("https://gitlab.com/N0va2/xcode-discord-rpc"),
+            None
+        );
+    }
+
+    #[test]
+    fn skips_repositories_off_github() {
+        // Answered without a request, the API URL is never reached
+        let pull_request = open_pull_request(
+            "http://127.0.0.1:9",
+            "https://gitlab.com/N0va2/xcode-discord-rpc",
+            "main",
+            None,
+        );
+        assert_eq!(pull_request.unwrap(), None);
+    }
+}
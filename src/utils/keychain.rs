@@ -0,0 +1,131 @@
+use std::process::Command;
+
+use clap::ValueEnum;
+
+use crate::{Error, Result};
+
+/// Keychain service under which secrets of xcode-discord-rpc are stored
+pub const KEYCHAIN_SERVICE: &str = "xcode-discord-rpc";
+
+/// A token or password of an integration, stored in the login Keychain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Secret {
+    /// GitHub token for pull request lookups
+    Github,
+    /// Slack user token
+    Slack,
+    /// Toggl Track API token
+    Toggl,
+    /// Clockify API key
+    Clockify,
+    /// MQTT broker password
+    Mqtt,
+}
+
+impl Secret {
+    /// Keychain account of the secret
+    pub fn account(&self) -> &'static str {
+        match self {
+            Secret::Github => "github",
+            Secret::Slack => "slack",
+            Secret::Toggl => "toggl",
+            Secret::Clockify => "clockify",
+            Secret::Mqtt => "mqtt",
+        }
+    }
+}
+
+/// Runs `security` with `args`, failing with `message` when it does not succeed
+fn security(args: &[&str], message: String) -> Result<Vec<u8>> {
+    let output = Command::new("security")
+        .args(args)
+        .output()
+        .map_err(|err| Error::Keychain(err.to_string()))?;
+    if !output.status.success() {
+        return Err(Error::Keychain(message));
+    }
+    Ok(output.stdout)
+}
+
+/// Read `secret` from the login Keychain, stored with `secret set <name>`
+pub fn read_secret(secret: Secret) -> Result<String> {
+    let account = secret.account();
+    let stdout = security(
+        &[
+            "find-generic-password",
+            "-s",
+            KEYCHAIN_SERVICE,
+            "-a",
+            account,
+            "-w",
+        ],
+        format!("no secret for {account:?} in service {KEYCHAIN_SERVICE:?}"),
+    )?;
+    Ok(String::from_utf8_lossy(&stdout).trim_end().to_string())
+}
+
+/// Stores `secret` in the login Keychain, replacing an existing one. The value
+/// is prompted for by `security` so it never appears in the process list.
+pub fn write_secret(secret: Secret) -> Result<()> {
+    let account = secret.account();
+    // `-w` as the last option makes `security` prompt for the password
+    let status = Command::new("security")
+        .args([
+            "add-generic-password",
+            "-U",
+            "-s",
+            KEYCHAIN_SERVICE,
+            "-a",
+            account,
+            "-w",
+        ])
+        .status()
+        .map_err(|err| Error::Keychain(err.to_string()))?;
+    if !status.success() {
+        return Err(Error::Keychain(format!("failed to store {account:?}")));
+    }
+    Ok(())
+}
+
+/// Removes `secret` from the login Keychain
+pub fn delete_secret(secret: Secret) -> Result<()> {
+    let account = secret.account();
+    security(
+        &[
+            "delete-generic-password",
+            "-s",
+            KEYCHAIN_SERVICE,
+            "-a",
+            account,
+        ],
+        format!("no secret for {account:?} in service {KEYCHAIN_SERVICE:?}"),
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sandbox;
+
+    #[test]
+    fn reads_secret_of_account() {
+        // The fake `security` of the sandbox stores `<account>-token`
+        sandbox();
+        assert_eq!(read_secret(Secret::Slack).unwrap(), "slack-token");
+        assert_eq!(read_secret(Secret::Mqtt).unwrap(), "mqtt-token");
+    }
+
+    #[test]
+    fn reports_missing_secret() {
+        sandbox();
+        let err = delete_secret(Secret::Toggl).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            Error::Keychain(format!(
+                "no secret for \"toggl\" in service \"{KEYCHAIN_SERVICE}\""
+            ))
+            .to_string()
+        );
+    }
+}
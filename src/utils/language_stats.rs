@@ -0,0 +1,109 @@
+use std::{path::Path, process::Command};
+
+use serde_json::Value;
+
+use crate::{watchdog::WatchedCommand, Error, Result};
+
+/// Lines of code of a project per language, as counted by tokei
+#[derive(Debug, Clone, PartialEq)]
+pub struct LanguageStats {
+    /// Language with the most lines of code
+    pub top_language: String,
+    /// Share of the top language in percent
+    pub top_language_percent: u64,
+    /// Lines of code in all languages, without comments and blank lines
+    pub lines_of_code: u64,
+}
+
+impl LanguageStats {
+    /// Returns the top language with its share, e.g. "87% Swift"
+    pub fn top_language_summary(&self) -> String {
+        format!("{}% {}", self.top_language_percent, self.top_language)
+    }
+
+    /// Returns the lines of code in a short form, e.g. "42k"
+    pub fn lines_of_code_summary(&self) -> String {
+        match self.lines_of_code {
+            lines @ 0..=999 => lines.to_string(),
+            lines @ 1_000..=999_999 => format!("{}k", (lines + 500) / 1_000),
+            lines => format!("{:.1}M", lines as f64 / 1_000_000.0),
+        }
+    }
+}
+
+/// Parses the JSON printed by `tokei --output json`
+pub fn parse_tokei_output(json: &str) -> Result<Option<LanguageStats>> {
+    let languages: Value = serde_json::from_str(json)?;
+    let Some(languages) = languages.as_object() else {
+        return Ok(None);
+    };
+    let counts = languages
+        .iter()
+        .filter(|(language, _)| *language != "Total")
+        .filter_map(|(language, stats)| Some((language, stats.get("code")?.as_u64()?)))
+        .collect::<Vec<_>>();
+    let lines_of_code = counts.iter().map(|(_, code)| code).sum::<u64>();
+    let Some((top_language, top_code)) = counts.into_iter().max_by_key(|(_, code)| *code) else {
+        return Ok(None);
+    };
+    if lines_of_code == 0 {
+        return Ok(None);
+    }
+    Ok(Some(LanguageStats {
+        top_language: top_language.clone(),
+        top_language_percent: top_code * 100 / lines_of_code,
+        lines_of_code,
+    }))
+}
+
+/// Counts the lines of code in `root` with tokei, which has to be installed
+pub fn scan(root: &Path) -> Result<Option<LanguageStats>> {
+    let output = Command::new("tokei")
+        .args(["--output", "json"])
+        .arg(root)
+        .watched_output()
+        .map_err(|err| Error::Tokei(err.to_string()))?;
+    if !output.status.success() {
+        return Err(Error::Tokei(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    parse_tokei_output(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_top_language() {
+        let json = r#"{
+            "Swift": {"blanks": 10, "code": 870, "comments": 5},
+            "Objective-C": {"blanks": 1, "code": 130, "comments": 0},
+            "Total": {"blanks": 11, "code": 1000, "comments": 5}
+        }"#;
+        let stats = parse_tokei_output(json).unwrap().unwrap();
+        assert_eq!(stats.top_language_summary(), "87% Swift");
+        assert_eq!(stats.lines_of_code_summary(), "1k");
+    }
+
+    #[test]
+    fn ignores_empty_projects() {
+        assert_eq!(
+            parse_tokei_output(r#"{"Total": {"code": 0}}"#).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn shortens_lines_of_code() {
+        let stats = |lines_of_code| LanguageStats {
+            top_language: String::from("Swift"),
+            top_language_percent: 100,
+            lines_of_code,
+        };
+        assert_eq!(stats(950).lines_of_code_summary(), "950");
+        assert_eq!(stats(41_600).lines_of_code_summary(), "42k");
+        assert_eq!(stats(1_250_000).lines_of_code_summary(), "1.2M");
+    }
+}
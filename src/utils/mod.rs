@@ -1,13 +1,43 @@
+use directories::ProjectDirs;
 use discord_rich_presence::DiscordIpcClient;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::{
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
+pub mod app_info;
+pub mod assets;
+pub mod clock;
+pub mod codename;
+pub mod devices;
+pub mod duration;
+pub mod emoji;
+pub mod file_display;
 pub mod file_language;
+pub mod focus;
+pub mod git;
+pub mod github;
+pub mod keychain;
+pub mod language_stats;
 pub mod osascript;
+pub mod osascript_parser;
+pub mod periodic;
+pub mod platform;
+pub mod power;
+pub mod process;
+pub mod project_type;
+pub mod repository;
+pub mod scheme;
+pub mod system_info;
+pub mod template;
+pub mod text;
+pub mod ticket;
+pub mod xcode_version;
 
-use crate::{Error, Result};
+use crate::{watchdog, Error, Result};
 
-pub fn init_discord_ipc() -> Result<DiscordIpcClient> {
-    match DiscordIpcClient::new("1158013054898950185") {
+pub fn init_discord_ipc(application_id: &str) -> Result<DiscordIpcClient> {
+    match DiscordIpcClient::new(application_id) {
         Ok(client) => {
             log::debug!("Discord IPC client initialized");
             Ok(client)
@@ -16,6 +46,34 @@ pub fn init_discord_ipc() -> Result<DiscordIpcClient> {
     }
 }
 
+/// Get the platform directories of xcode-discord-rpc
+fn project_dirs() -> Result<ProjectDirs> {
+    ProjectDirs::from("", "", "xcode-discord-rpc").ok_or_else(|| {
+        Error::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "home directory not found",
+        ))
+    })
+}
+
+/// Get the directory for persistent data such as statistics, creating it if needed
+pub fn data_dir() -> Result<PathBuf> {
+    let dir = project_dirs()?.data_dir().to_path_buf();
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Get the directory for runtime files such as the PID file, creating it if needed
+pub fn runtime_dir() -> Result<PathBuf> {
+    let proj_dirs = project_dirs()?;
+    let dir = proj_dirs
+        .runtime_dir()
+        .unwrap_or_else(|| proj_dirs.cache_dir())
+        .to_path_buf();
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
 /// Get the current time in seconds since the UNIX epoch as `i64`
 pub fn current_time() -> i64 {
     SystemTime::now()
@@ -24,7 +82,49 @@ pub fn current_time() -> i64 {
         .as_secs() as i64
 }
 
-/// Sleep for `Config::update_interval` seconds
-pub fn sleep(update_interval: u64) {
-    std::thread::sleep(std::time::Duration::from_secs(update_interval));
+/// Clock that keeps counting while the system is asleep, unlike `Instant`,
+/// and that neither NTP nor setting the time moves
+#[cfg(target_os = "macos")]
+const SLEEP_COUNTING_CLOCK: libc::clockid_t = libc::CLOCK_MONOTONIC_RAW;
+#[cfg(not(target_os = "macos"))]
+const SLEEP_COUNTING_CLOCK: libc::clockid_t = libc::CLOCK_BOOTTIME;
+
+/// Current time of `SLEEP_COUNTING_CLOCK`
+fn sleep_counting_time() -> Duration {
+    let mut time = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: `clock_gettime` only writes to `time`, which is a valid
+    // `timespec`
+    unsafe { libc::clock_gettime(SLEEP_COUNTING_CLOCK, &mut time) };
+    Duration::new(time.tv_sec as u64, time.tv_nsec as u32)
+}
+
+/// Sleep for `duration`.
+///
+/// The monotonic clock used by `std::thread::sleep` stops while the Mac is
+/// asleep, so the sleep is split in short chunks measured with a clock that
+/// keeps counting during system sleep. When the system slept during a chunk,
+/// this returns early with the number of seconds spent asleep.
+///
+/// This stands in for NSWorkspace's sleep and wake notifications, which are
+/// only delivered to an AppKit run loop. A wake is noticed at the end of the
+/// chunk it happened in, up to 5 seconds late.
+pub fn sleep(duration: Duration) -> Option<u64> {
+    watchdog::cycle_finished(duration);
+    const CHUNK: Duration = Duration::from_secs(5);
+    const SLACK: Duration = Duration::from_secs(5);
+    let mut remaining = duration;
+    while !remaining.is_zero() {
+        let chunk = remaining.min(CHUNK);
+        let before = sleep_counting_time();
+        std::thread::sleep(chunk);
+        let elapsed = sleep_counting_time().saturating_sub(before);
+        if elapsed > chunk + SLACK {
+            return Some((elapsed - chunk).as_secs());
+        }
+        remaining -= chunk;
+    }
+    None
 }
@@ -1,15 +1,33 @@
-use std::process::Command;
+use std::{path::PathBuf, process::Command};
 
-use crate::{Error, Result};
+use crate::{
+    utils::osascript_parser::{
+        parse_front_app, parse_front_window, parse_project, split_fields, strip_output, FrontApp,
+        FrontWindow, ProjectInfo, FIELD_SEPARATOR_SCRIPT,
+    },
+    watchdog::WatchedCommand,
+    Error, Result,
+};
 
 /// Execute an AppleScript command using osascript and returns the output as a String
 pub fn run_osascript(script: &str) -> Result<String> {
     let output = Command::new("osascript")
         .arg("-e")
         .arg(script)
-        .output()
+        .watched_output()
         .map_err(|err| Error::Oascript(err.to_string()))?;
-    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    Ok(strip_output(&String::from_utf8_lossy(&output.stdout)).to_string())
+}
+
+/// Shows a macOS notification with `title` and `message`
+pub fn display_notification(title: &str, message: &str) -> Result<()> {
+    let escape = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+    run_osascript(&format!(
+        r#"display notification "{}" with title "{}""#,
+        escape(message),
+        escape(title)
+    ))?;
+    Ok(())
 }
 
 /// Check if Xcode is running
@@ -24,63 +42,239 @@ pub fn check_xcode() -> Result<bool> {
     Ok(xcode_is_running == "true")
 }
 
-/// Get the current file's name as a String
-pub fn current_file() -> Result<String> {
-    let file = run_osascript(
+/// Check if any of the processes with the given names is running
+pub fn is_any_process_running(names: &[String]) -> Result<bool> {
+    if names.is_empty() {
+        return Ok(false);
+    }
+    let names = names
+        .iter()
+        .map(|name| format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let is_running = run_osascript(&format!(
+        r#"
+        tell application "System Events"
+            return exists (processes where name is in {{{names}}})
+        end tell
+    "#
+    ))?;
+    Ok(is_running == "true")
+}
+
+/// Check if Xcode's commit sheet is open on the front window.
+/// Inspecting the sheet requires the Accessibility permission.
+pub fn is_commit_sheet_open() -> Result<bool> {
+    let is_open = run_osascript(
+        r#"
+        tell application "System Events"
+            tell process "Xcode"
+                try
+                    return (count of (buttons of sheet 1 of front window whose name starts with "Commit")) > 0
+                on error
+                    return false
+                end try
+            end tell
+        end tell
+    "#,
+    )?;
+    Ok(is_open == "true")
+}
+
+/// Check if the SwiftUI preview canvas is shown and the keyboard focus is
+/// outside of the source editor, e.g. on the canvas or the inspector.
+/// Inspecting the focus requires the Accessibility permission.
+pub fn is_preview_focused() -> Result<bool> {
+    let is_focused = run_osascript(
+        r#"
+        tell application "System Events"
+            tell process "Xcode"
+                try
+                    set canvasItem to menu item "Canvas" of menu "Editor" of menu bar item "Editor" of menu bar 1
+                    if value of attribute "AXMenuItemMarkChar" of canvasItem is not "✓" then
+                        return false
+                    end if
+                    set focusedElement to value of attribute "AXFocusedUIElement"
+                    return description of focusedElement is not "Source Editor"
+                on error
+                    return false
+                end try
+            end tell
+        end tell
+    "#,
+    )?;
+    Ok(is_focused == "true")
+}
+
+/// Get the status shown by the activity view in the toolbar of the front
+/// Xcode window, e.g. `Running MyApp on iPhone 15`, or an empty string.
+/// Inspecting the toolbar requires the Accessibility permission.
+pub fn activity_status() -> Result<String> {
+    run_osascript(
+        r#"
+        tell application "System Events"
+            tell process "Xcode"
+                try
+                    set activityView to first group of toolbar 1 of front window whose description is "Activity View"
+                    return value of static text 1 of activityView
+                on error
+                    return ""
+                end try
+            end tell
+        end tell
+    "#,
+    )
+}
+
+/// Get the file focused in the front Xcode window, whether it has unsaved
+/// changes and how many source documents are open
+pub fn front_window() -> Result<FrontWindow> {
+    let output = run_osascript(&format!(
+        r#"
+        tell application "Xcode"
+            set projectName to ""
+            set windowTitle to ""
+            set modifiedNames to ""
+            set openFiles to 0
+            try
+                set projectName to name of active workspace document
+            end try
+            try
+                set windowTitle to name of front window
+            end try
+            try
+                set openFiles to count of source documents
+            end try
+            try
+                set AppleScript's text item delimiters to {FIELD_SEPARATOR_SCRIPT}
+                set modifiedNames to (name of every source document whose modified is true) as text
+            end try
+            return projectName & {FIELD_SEPARATOR_SCRIPT} & windowTitle & {FIELD_SEPARATOR_SCRIPT} & openFiles & {FIELD_SEPARATOR_SCRIPT} & modifiedNames
+        end tell
+    "#
+    ))?;
+    Ok(parse_front_window(&output))
+}
+
+/// Get the name, path and kind of the project of the front Xcode window,
+/// falling back to the active workspace document when the front window
+/// belongs to no project, or `None` when no project is open
+pub fn current_project() -> Result<Option<ProjectInfo>> {
+    let output = run_osascript(&format!(
+        r#"
+        tell application "Xcode"
+            set projectDocument to missing value
+            set projectName to ""
+            set projectPath to ""
+            try
+                set frontDocument to document of front window
+                if class of frontDocument is workspace document then
+                    set projectDocument to frontDocument
+                end if
+            end try
+            if projectDocument is missing value then
+                try
+                    set projectDocument to active workspace document
+                end try
+            end if
+            try
+                set projectName to name of projectDocument
+            end try
+            try
+                set projectPath to path of projectDocument
+            end try
+            return projectName & {FIELD_SEPARATOR_SCRIPT} & projectPath
+        end tell
+    "#
+    ))?;
+    let [name, path] = split_fields(&output);
+    Ok(parse_project(name, path))
+}
+
+/// Get the number of projects open in Xcode
+pub fn open_project_count() -> Result<usize> {
+    let output = run_osascript(
         r#"
         tell application "Xcode"
-            return name of windows whose index is 1
+            return count of workspace documents
         end tell
     "#,
     )?;
-    if !file.contains(" — ") {
-        return Ok(file);
-    }
-    let file = file.split(" — ").collect::<Vec<&str>>()[1];
-    Ok(file.to_string())
+    Ok(output.trim().parse().unwrap_or(0))
 }
 
-/// Get the current project's name as a String
-pub fn current_project() -> Result<String> {
-    let project = run_osascript(
+/// Get the path of the open source document called `name`, or `None` when
+/// Xcode does not report it
+pub fn current_file_path(name: &str) -> Result<Option<PathBuf>> {
+    let name = name.replace('\\', "\\\\").replace('"', "\\\"");
+    let path = run_osascript(&format!(
         r#"
         tell application "Xcode"
-            return active workspace document
+            try
+                return path of first source document whose name is "{name}"
+            on error
+                return ""
+            end try
+        end tell
+    "#
+    ))?;
+    Ok((!path.is_empty()).then(|| PathBuf::from(path)))
+}
+
+/// Get the name and the front window title of the frontmost application
+pub fn front_app() -> Result<FrontApp> {
+    let output = run_osascript(&format!(
+        r#"
+        tell application "System Events"
+            set frontApp to first application process whose frontmost is true
+            set windowTitle to ""
+            try
+                set windowTitle to name of front window of frontApp
+            end try
+            return name of frontApp & {FIELD_SEPARATOR_SCRIPT} & windowTitle
+        end tell
+    "#
+    ))?;
+    Ok(parse_front_app(&output))
+}
+
+/// Get the name of the active scheme of the active workspace document, or
+/// `None` when no project is open
+pub fn active_scheme() -> Result<Option<String>> {
+    let scheme = run_osascript(
+        r#"
+        tell application "Xcode"
+            try
+                return name of active scheme of active workspace document
+            on error
+                return ""
+            end try
         end tell
     "#,
     )?;
-    if project == "missing value" {
-        return Ok(String::new());
-    }
-    if project.starts_with("workspace document ") {
-        return Ok(project.replace("workspace document ", ""));
-    }
-    Ok(project)
+    Ok((!scheme.is_empty()).then_some(scheme))
 }
 
-// pub fn current_file_path() -> Result<String> {
-//     run_osascript(
-//         r#"
-//         tell application "Xcode"
-//             try 
-//                 return path of document of front window
-//             on error
-//                 return ""
-//             end try
-//         end tell
-//     "#,
-//     )
-// }
-
-
-// /// Check if frontmost application is Xcode
-// pub fn is_xcode_frontmost() -> Result<bool> {
-//     let frontmost_app = run_osascript(
-//         r#"
-//         if frontmost of application "Xcode" is true then
-//             return "Xcode"
-//         end if
-//     "#,
-//     )?;
-//     Ok(frontmost_app == "Xcode")
-// }
+/// Get the path of the running Xcode app bundle
+pub fn xcode_app_path() -> Result<PathBuf> {
+    let path = run_osascript(
+        r#"
+        tell application "System Events"
+            return POSIX path of (application file of process "Xcode")
+        end tell
+    "#,
+    )?;
+    Ok(PathBuf::from(path))
+}
+
+/// Check if frontmost application is Xcode
+pub fn is_xcode_frontmost() -> Result<bool> {
+    let frontmost_app = run_osascript(
+        r#"
+        if frontmost of application "Xcode" is true then
+            return "Xcode"
+        end if
+    "#,
+    )?;
+    Ok(frontmost_app == "Xcode")
+}
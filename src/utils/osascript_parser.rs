@@ -0,0 +1,426 @@
+//! Parsing of the text printed by `osascript`. The scripts separate their
+//! fields with the ASCII unit separator, which cannot be typed in Xcode, so
+//! names containing quotes, commas, newlines or unicode are kept intact.
+//!
+//! This module only depends on `std` so it can be fuzzed on its own.
+
+use std::path::{Path, PathBuf};
+
+/// Separator between the fields printed by a script (ASCII unit separator)
+pub const FIELD_SEPARATOR: char = '\u{1f}';
+/// AppleScript expression evaluating to `FIELD_SEPARATOR`
+pub const FIELD_SEPARATOR_SCRIPT: &str = "(character id 31)";
+/// Separator between the project and the file in Xcode window titles
+pub const TITLE_SEPARATOR: &str = " — ";
+/// Title of Xcode's Developer Documentation window
+pub const DOCUMENTATION_TITLE: &str = "Developer Documentation";
+/// Last part of the window title while the comparison editor reviews the
+/// changes of a file, depending on the Xcode version
+const REVIEW_TITLES: [&str; 2] = ["Comparison", "Code Review"];
+/// Text printed by AppleScript for a missing value
+const MISSING_VALUE: &str = "missing value";
+/// Start of the status of the activity view while an app is running
+const RUNNING_STATUS: &str = "Running ";
+
+/// Removes the single trailing newline `osascript` appends to its output,
+/// leaving any other whitespace that is part of a name
+pub fn strip_output(output: &str) -> &str {
+    output
+        .strip_suffix('\n')
+        .map(|output| output.strip_suffix('\r').unwrap_or(output))
+        .unwrap_or(output)
+}
+
+/// Splits a script output into `N` fields. Missing fields are empty and any
+/// extra separator is kept in the last field.
+pub fn split_fields<const N: usize>(output: &str) -> [&str; N] {
+    let mut fields = [""; N];
+    for (field, value) in fields.iter_mut().zip(output.splitn(N, FIELD_SEPARATOR)) {
+        *field = value;
+    }
+    fields
+}
+
+/// Returns an empty string for AppleScript's `missing value`
+pub fn parse_missing_value(field: &str) -> &str {
+    if field == MISSING_VALUE {
+        ""
+    } else {
+        field
+    }
+}
+
+/// Removes the workspace document extension from a project name
+pub fn project_stem(project: &str) -> &str {
+    [".xcodeproj", ".xcworkspace", ".playground"]
+        .iter()
+        .find_map(|ext| project.strip_suffix(ext))
+        .unwrap_or(project)
+}
+
+/// Kind of document open as Xcode's active workspace document
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectKind {
+    /// An `.xcworkspace`
+    Workspace,
+    /// An `.xcodeproj`
+    Project,
+    /// A Swift package opened from its `Package.swift` or folder
+    Package,
+    /// A `.playground`
+    Playground,
+}
+
+impl ProjectKind {
+    /// Infers the kind from the path of the document, or from its name when
+    /// Xcode does not report a path
+    fn detect(name: &str, path: &str) -> Self {
+        let document = if path.is_empty() { name } else { path };
+        match Path::new(document).extension().and_then(|ext| ext.to_str()) {
+            Some("xcworkspace") => ProjectKind::Workspace,
+            Some("xcodeproj") => ProjectKind::Project,
+            Some("playground") => ProjectKind::Playground,
+            _ => ProjectKind::Package,
+        }
+    }
+
+    /// Returns the display name of the kind
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProjectKind::Workspace => "Workspace",
+            ProjectKind::Project => "Project",
+            ProjectKind::Package => "Package",
+            ProjectKind::Playground => "Playground",
+        }
+    }
+}
+
+/// The active workspace document of Xcode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectInfo {
+    /// Name without the `.xcworkspace`, `.xcodeproj` or `.playground` suffix
+    pub name: String,
+    /// Path of the document, if Xcode reports one
+    pub path: Option<PathBuf>,
+    pub kind: ProjectKind,
+}
+
+/// Builds the `ProjectInfo` from the name and path of the active workspace
+/// document, or `None` when no project is open
+pub fn parse_project(name: &str, path: &str) -> Option<ProjectInfo> {
+    let name = parse_missing_value(name);
+    let path = parse_missing_value(path);
+    if name.is_empty() {
+        return None;
+    }
+    Some(ProjectInfo {
+        name: project_stem(name).to_string(),
+        path: Some(PathBuf::from(path)).filter(|_| !path.is_empty()),
+        kind: ProjectKind::detect(name, path),
+    })
+}
+
+/// The document focused in the front Xcode window
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FocusedFile {
+    /// A file is open in the editor
+    Document(String),
+    /// No file is focused, e.g. the project navigator or scheme editor is shown
+    NoDocument,
+    /// The Developer Documentation window is in front
+    Documentation,
+    /// The comparison editor shows the changes of a file
+    Review,
+}
+
+/// The front Xcode window
+#[derive(Debug, PartialEq, Eq)]
+pub struct FrontWindow {
+    /// Document focused in the window
+    pub file: FocusedFile,
+    /// Whether the focused document has unsaved changes
+    pub unsaved: bool,
+    /// Number of open source documents
+    pub open_files: usize,
+}
+
+/// The frontmost application
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrontApp {
+    /// Name of the application process
+    pub name: String,
+    /// Title of its front window, empty when it has none
+    pub window_title: String,
+}
+
+/// Parses the output of the front application script: the process name and
+/// the title of its front window separated by `FIELD_SEPARATOR`
+pub fn parse_front_app(output: &str) -> FrontApp {
+    let [name, window_title] = split_fields(output);
+    FrontApp {
+        name: name.to_string(),
+        window_title: parse_missing_value(window_title).to_string(),
+    }
+}
+
+/// Parses the output of the front window script: the project, the window
+/// title, the number of open source documents and the names of the modified
+/// ones, all separated by `FIELD_SEPARATOR`
+pub fn parse_front_window(output: &str) -> FrontWindow {
+    let [project, window_title, open_files, modified] = split_fields(output);
+    let file = parse_file_name(project, window_title);
+    let unsaved = match &file {
+        FocusedFile::Document(name) => modified
+            .split(FIELD_SEPARATOR)
+            .any(|modified| modified == name),
+        _ => false,
+    };
+    FrontWindow {
+        file,
+        unsaved,
+        open_files: open_files.trim().parse().unwrap_or(0),
+    }
+}
+
+/// Tells windows that do not edit a file from their title: the documentation
+/// window, and the comparison editor whose title ends with `— Comparison`
+pub fn classify_title(window_title: &str) -> Option<FocusedFile> {
+    if window_title == DOCUMENTATION_TITLE {
+        return Some(FocusedFile::Documentation);
+    }
+    window_title
+        .rsplit(TITLE_SEPARATOR)
+        .next()
+        .filter(|last| REVIEW_TITLES.contains(last))
+        .map(|_| FocusedFile::Review)
+}
+
+/// Extracts the run destination from the status of the activity view in the
+/// toolbar, e.g. `Hamza's iPhone` from `Running MyApp on Hamza's iPhone`
+pub fn parse_run_destination(status: &str) -> Option<&str> {
+    let (_, destination) = status.strip_prefix(RUNNING_STATUS)?.split_once(" on ")?;
+    let destination = destination.trim();
+    (!destination.is_empty()).then_some(destination)
+}
+
+/// Extracts the focused file from an Xcode window title such as
+/// `MyApp — ContentView.swift`. The project name is stripped first so
+/// project names containing the separator are handled, otherwise the title
+/// is split on the first separator. A title without a file part, such as
+/// `MyApp`, means no document is focused.
+pub fn parse_file_name(project: &str, window_title: &str) -> FocusedFile {
+    if let Some(window) = classify_title(window_title) {
+        return window;
+    }
+    let stem = project_stem(project);
+    let file = window_title
+        .strip_prefix(stem)
+        .filter(|_| !stem.is_empty())
+        .and_then(|rest| rest.strip_prefix(TITLE_SEPARATOR))
+        .or_else(|| {
+            window_title
+                .split_once(TITLE_SEPARATOR)
+                .map(|(_, file)| file)
+        });
+    match file {
+        Some(file) if !file.is_empty() => FocusedFile::Document(file.to_string()),
+        _ => FocusedFile::NoDocument,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_project_info() {
+        let project = parse_project("My App.xcworkspace", "/Users/me/My App/My App.xcworkspace");
+        assert_eq!(
+            project,
+            Some(ProjectInfo {
+                name: String::from("My App"),
+                path: Some(PathBuf::from("/Users/me/My App/My App.xcworkspace")),
+                kind: ProjectKind::Workspace,
+            })
+        );
+        let package = parse_project("Package", "/Users/me/Package").unwrap();
+        assert_eq!(package.kind, ProjectKind::Package);
+        let playground = parse_project("Sketch.playground", "missing value").unwrap();
+        assert_eq!(
+            (playground.kind, playground.path),
+            (ProjectKind::Playground, None)
+        );
+        assert_eq!(parse_project("missing value", ""), None);
+    }
+
+    #[test]
+    fn strips_only_the_trailing_newline() {
+        assert_eq!(strip_output("true\n"), "true");
+        assert_eq!(strip_output(" spaced name \n"), " spaced name ");
+        assert_eq!(strip_output("name\r\n"), "name");
+        assert_eq!(strip_output("two\nlines\n"), "two\nlines");
+        assert_eq!(strip_output(""), "");
+    }
+
+    #[test]
+    fn splits_fields() {
+        assert_eq!(
+            split_fields::<2>("MyApp\u{1f}MyApp — main.swift"),
+            ["MyApp", "MyApp — main.swift"]
+        );
+        assert_eq!(split_fields::<2>("MyApp"), ["MyApp", ""]);
+        assert_eq!(split_fields::<2>(""), ["", ""]);
+        assert_eq!(split_fields::<2>("a\u{1f}b\u{1f}c"), ["a", "b\u{1f}c"]);
+    }
+
+    #[test]
+    fn parses_missing_value() {
+        assert_eq!(parse_missing_value("missing value"), "");
+        assert_eq!(
+            parse_missing_value("missing value.swift"),
+            "missing value.swift"
+        );
+    }
+
+    #[test]
+    fn parses_unsaved_documents() {
+        let window = parse_front_window(
+            "MyApp.xcodeproj\u{1f}MyApp — ContentView.swift\u{1f}3\u{1f}AppDelegate.swift\u{1f}ContentView.swift",
+        );
+        assert_eq!(
+            window,
+            FrontWindow {
+                file: document("ContentView.swift"),
+                unsaved: true,
+                open_files: 3,
+            }
+        );
+        let window =
+            parse_front_window("MyApp.xcodeproj\u{1f}MyApp — ContentView.swift\u{1f}1\u{1f}");
+        assert!(!window.unsaved);
+        let window = parse_front_window("MyApp.xcodeproj\u{1f}MyApp\u{1f}0\u{1f}MyApp");
+        assert_eq!(window.file, FocusedFile::NoDocument);
+        assert!(!window.unsaved);
+    }
+
+    fn document(name: &str) -> FocusedFile {
+        FocusedFile::Document(name.to_string())
+    }
+
+    #[test]
+    fn parses_run_destination() {
+        assert_eq!(
+            parse_run_destination("Running MyApp on Hamza's iPhone"),
+            Some("Hamza's iPhone")
+        );
+        assert_eq!(parse_run_destination("Build Succeeded"), None);
+        assert_eq!(parse_run_destination("Running MyApp"), None);
+    }
+
+    #[test]
+    fn parses_front_app() {
+        assert_eq!(
+            parse_front_app("Reality Composer Pro\u{1f}Garden — Scene.usda"),
+            FrontApp {
+                name: "Reality Composer Pro".to_string(),
+                window_title: "Garden — Scene.usda".to_string(),
+            }
+        );
+        assert_eq!(
+            parse_front_app("Finder\u{1f}missing value").window_title,
+            ""
+        );
+    }
+
+    #[test]
+    fn parses_simple_title() {
+        assert_eq!(
+            parse_file_name("MyApp.xcodeproj", "MyApp — ContentView.swift"),
+            document("ContentView.swift")
+        );
+    }
+
+    #[test]
+    fn parses_title_without_document() {
+        assert_eq!(
+            parse_file_name("MyApp.xcodeproj", "MyApp"),
+            FocusedFile::NoDocument
+        );
+        assert_eq!(
+            parse_file_name("MyApp.xcodeproj", "MyApp — "),
+            FocusedFile::NoDocument
+        );
+        assert_eq!(
+            parse_file_name("MyApp.xcodeproj", "Welcome to Xcode"),
+            FocusedFile::NoDocument
+        );
+        assert_eq!(parse_file_name("", ""), FocusedFile::NoDocument);
+    }
+
+    #[test]
+    fn parses_comparison_editor() {
+        assert_eq!(
+            parse_file_name("MyApp.xcodeproj", "MyApp — ContentView.swift — Comparison"),
+            FocusedFile::Review
+        );
+        assert_eq!(
+            parse_file_name("MyApp.xcodeproj", "MyApp — Code Review"),
+            FocusedFile::Review
+        );
+        assert_eq!(
+            parse_file_name("MyApp.xcodeproj", "MyApp — Comparison.swift"),
+            document("Comparison.swift")
+        );
+    }
+
+    #[test]
+    fn parses_documentation_window() {
+        assert_eq!(
+            parse_file_name("MyApp.xcodeproj", "Developer Documentation"),
+            FocusedFile::Documentation
+        );
+        assert_eq!(
+            parse_file_name("MyApp.xcodeproj", "MyApp — Developer Documentation"),
+            document("Developer Documentation")
+        );
+    }
+
+    #[test]
+    fn parses_project_containing_separator() {
+        assert_eq!(
+            parse_file_name("Foo — Bar.xcworkspace", "Foo — Bar — main.swift"),
+            document("main.swift")
+        );
+    }
+
+    #[test]
+    fn parses_file_containing_separator() {
+        assert_eq!(
+            parse_file_name("MyApp", "MyApp — a — b.swift"),
+            document("a — b.swift")
+        );
+        assert_eq!(
+            parse_file_name("", "MyApp — a — b.swift"),
+            document("a — b.swift")
+        );
+    }
+
+    #[test]
+    fn parses_adversarial_names() {
+        for file in [
+            r#"Say "hi".swift"#,
+            "line\nbreak.swift",
+            "comma, separated.swift",
+            "日本語のファイル.swift",
+            "emoji 👩‍💻.swift",
+            "{braces}.swift",
+            "  padded  .swift",
+        ] {
+            let title = format!("My \"App\"{TITLE_SEPARATOR}{file}");
+            assert_eq!(
+                parse_file_name("My \"App\".xcodeproj", &title),
+                document(file)
+            );
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::time::{Duration, Instant};
+
+/// Deadline of a task of the main loop that runs every interval.
+///
+/// The next run is planned from the planned time of the previous one rather
+/// than from when it finished, so that slow osascript calls and oversleeping
+/// do not add up over time.
+#[derive(Debug, Clone, Copy)]
+pub struct Periodic {
+    next: Instant,
+}
+
+impl Periodic {
+    /// A task that is due right away
+    pub fn new(now: Instant) -> Self {
+        Self { next: now }
+    }
+
+    /// Returns when the task is due
+    pub fn next(&self) -> Instant {
+        self.next
+    }
+
+    /// Returns whether the task is due at `now`
+    pub fn is_due(&self, now: Instant) -> bool {
+        now >= self.next
+    }
+
+    /// Plans the next run `interval` after the planned time of the current
+    /// one. A task that fell behind by more than an interval skips the runs
+    /// it missed and is planned `interval` after `now`.
+    pub fn schedule(&mut self, now: Instant, interval: Duration) {
+        let next = self.next + interval;
+        self.next = if next > now { next } else { now + interval };
+    }
+
+    /// Makes the task due right away
+    pub fn reset(&mut self, now: Instant) {
+        self.next = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_secs(3);
+
+    #[test]
+    fn corrects_drift() {
+        let start = Instant::now();
+        let mut task = Periodic::new(start);
+        assert!(task.is_due(start));
+        // The run took a second, the next one stays on the 3s grid
+        task.schedule(start + Duration::from_secs(1), INTERVAL);
+        assert_eq!(task.next(), start + INTERVAL);
+        assert!(!task.is_due(start + Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn skips_missed_runs() {
+        let start = Instant::now();
+        let mut task = Periodic::new(start);
+        let late = start + Duration::from_secs(10);
+        task.schedule(late, INTERVAL);
+        assert_eq!(task.next(), late + INTERVAL);
+        task.reset(late);
+        assert!(task.is_due(late));
+    }
+}
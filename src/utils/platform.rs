@@ -0,0 +1,107 @@
+use std::{fs, path::Path};
+
+/// Apple platform a project is built for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    Ios,
+    MacOS,
+    WatchOS,
+    TvOS,
+    VisionOS,
+}
+
+impl Platform {
+    /// Maps an SDK name of a build setting to its platform
+    fn from_sdk(sdk: &str) -> Option<Self> {
+        match sdk.trim_matches('"') {
+            "iphoneos" | "iphonesimulator" => Some(Platform::Ios),
+            "macosx" => Some(Platform::MacOS),
+            "watchos" | "watchsimulator" => Some(Platform::WatchOS),
+            "appletvos" | "appletvsimulator" => Some(Platform::TvOS),
+            "xros" | "xrsimulator" => Some(Platform::VisionOS),
+            _ => None,
+        }
+    }
+
+    /// Returns the display name of the platform
+    pub fn name(&self) -> &'static str {
+        match self {
+            Platform::Ios => "iOS",
+            Platform::MacOS => "macOS",
+            Platform::WatchOS => "watchOS",
+            Platform::TvOS => "tvOS",
+            Platform::VisionOS => "visionOS",
+        }
+    }
+}
+
+/// Determines the primary platform of a `project.pbxproj` from the SDKs its
+/// build configurations use. The platform used most wins, ties going to the
+/// one that appears first.
+pub fn primary_platform(pbxproj: &str) -> Option<Platform> {
+    let mut counts: Vec<(Platform, usize)> = Vec::new();
+    for line in pbxproj.lines() {
+        let Some((key, value)) = line.trim().split_once(" = ") else {
+            continue;
+        };
+        if key != "SDKROOT" && key != "SUPPORTED_PLATFORMS" {
+            continue;
+        }
+        let value = value.trim_end_matches(';').trim_matches('"');
+        for platform in value.split_whitespace().filter_map(Platform::from_sdk) {
+            match counts.iter_mut().find(|(counted, _)| *counted == platform) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((platform, 1)),
+            }
+        }
+    }
+    counts
+        .iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map(|(platform, _)| *platform)
+}
+
+/// Detects the primary platform of the Xcode projects in `root`.
+/// Swift packages without an Xcode project have no platform.
+pub fn detect_platform(root: &Path) -> Option<Platform> {
+    let pbxproj = fs::read_dir(root)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "xcodeproj"))
+        .filter_map(|path| fs::read_to_string(path.join("project.pbxproj")).ok())
+        .collect::<String>();
+    primary_platform(&pbxproj)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_most_used_sdk() {
+        let pbxproj = r#"
+            SDKROOT = iphoneos;
+            SDKROOT = iphoneos;
+            SDKROOT = watchos;
+        "#;
+        assert_eq!(primary_platform(pbxproj), Some(Platform::Ios));
+    }
+
+    #[test]
+    fn reads_supported_platforms_of_multiplatform_targets() {
+        let pbxproj = r#"
+            SDKROOT = auto;
+            SUPPORTED_PLATFORMS = "xros xrsimulator";
+        "#;
+        assert_eq!(primary_platform(pbxproj), Some(Platform::VisionOS));
+    }
+
+    #[test]
+    fn breaks_ties_by_first_appearance() {
+        let pbxproj = "SDKROOT = macosx;\nSDKROOT = appletvos;\n";
+        assert_eq!(primary_platform(pbxproj), Some(Platform::MacOS));
+        assert_eq!(primary_platform("SDKROOT = auto;"), None);
+    }
+}
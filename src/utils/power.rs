@@ -0,0 +1,72 @@
+use std::process::Command;
+
+use crate::{watchdog::WatchedCommand, Error, Result};
+
+/// Run `pmset` with the given arguments and return its output as a String
+fn run_pmset(args: &[&str]) -> Result<String> {
+    let output = Command::new("pmset")
+        .args(args)
+        .watched_output()
+        .map_err(|err| Error::Pmset(err.to_string()))?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Check if the output of `pmset -g batt` says the Mac draws from its battery
+fn is_on_battery(battery: &str) -> bool {
+    battery
+        .lines()
+        .next()
+        .is_some_and(|line| line.contains("'Battery Power'"))
+}
+
+/// Check if the output of `pmset -g` has Low Power Mode turned on
+fn is_low_power_mode(settings: &str) -> bool {
+    settings.lines().any(|line| {
+        let mut parts = line.split_whitespace();
+        parts.next() == Some("lowpowermode") && parts.next() == Some("1")
+    })
+}
+
+/// Check if the Mac is running on battery power or in Low Power Mode.
+///
+/// The power source is polled with `pmset` instead of subscribing to IOKit
+/// power source notifications, which would need bindings to IOKit and a
+/// CoreFoundation run loop thread. The caller checks at most once a minute,
+/// which costs up to two short-lived `pmset` processes per minute and picks
+/// up a change of the power source within that minute.
+pub fn is_power_saving() -> Result<bool> {
+    if is_on_battery(&run_pmset(&["-g", "batt"])?) {
+        return Ok(true);
+    }
+    Ok(is_low_power_mode(&run_pmset(&["-g"])?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_power_source() {
+        let battery = "Now drawing from 'Battery Power'\n \
+                       -InternalBattery-0 (id=4653155)\t87%; discharging; 6:12 remaining present: true\n";
+        assert!(is_on_battery(battery));
+        let plugged = "Now drawing from 'AC Power'\n \
+                       -InternalBattery-0 (id=4653155)\t100%; charged; 0:00 remaining present: true\n";
+        assert!(!is_on_battery(plugged));
+        // Desktop Macs have no battery
+        assert!(!is_on_battery("Now drawing from 'AC Power'\n"));
+    }
+
+    #[test]
+    fn reads_low_power_mode() {
+        let settings = "System-wide power settings:\nCurrently in use:\n \
+                        standby              1\n lowpowermode         1\n sleep                1\n";
+        assert!(is_low_power_mode(settings));
+        assert!(!is_low_power_mode(
+            &settings.replace("lowpowermode         1", "lowpowermode         0")
+        ));
+        assert!(!is_low_power_mode(
+            "Currently in use:\n powermode            1\n"
+        ));
+    }
+}
@@ -0,0 +1,71 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{watchdog::WatchedCommand, Error, Result};
+
+/// Process names of command-line builds
+const CLI_BUILD_PROCESSES: [&str; 2] = ["xcodebuild", "swift-build"];
+/// Part of the command line of the stub process Xcode runs a playground in
+const PLAYGROUND_PROCESS: &str = "PlaygroundStub";
+
+/// Get the PIDs of the processes named exactly `name`
+fn pids_of(name: &str) -> Result<Vec<u32>> {
+    pgrep("-x", name)
+}
+
+/// Get the PIDs of the processes whose command line contains `pattern`
+fn pids_matching(pattern: &str) -> Result<Vec<u32>> {
+    pgrep("-f", pattern)
+}
+
+/// Runs `pgrep` with the matching `mode` and returns the PIDs it printed
+fn pgrep(mode: &str, pattern: &str) -> Result<Vec<u32>> {
+    let output = Command::new("pgrep")
+        .arg(mode)
+        .arg(pattern)
+        .watched_output()
+        .map_err(|err| Error::Process(err.to_string()))?;
+    // pgrep exits with 1 when no process matched
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.trim().parse().ok())
+        .collect())
+}
+
+/// Check if Xcode is executing a playground
+pub fn is_playground_running() -> Result<bool> {
+    Ok(!pids_matching(PLAYGROUND_PROCESS)?.is_empty())
+}
+
+/// Check if a debugger is attached to an app, Xcode starts `debugserver` for
+/// every debugging session
+pub fn is_debugger_running() -> Result<bool> {
+    Ok(!pids_of("debugserver")?.is_empty())
+}
+
+/// Get the working directory of the process with the given PID
+fn working_directory(pid: u32) -> Result<Option<PathBuf>> {
+    let output = Command::new("lsof")
+        .args(["-a", "-d", "cwd", "-Fn", "-p"])
+        .arg(pid.to_string())
+        .watched_output()
+        .map_err(|err| Error::Process(err.to_string()))?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix('n'))
+        .map(PathBuf::from))
+}
+
+/// Check if an `xcodebuild` or `swift build` process is running inside `root`
+pub fn is_cli_build_running(root: &Path) -> Result<bool> {
+    for name in CLI_BUILD_PROCESSES {
+        for pid in pids_of(name)? {
+            if working_directory(pid)?.is_some_and(|cwd| cwd.starts_with(root)) {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
@@ -0,0 +1,52 @@
+use std::path::Path;
+
+/// Tooling used to set up a project, detected from the files in its root
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProjectType {
+    Tuist,
+    CocoaPods,
+    Carthage,
+    SwiftPackage,
+    #[default]
+    Xcode,
+}
+
+impl ProjectType {
+    /// Every project type
+    pub const ALL: [ProjectType; 5] = [
+        ProjectType::Tuist,
+        ProjectType::CocoaPods,
+        ProjectType::Carthage,
+        ProjectType::SwiftPackage,
+        ProjectType::Xcode,
+    ];
+
+    /// Detects the project type from the manifest files in `root`. Tuist,
+    /// CocoaPods and Carthage take precedence because their projects often
+    /// contain a `Package.swift` as well.
+    pub fn detect(root: &Path) -> Self {
+        let has = |file: &str| root.join(file).is_file();
+        if has("Project.swift") || has("Workspace.swift") {
+            ProjectType::Tuist
+        } else if has("Podfile") {
+            ProjectType::CocoaPods
+        } else if has("Cartfile") {
+            ProjectType::Carthage
+        } else if has("Package.swift") {
+            ProjectType::SwiftPackage
+        } else {
+            ProjectType::Xcode
+        }
+    }
+
+    /// Returns the asset key and text for the `ProjectType` as (text, image)
+    pub fn get_asset_keys(&self) -> (&'static str, &'static str) {
+        match self {
+            ProjectType::Tuist => ("Tuist", "tuist"),
+            ProjectType::CocoaPods => ("CocoaPods", "cocoapods"),
+            ProjectType::Carthage => ("Carthage", "carthage"),
+            ProjectType::SwiftPackage => ("Swift Package", "spm"),
+            ProjectType::Xcode => ("Xcode Project", "xcode"),
+        }
+    }
+}
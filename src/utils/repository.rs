@@ -0,0 +1,74 @@
+use crate::integrations::http::is_ok;
+
+/// Hosts whose repositories get a "View Repository" button
+const SUPPORTED_HOSTS: [&str; 2] = ["github.com", "gitlab.com"];
+
+/// Converts a git remote URL to the repository's web URL, if it is hosted on
+/// GitHub or GitLab. Both `https://` and SSH remotes are supported.
+pub fn web_url(remote: &str) -> Option<String> {
+    let remote = remote.trim();
+    let (host, path) = if let Some(rest) = remote
+        .strip_prefix("https://")
+        .or_else(|| remote.strip_prefix("http://"))
+        .or_else(|| remote.strip_prefix("ssh://"))
+    {
+        let rest = rest.rsplit_once('@').map_or(rest, |(_, rest)| rest);
+        rest.split_once('/')?
+    } else {
+        // scp-like syntax: git@github.com:owner/repo.git
+        let rest = remote.rsplit_once('@').map_or(remote, |(_, rest)| rest);
+        rest.split_once(':')?
+    };
+    let host = host.split(':').next()?.to_lowercase();
+    if !SUPPORTED_HOSTS.contains(&host.as_str()) {
+        return None;
+    }
+    let path = path.trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    if path.split('/').filter(|part| !part.is_empty()).count() < 2 {
+        return None;
+    }
+    Some(format!("https://{host}/{path}"))
+}
+
+/// Check if `url` can be opened without signing in. GitHub and GitLab answer
+/// with 404 or a redirect to the sign in page for private repositories.
+pub fn is_public(url: &str) -> bool {
+    is_ok(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_https_remotes() {
+        assert_eq!(
+            web_url("https://github.com/N0va2/xcode-discord-rpc.git").as_deref(),
+            Some("https://github.com/N0va2/xcode-discord-rpc")
+        );
+        assert_eq!(
+            web_url("https://user@gitlab.com/group/sub/project").as_deref(),
+            Some("https://gitlab.com/group/sub/project")
+        );
+    }
+
+    #[test]
+    fn converts_ssh_remotes() {
+        assert_eq!(
+            web_url("git@github.com:N0va2/xcode-discord-rpc.git").as_deref(),
+            Some("https://github.com/N0va2/xcode-discord-rpc")
+        );
+        assert_eq!(
+            web_url("ssh://git@gitlab.com:22/group/project.git").as_deref(),
+            Some("https://gitlab.com/group/project")
+        );
+    }
+
+    #[test]
+    fn ignores_other_remotes() {
+        assert_eq!(web_url("git@example.com:owner/repo.git"), None);
+        assert_eq!(web_url("/Users/me/repos/project.git"), None);
+        assert_eq!(web_url("https://github.com/owner"), None);
+    }
+}
@@ -0,0 +1,83 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Extension of the Xcode projects a workspace may contain
+const PROJECT_EXTENSION: &str = "xcodeproj";
+
+/// Returns the build configuration the scheme named `scheme` runs with, read
+/// from its `.xcscheme` file in the workspace document at `document` or the
+/// projects next to it
+pub fn launch_configuration(document: &Path, scheme: &str) -> Option<String> {
+    let contents = scheme_files(document, scheme)
+        .into_iter()
+        .find_map(|path| fs::read_to_string(path).ok())?;
+    parse_launch_configuration(&contents)
+}
+
+/// Returns the paths the file of the scheme named `scheme` may have, shared
+/// schemes first and then the schemes of every user
+fn scheme_files(document: &Path, scheme: &str) -> Vec<PathBuf> {
+    let mut containers = vec![document.to_path_buf()];
+    if let Some(dir) = document.parent() {
+        containers.extend(
+            fs::read_dir(dir)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path != document && path.extension().is_some_and(|ext| ext == PROJECT_EXTENSION)
+                }),
+        );
+    }
+    let file_name = format!("{scheme}.xcscheme");
+    containers
+        .iter()
+        .flat_map(|container| {
+            let shared = container.join("xcshareddata/xcschemes").join(&file_name);
+            let users = fs::read_dir(container.join("xcuserdata"))
+                .into_iter()
+                .flatten()
+                .flatten()
+                .map(|user| user.path().join("xcschemes").join(&file_name));
+            std::iter::once(shared).chain(users)
+        })
+        .collect()
+}
+
+/// Reads the `buildConfiguration` attribute of the `LaunchAction` element of
+/// an `.xcscheme` file
+fn parse_launch_configuration(scheme: &str) -> Option<String> {
+    let (_, action) = scheme.split_once("<LaunchAction")?;
+    let (attributes, _) = action.split_once('>')?;
+    let (_, value) = attributes.split_once("buildConfiguration")?;
+    let (_, value) = value.trim_start().strip_prefix('=')?.split_once('"')?;
+    let (configuration, _) = value.split_once('"')?;
+    (!configuration.is_empty()).then(|| configuration.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_launch_configuration() {
+        let scheme = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Scheme LastUpgradeVersion = "1500" version = "1.7">
+   <TestAction
+      buildConfiguration = "Debug">
+   </TestAction>
+   <LaunchAction
+      buildConfiguration = "Release"
+      launchStyle = "0">
+   </LaunchAction>
+</Scheme>"#;
+        assert_eq!(
+            parse_launch_configuration(scheme).as_deref(),
+            Some("Release")
+        );
+        assert_eq!(parse_launch_configuration("<Scheme></Scheme>"), None);
+    }
+}
@@ -0,0 +1,145 @@
+use std::process::Command;
+
+use crate::{watchdog::WatchedCommand, Error, Result};
+
+/// The Mac the program runs on, for `{macos_version}` and `{machine}`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SystemInfo {
+    /// Version of macOS, e.g. "macOS 15.1"
+    pub macos_version: Option<String>,
+    /// Model and chip of the Mac, e.g. "MacBook Pro M3"
+    pub machine: Option<String>,
+}
+
+impl SystemInfo {
+    /// Reads the version of macOS and the model of the Mac with `sysctl`.
+    /// Values that cannot be read are left out.
+    pub fn read() -> Self {
+        let read = |name: &str| {
+            sysctl(name)
+                .inspect_err(|err| log::debug!("Failed to read {}: {}", name, err))
+                .ok()
+        };
+        let macos_version = read("kern.osproductversion").map(|version| format!("macOS {version}"));
+        let machine = read("hw.model")
+            .map(|model| format_machine(&model, read("machdep.cpu.brand_string").as_deref()));
+        Self {
+            macos_version,
+            machine,
+        }
+    }
+}
+
+/// Returns the value of the kernel variable `name`
+fn sysctl(name: &str) -> Result<String> {
+    let output = Command::new("sysctl")
+        .args(["-n", name])
+        .watched_output()
+        .map_err(|err| Error::Process(err.to_string()))?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || value.is_empty() {
+        return Err(Error::Process(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(value)
+}
+
+/// Formats the model identifier `model`, e.g. `Mac15,3`, and the CPU brand
+/// `cpu`, e.g. `Apple M3`, as "MacBook Pro M3". Intel CPUs are left out.
+fn format_machine(model: &str, cpu: Option<&str>) -> String {
+    let family = model_family(model);
+    match cpu.and_then(|cpu| cpu.strip_prefix("Apple ")) {
+        Some(chip) => format!("{family} {chip}"),
+        None => family.to_string(),
+    }
+}
+
+/// Returns the marketing name of the model identifier `model`. Macs with
+/// Apple silicon since 2022 report `MacXX,Y`, which is looked up.
+fn model_family(model: &str) -> &'static str {
+    const FAMILIES: [(&str, &str); 6] = [
+        ("MacBookPro", "MacBook Pro"),
+        ("MacBookAir", "MacBook Air"),
+        ("MacBook", "MacBook"),
+        ("Macmini", "Mac mini"),
+        ("MacPro", "Mac Pro"),
+        ("iMac", "iMac"),
+    ];
+    const MODELS: [(&str, &str); 38] = [
+        ("Mac13,1", "Mac Studio"),
+        ("Mac13,2", "Mac Studio"),
+        ("Mac14,2", "MacBook Air"),
+        ("Mac14,3", "Mac mini"),
+        ("Mac14,5", "MacBook Pro"),
+        ("Mac14,6", "MacBook Pro"),
+        ("Mac14,7", "MacBook Pro"),
+        ("Mac14,8", "Mac Pro"),
+        ("Mac14,9", "MacBook Pro"),
+        ("Mac14,10", "MacBook Pro"),
+        ("Mac14,12", "Mac mini"),
+        ("Mac14,13", "Mac Studio"),
+        ("Mac14,14", "Mac Studio"),
+        ("Mac14,15", "MacBook Air"),
+        ("Mac15,3", "MacBook Pro"),
+        ("Mac15,4", "iMac"),
+        ("Mac15,5", "iMac"),
+        ("Mac15,6", "MacBook Pro"),
+        ("Mac15,7", "MacBook Pro"),
+        ("Mac15,8", "MacBook Pro"),
+        ("Mac15,9", "MacBook Pro"),
+        ("Mac15,10", "MacBook Pro"),
+        ("Mac15,11", "MacBook Pro"),
+        ("Mac15,12", "MacBook Air"),
+        ("Mac15,13", "MacBook Air"),
+        ("Mac15,14", "Mac Studio"),
+        ("Mac16,1", "MacBook Pro"),
+        ("Mac16,2", "iMac"),
+        ("Mac16,3", "iMac"),
+        ("Mac16,5", "MacBook Pro"),
+        ("Mac16,6", "MacBook Pro"),
+        ("Mac16,7", "MacBook Pro"),
+        ("Mac16,8", "MacBook Pro"),
+        ("Mac16,9", "Mac Studio"),
+        ("Mac16,10", "Mac mini"),
+        ("Mac16,11", "Mac mini"),
+        ("Mac16,12", "MacBook Air"),
+        ("Mac16,13", "MacBook Air"),
+    ];
+    if let Some((_, name)) = MODELS.iter().find(|(id, _)| *id == model) {
+        return name;
+    }
+    FAMILIES
+        .iter()
+        .find(|(prefix, _)| {
+            model
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+        })
+        .map_or("Mac", |(_, name)| name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_machine() {
+        assert_eq!(
+            format_machine("Mac15,3", Some("Apple M3")),
+            "MacBook Pro M3"
+        );
+        assert_eq!(
+            format_machine("MacBookPro18,3", Some("Apple M1 Pro")),
+            "MacBook Pro M1 Pro"
+        );
+        assert_eq!(
+            format_machine(
+                "Macmini8,1",
+                Some("Intel(R) Core(TM) i7-8700B CPU @ 3.20GHz")
+            ),
+            "Mac mini"
+        );
+        assert_eq!(format_machine("Mac99,1", Some("Apple M9")), "Mac M9");
+    }
+}
@@ -0,0 +1,9 @@
+/// Replaces every `{key}` in `template` with its value from `placeholders`.
+/// Unknown placeholders are left untouched.
+pub fn render_template(template: &str, placeholders: &[(&str, &str)]) -> String {
+    let mut rendered = String::from(template);
+    for (key, value) in placeholders {
+        rendered = rendered.replace(&format!("{{{key}}}"), value);
+    }
+    rendered
+}
@@ -0,0 +1,87 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Maximum length of the details, state and image texts accepted by Discord
+pub const MAX_FIELD_LEN: usize = 128;
+/// Maximum length of a button label accepted by Discord
+pub const MAX_BUTTON_LABEL_LEN: usize = 32;
+
+/// Truncates `text` to at most `max_len` characters, cutting between grapheme
+/// clusters so multi-byte characters and emoji are never split, and appending
+/// an ellipsis when anything was removed
+pub fn truncate(text: &str, max_len: usize) -> String {
+    if text.chars().count() <= max_len {
+        return text.to_string();
+    }
+    let mut truncated = String::new();
+    let mut len = 0;
+    for grapheme in text.graphemes(true) {
+        let grapheme_len = grapheme.chars().count();
+        // keep room for the ellipsis
+        if len + grapheme_len + 1 > max_len {
+            break;
+        }
+        truncated.push_str(grapheme);
+        len += grapheme_len;
+    }
+    truncated.push('…');
+    truncated
+}
+
+/// Returns whether `text` matches the glob `pattern`, where `*` stands for
+/// any number of characters and `?` for one, ignoring case
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    // Position after the last `*` and the text it is matched up to
+    let mut star: Option<(usize, usize)> = None;
+    let (mut p, mut t) = (0, 0);
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((after, matched)) => {
+                    p = after;
+                    t = matched + 1;
+                    star = Some((after, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Percent-encodes `value` for use in a URL path segment or query string
+pub fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                char::from(byte).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_globs() {
+        assert!(glob_matches("*Secrets*", "AppSecrets.swift"));
+        assert!(glob_matches("*.env", "Production.ENV"));
+        assert!(glob_matches("Config?.plist", "Config2.plist"));
+        assert!(glob_matches("*a*b", "xaxxab"));
+        assert!(!glob_matches("*.env", "env.swift"));
+        assert!(!glob_matches("Config?.plist", "Config.plist"));
+    }
+}
@@ -0,0 +1,66 @@
+/// Extracts an issue tracker ticket ID such as `PROJ-123` from a branch name.
+///
+/// Without `prefixes`, only keys written in upper case match, so that words
+/// like `release-2` are not mistaken for tickets. When `prefixes` is not
+/// empty, only those keys match, in any case, which supports trackers like
+/// Linear whose branch names are lower case. The ID is returned in upper case.
+pub fn ticket_id(branch: &str, prefixes: &[String]) -> Option<String> {
+    let chars = branch.chars().collect::<Vec<_>>();
+    let is_word = |index: usize| chars.get(index).is_some_and(char::is_ascii_alphanumeric);
+    for start in 0..chars.len() {
+        if !chars[start].is_ascii_alphabetic() || (start > 0 && is_word(start - 1)) {
+            continue;
+        }
+        let key_end = (start..chars.len())
+            .find(|&index| !is_word(index))
+            .unwrap_or(chars.len());
+        if chars.get(key_end) != Some(&'-') {
+            continue;
+        }
+        let number_end = (key_end + 1..chars.len())
+            .find(|&index| !chars[index].is_ascii_digit())
+            .unwrap_or(chars.len());
+        if number_end == key_end + 1 || is_word(number_end) {
+            continue;
+        }
+        let key = chars[start..key_end].iter().collect::<String>();
+        let matches = if prefixes.is_empty() {
+            key.len() >= 2 && !key.chars().any(|char| char.is_ascii_lowercase())
+        } else {
+            prefixes
+                .iter()
+                .any(|prefix| prefix.eq_ignore_ascii_case(&key))
+        };
+        if matches {
+            let number = chars[key_end + 1..number_end].iter().collect::<String>();
+            return Some(format!("{}-{}", key.to_ascii_uppercase(), number));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_upper_case_tickets() {
+        assert_eq!(
+            ticket_id("feature/PROJ-123-login", &[]).as_deref(),
+            Some("PROJ-123")
+        );
+        assert_eq!(ticket_id("ABC2-7_fix", &[]).as_deref(), Some("ABC2-7"));
+        assert_eq!(ticket_id("release-2", &[]), None);
+        assert_eq!(ticket_id("feature/PROJ-12a", &[]), None);
+    }
+
+    #[test]
+    fn finds_configured_prefixes_in_any_case() {
+        let prefixes = [String::from("ENG")];
+        assert_eq!(
+            ticket_id("jane/eng-42-onboarding", &prefixes).as_deref(),
+            Some("ENG-42")
+        );
+        assert_eq!(ticket_id("feature/PROJ-123-login", &prefixes), None);
+    }
+}
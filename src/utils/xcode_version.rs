@@ -0,0 +1,86 @@
+use std::{path::Path, process::Command};
+
+use crate::{watchdog::WatchedCommand, Error, Result};
+
+/// Reads the version of the Xcode app bundle at `app` from its Info.plist,
+/// e.g. "Xcode 16.2 beta" for `/Applications/Xcode-beta.app`
+pub fn read_version(app: &Path) -> Result<String> {
+    let output = Command::new("defaults")
+        .arg("read")
+        .arg(app.join("Contents/Info"))
+        .arg("CFBundleShortVersionString")
+        .watched_output()
+        .map_err(|err| Error::Process(err.to_string()))?;
+    if !output.status.success() {
+        return Err(Error::Process(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    let version = String::from_utf8_lossy(&output.stdout);
+    let app_name = app
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    Ok(format_version(version.trim(), &app_name))
+}
+
+/// Reads the version of the Swift compiler of the active toolchain, which
+/// `xcrun` picks from `xcode-select` or the `TOOLCHAINS` variable
+pub fn read_swift_version() -> Result<Option<String>> {
+    let output = Command::new("xcrun")
+        .args(["swift", "--version"])
+        .watched_output()
+        .map_err(|err| Error::Process(err.to_string()))?;
+    if !output.status.success() {
+        return Err(Error::Process(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    // The driver prints its version to stderr on some toolchains
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Ok(parse_swift_version(&stdout).or_else(|| parse_swift_version(&stderr)))
+}
+
+/// Parses `swift --version`, e.g. "Swift 6.0" from `Apple Swift version
+/// 6.0.3 (swiftlang-6.0.3.1.10 clang-1600.0.30.1)`
+fn parse_swift_version(output: &str) -> Option<String> {
+    let (_, version) = output.split_once("Swift version ")?;
+    let version = version.split_whitespace().next()?;
+    let major_minor = version.splitn(3, '.').take(2).collect::<Vec<_>>().join(".");
+    (!major_minor.is_empty()).then(|| format!("Swift {major_minor}"))
+}
+
+/// Formats the version of the app named `app_name`, marking betas, which
+/// Apple ships as `Xcode-beta.app`
+fn format_version(version: &str, app_name: &str) -> String {
+    if app_name.to_lowercase().contains("beta") {
+        format!("Xcode {version} beta")
+    } else {
+        format!("Xcode {version}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_swift_version() {
+        let output = "swift-driver version: 1.115 Apple Swift version 6.0.3 \
+                      (swiftlang-6.0.3.1.10 clang-1600.0.30.1)\nTarget: arm64-apple-macosx15.0";
+        assert_eq!(parse_swift_version(output).as_deref(), Some("Swift 6.0"));
+        let output = "Swift version 5.10-dev (LLVM 1b2c3d, Swift 4e5f6a)";
+        assert_eq!(
+            parse_swift_version(output).as_deref(),
+            Some("Swift 5.10-dev")
+        );
+        assert_eq!(parse_swift_version("xcrun: error"), None);
+    }
+
+    #[test]
+    fn marks_betas() {
+        assert_eq!(format_version("16.2", "Xcode.app"), "Xcode 16.2");
+        assert_eq!(format_version("16.2", "Xcode-beta.app"), "Xcode 16.2 beta");
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::HashSet;
+
+use serde::Deserialize;
+
+use crate::{
+    config::AppConfig,
+    integrations::http::request,
+    utils::{assets::ActivityAsset, file_language::FileLanguage, project_type::ProjectType},
+    Error, Result,
+};
+
+/// Art asset uploaded to a Discord application
+#[derive(Debug, Deserialize)]
+struct ApplicationAsset {
+    name: String,
+}
+
+/// Every `(text, image)` asset key shown in the presence
+fn used_assets() -> Vec<(&'static str, &'static str)> {
+    FileLanguage::ALL
+        .iter()
+        .map(FileLanguage::get_asset_keys)
+        .chain(ActivityAsset::ALL.iter().map(ActivityAsset::get_asset_keys))
+        .chain(ProjectType::ALL.iter().map(ProjectType::get_asset_keys))
+        .collect()
+}
+
+/// Returns the used assets whose image key is not in `available`. Discord
+/// stores asset names in lower case, so keys are compared ignoring case.
+fn missing_assets(available: &[String]) -> Vec<(&'static str, &'static str)> {
+    let mut seen = HashSet::new();
+    used_assets()
+        .into_iter()
+        .filter(|(_, image)| seen.insert(*image))
+        .filter(|(_, image)| {
+            !available
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(image))
+        })
+        .collect()
+}
+
+/// Fetches the art assets of the configured Discord application and fails
+/// when an asset key used in the presence is missing
+pub fn verify_assets(config: &AppConfig) -> Result<()> {
+    let body = request(
+        "GET",
+        &format!(
+            "https://discord.com/api/v10/oauth2/applications/{}/assets",
+            config.application_id
+        ),
+        &[],
+        None::<&()>,
+    )?;
+    let assets: Vec<ApplicationAsset> = serde_json::from_str(&body)?;
+    let available: Vec<String> = assets.into_iter().map(|asset| asset.name).collect();
+    let missing = missing_assets(&available);
+    if missing.is_empty() {
+        println!(
+            "All {} asset keys exist in application {}",
+            used_assets().len(),
+            config.application_id
+        );
+        return Ok(());
+    }
+    for (text, image) in &missing {
+        println!("Missing asset {image:?} ({text})");
+    }
+    Err(Error::Assets(format!(
+        "{} asset keys are missing in application {}",
+        missing.len(),
+        config.application_id
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_missing_assets_ignoring_case() {
+        let available: Vec<String> = used_assets()
+            .into_iter()
+            .map(|(_, image)| image.to_lowercase())
+            .filter(|image| image != "swift" && image != "tuist")
+            .collect();
+        assert_eq!(
+            missing_assets(&available),
+            [("Swift", "swift"), ("Tuist", "tuist")]
+        );
+    }
+
+    #[test]
+    fn reports_shared_keys_once() {
+        let missing = missing_assets(&[]);
+        assert_eq!(
+            missing
+                .iter()
+                .filter(|(_, image)| *image == "xcode")
+                .count(),
+            1
+        );
+    }
+}
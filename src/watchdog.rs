@@ -0,0 +1,240 @@
+use std::{
+    io::{self, Read},
+    mem,
+    process::{Child, Command, Output, Stdio},
+    sync::{Mutex, MutexGuard},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Number of expected cycle lengths after which a cycle is considered stuck
+const STALL_FACTOR: u32 = 4;
+/// Shortest time after which a cycle is considered stuck, leaving room for
+/// slow probes such as the first osascript call waiting for the Automation
+/// permission
+const MIN_STALL_LIMIT: Duration = Duration::from_secs(120);
+/// Interval between two checks of the watchdog thread
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Timing of the running cycle
+#[derive(Debug, Clone, Copy)]
+struct Cycle {
+    /// When the previous cycle finished
+    started_at: Instant,
+    /// When the running cycle is considered stuck
+    deadline: Instant,
+}
+
+/// A child process started by a probe of the main loop
+#[derive(Debug)]
+struct Probe {
+    pid: u32,
+    program: String,
+    /// Whether the watchdog killed the process
+    killed: bool,
+}
+
+/// Running cycle, `None` before the first cycle finished
+static CYCLE: Mutex<Option<Cycle>> = Mutex::new(None);
+/// Child processes of the running probes
+static PROBES: Mutex<Vec<Probe>> = Mutex::new(Vec::new());
+
+/// Locks `mutex`, recovering it if a thread panicked while holding the lock
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Marks the end of a cycle of the main loop, followed by `interval` of
+/// sleep before the next one
+pub fn cycle_finished(interval: Duration) {
+    let expected = interval.max(Duration::from_secs(1));
+    let now = Instant::now();
+    *lock(&CYCLE) = Some(Cycle {
+        started_at: now,
+        deadline: now + expected + (expected * STALL_FACTOR).max(MIN_STALL_LIMIT),
+    });
+}
+
+/// `Command::output` for probes of the main loop, whose process is killed
+/// by the watchdog when the cycle is stuck
+pub trait WatchedCommand {
+    /// Runs the command and collects its output, failing with
+    /// `ErrorKind::TimedOut` when the watchdog killed it
+    fn watched_output(&mut self) -> io::Result<Output>;
+}
+
+impl WatchedCommand for Command {
+    fn watched_output(&mut self) -> io::Result<Output> {
+        let mut child = self
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let pid = child.id();
+        lock(&PROBES).push(Probe {
+            pid,
+            program: self.get_program().to_string_lossy().to_string(),
+            killed: false,
+        });
+        let output = read_output(&mut child);
+        // The child is only reaped once it is no longer in `PROBES`, so that
+        // the watchdog never kills another process that reused its PID
+        let exited = wait_exited(pid);
+        let killed = {
+            let mut probes = lock(&PROBES);
+            let killed = probes.iter().any(|probe| probe.pid == pid && probe.killed);
+            probes.retain(|probe| probe.pid != pid);
+            killed
+        };
+        let status = child.wait()?;
+        if killed {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "killed by the watchdog",
+            ));
+        }
+        exited?;
+        let (stdout, stderr) = output?;
+        Ok(Output {
+            status,
+            stdout,
+            stderr,
+        })
+    }
+}
+
+/// Reads the standard output and error of `child` until it closes them
+fn read_output(child: &mut Child) -> io::Result<(Vec<u8>, Vec<u8>)> {
+    let stderr = child.stderr.take();
+    // Both pipes are read at the same time, so that a child filling one of
+    // them cannot block
+    let stderr = thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut stderr) = stderr {
+            stderr.read_to_end(&mut buffer)?;
+        }
+        Ok::<_, io::Error>(buffer)
+    });
+    let mut stdout = Vec::new();
+    if let Some(pipe) = child.stdout.as_mut() {
+        pipe.read_to_end(&mut stdout)?;
+    }
+    let stderr = stderr
+        .join()
+        .map_err(|_| io::Error::other("reading stderr panicked"))??;
+    Ok((stdout, stderr))
+}
+
+/// Waits until the child `pid` exits, leaving it to be reaped by
+/// `Child::wait`
+fn wait_exited(pid: u32) -> io::Result<()> {
+    loop {
+        // SAFETY: `waitid` only writes to `info`, which is a valid
+        // `siginfo_t`, and `WNOWAIT` leaves the child waitable
+        let result = unsafe {
+            let mut info: libc::siginfo_t = mem::zeroed();
+            libc::waitid(
+                libc::P_PID,
+                pid as libc::id_t,
+                &mut info,
+                libc::WEXITED | libc::WNOWAIT,
+            )
+        };
+        if result == 0 {
+            return Ok(());
+        }
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+}
+
+/// Kills the child processes of the running probes that are `selected`,
+/// returning their programs
+fn kill_probes(selected: impl Fn(&Probe) -> bool) -> Vec<String> {
+    let mut probes = lock(&PROBES);
+    probes
+        .iter_mut()
+        .filter(|probe| !probe.killed && selected(probe))
+        .filter_map(|probe| {
+            // SAFETY: `kill` has no memory effects. The process is a child
+            // that has not been reaped yet, as `watched_output` removes it
+            // from `PROBES` under this lock before reaping it, so its PID
+            // cannot have been reused
+            let result = unsafe { libc::kill(probe.pid as libc::pid_t, libc::SIGKILL) };
+            probe.killed = result == 0;
+            probe
+                .killed
+                .then(|| format!("{}[{}]", probe.program, probe.pid))
+        })
+        .collect()
+}
+
+/// Starts a thread that kills the probes of the main loop once a cycle did
+/// not finish in time, so that the failing probe ends the cycle with an
+/// error and the loop starts over
+pub fn spawn() {
+    thread::spawn(|| loop {
+        thread::sleep(CHECK_INTERVAL);
+        let mut cycle = lock(&CYCLE);
+        let Some(Cycle {
+            started_at,
+            deadline,
+        }) = *cycle
+        else {
+            continue;
+        };
+        let now = Instant::now();
+        if now < deadline {
+            continue;
+        }
+        // Give the loop time to recover before the next intervention
+        *cycle = Some(Cycle {
+            started_at,
+            deadline: now + MIN_STALL_LIMIT,
+        });
+        drop(cycle);
+        let killed = kill_probes(|_| true);
+        log::warn!(
+            "watchdog: cycle stuck stalled_secs={} killed={:?}",
+            now.duration_since(started_at).as_secs(),
+            killed
+        );
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fails_when_killed() {
+        let probe = thread::spawn(|| Command::new("sleep").arg("10").watched_output());
+        // Only the probe of this test is killed, as other tests run probes too
+        let pid = loop {
+            if let Some(probe) = lock(&PROBES).iter().find(|probe| probe.program == "sleep") {
+                break probe.pid;
+            }
+            thread::sleep(Duration::from_millis(10));
+        };
+        assert_eq!(
+            kill_probes(|probe| probe.pid == pid),
+            [format!("sleep[{pid}]")]
+        );
+        let err = probe.join().unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[test]
+    fn collects_output_and_forgets_reaped_probes() {
+        let output = Command::new("sh")
+            .args(["-c", "echo out; echo err >&2; exit 3"])
+            .watched_output()
+            .unwrap();
+        assert_eq!(output.stdout, b"out\n");
+        assert_eq!(output.stderr, b"err\n");
+        assert_eq!(output.status.code(), Some(3));
+        assert!(lock(&PROBES).iter().all(|probe| probe.program != "sh"));
+    }
+}
@@ -0,0 +1,146 @@
+use std::path::PathBuf;
+
+use crate::{
+    utils::{
+        devices, osascript,
+        osascript_parser::{FrontApp, FrontWindow, ProjectInfo},
+        power, process, xcode_version,
+    },
+    Result,
+};
+
+/// What Xcode shows, read by the presence loop
+pub trait Xcode {
+    /// Returns whether Xcode is running
+    fn is_running(&self) -> Result<bool>;
+
+    /// Returns whether Xcode is the frontmost application
+    fn is_frontmost(&self) -> Result<bool>;
+
+    /// Returns the frontmost application, which may not be Xcode
+    fn front_app(&self) -> Result<FrontApp>;
+
+    /// Returns the names of the booted simulators
+    fn booted_simulators(&self) -> Result<Vec<String>>;
+
+    /// Returns the active workspace document, or `None` when no project is open
+    fn current_project(&self) -> Result<Option<ProjectInfo>>;
+
+    /// Returns the version of the running Xcode, e.g. "Xcode 16.2 beta"
+    fn version(&self) -> Result<Option<String>>;
+
+    /// Returns the version of the active Swift toolchain, e.g. "Swift 6.0"
+    fn swift_version(&self) -> Result<Option<String>>;
+
+    /// Returns the name of the active scheme, or `None` when no project is open
+    fn active_scheme(&self) -> Result<Option<String>>;
+
+    /// Returns the front window and its focused document
+    fn front_window(&self) -> Result<FrontWindow>;
+
+    /// Returns the path of the open document called `name`
+    fn file_path(&self, name: &str) -> Result<Option<PathBuf>>;
+
+    /// Returns whether the commit sheet of the front window is open
+    fn is_commit_sheet_open(&self) -> Result<bool>;
+
+    /// Returns whether the SwiftUI preview canvas has the focus
+    fn is_preview_focused(&self) -> Result<bool>;
+
+    /// Returns the status shown by the activity view in the toolbar, empty
+    /// when it shows none
+    fn activity_status(&self) -> Result<String>;
+
+    /// Returns the names of the physical devices connected to the Mac
+    fn connected_devices(&self) -> Result<Vec<String>>;
+
+    /// Returns whether a playground is executing
+    fn is_playground_running(&self) -> Result<bool>;
+
+    /// Returns the number of open projects
+    fn open_project_count(&self) -> Result<usize>;
+
+    /// Returns whether any of the applications called `names` is running
+    fn is_any_app_running(&self, names: &[String]) -> Result<bool>;
+
+    /// Returns whether the Mac is on battery or in Low Power Mode
+    fn is_power_saving(&self) -> Result<bool>;
+}
+
+/// Xcode queried with AppleScript and the process list
+#[derive(Debug, Default)]
+pub struct AppleScript;
+
+impl Xcode for AppleScript {
+    fn is_running(&self) -> Result<bool> {
+        osascript::check_xcode()
+    }
+
+    fn is_frontmost(&self) -> Result<bool> {
+        osascript::is_xcode_frontmost()
+    }
+
+    fn front_app(&self) -> Result<FrontApp> {
+        osascript::front_app()
+    }
+
+    fn booted_simulators(&self) -> Result<Vec<String>> {
+        devices::booted_simulators()
+    }
+
+    fn current_project(&self) -> Result<Option<ProjectInfo>> {
+        osascript::current_project()
+    }
+
+    fn version(&self) -> Result<Option<String>> {
+        xcode_version::read_version(&osascript::xcode_app_path()?).map(Some)
+    }
+
+    fn swift_version(&self) -> Result<Option<String>> {
+        xcode_version::read_swift_version()
+    }
+
+    fn active_scheme(&self) -> Result<Option<String>> {
+        osascript::active_scheme()
+    }
+
+    fn front_window(&self) -> Result<FrontWindow> {
+        osascript::front_window()
+    }
+
+    fn file_path(&self, name: &str) -> Result<Option<PathBuf>> {
+        osascript::current_file_path(name)
+    }
+
+    fn is_commit_sheet_open(&self) -> Result<bool> {
+        osascript::is_commit_sheet_open()
+    }
+
+    fn is_preview_focused(&self) -> Result<bool> {
+        osascript::is_preview_focused()
+    }
+
+    fn activity_status(&self) -> Result<String> {
+        osascript::activity_status()
+    }
+
+    fn connected_devices(&self) -> Result<Vec<String>> {
+        devices::connected_devices()
+    }
+
+    fn is_playground_running(&self) -> Result<bool> {
+        process::is_playground_running()
+    }
+
+    fn open_project_count(&self) -> Result<usize> {
+        osascript::open_project_count()
+    }
+
+    fn is_any_app_running(&self, names: &[String]) -> Result<bool> {
+        osascript::is_any_process_running(names)
+    }
+
+    fn is_power_saving(&self) -> Result<bool> {
+        power::is_power_saving()
+    }
+}
@@ -1,19 +1,86 @@
-use discord_rich_presence::{
-    activity::{Activity, Assets, Timestamps},
-    DiscordIpc, DiscordIpcClient,
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
 };
 
+use chrono::{Local, TimeZone};
+use discord_rich_presence::{DiscordIpc, DiscordIpcClient};
+
 use crate::{
-    config::AppConfig,
+    activity_model::{ActivityModel, AssetKeys},
+    build_log::{BuildLogWatcher, BuildResult},
+    companion_apps::CompanionApp,
+    config::{AppConfig, ButtonConfig, FocusBehavior, IdleAction, RotationItem},
+    control::{PresenceOverride, SharedControlState},
+    derived_data::{BuildPhase, PhaseMonitor},
+    integrations::{http::is_ok, ActivitySnapshot, Integrations},
+    locale::{Locale, Text},
+    pomodoro::{format_countdown, PomodoroPhase},
+    presence_timer::PresenceTimer,
+    project_config::{project_root, ProjectConfig},
+    stats::StatsTracker,
+    test_log::{TestLogWatcher, TestProgress, TestResult, TestRun},
     utils::{
-        current_time,
+        app_info::{detect_app_info, AppInfo},
+        assets::ActivityAsset,
+        clock::Clock,
+        codename::codename,
+        devices::{redact_device_name, simulator_device},
+        duration::{format_duration, format_hours},
+        emoji::{decorate, EmojiContext},
         file_language::{FileExtention, FileLanguage, ToFileLanguage},
-        osascript::{check_xcode, current_file, current_project},
-        sleep,
+        focus::current_focus,
+        git::{changed_files, current_branch, origin_url},
+        github::open_pull_request,
+        keychain::{read_secret, Secret},
+        language_stats::{scan, LanguageStats},
+        osascript::display_notification,
+        osascript_parser::{
+            parse_run_destination, FocusedFile, FrontApp, ProjectInfo, ProjectKind,
+        },
+        periodic::Periodic,
+        platform::{detect_platform, Platform},
+        process::{is_cli_build_running, is_debugger_running},
+        project_type::ProjectType,
+        repository::{is_public, web_url},
+        scheme::launch_configuration,
+        system_info::SystemInfo,
+        template::render_template,
+        text::{glob_matches, percent_encode, truncate, MAX_BUTTON_LABEL_LEN, MAX_FIELD_LEN},
+        ticket::ticket_id,
     },
+    xcode::Xcode,
     Result,
 };
 
+/// Details about the front Xcode window used to build the presence
+enum WindowDetails {
+    /// Editing a file, with the details line, the (text, image) asset keys
+    /// and the file name unless it is hidden
+    File(String, AssetKeys, Option<String>),
+    /// A project is open without a focused file
+    NoDocument,
+    /// Reading the developer documentation
+    Documentation,
+    /// Committing changes in the commit sheet
+    Committing,
+    /// Reviewing changes in the comparison editor
+    Reviewing,
+    /// Designing in Interface Builder or the SwiftUI preview canvas, with the
+    /// details line and the file name unless it is hidden
+    Designing(String, Option<String>),
+    /// Editing or running a playground, with the details line
+    Playground(String),
+}
+
+/// Extensions of the documents Xcode edits in Interface Builder
+const INTERFACE_BUILDER_EXTENSIONS: [&str; 2] = ["storyboard", "xib"];
+/// Process name of the Simulator app
+const SIMULATOR_PROCESS: &str = "Simulator";
+/// Extension of the pages of a playground
+const PLAYGROUND_PAGE_EXTENSION: &str = ".xcplaygroundpage";
+
 enum Flow {
     /// `continue` to the next loop
     Continue(()),
@@ -22,26 +89,179 @@ enum Flow {
 }
 
 pub struct XcodeState<'a> {
+    /// What Xcode shows
+    xcode: Box<dyn Xcode>,
     xcode_is_running: bool,
-    xcode_check_cycle_counter: u8,
+    /// When to check again whether Xcode was launched
+    xcode_check: Periodic,
+    /// When to check again whether Xcode is frontmost
+    frontmost_check: Periodic,
+    /// When to update the presence again
+    refresh: Periodic,
     config: &'a AppConfig,
     discord_ipc: &'a mut DiscordIpcClient,
     discord_is_connected: bool,
-    /// Multiplier used to progressively increase sleep duration when Xcode or
-    /// Discord is not running. This helps reduce CPU usage when idle.
-    sleep_multiplier: u64,
+    /// Root directory of the current project
+    project_root: Option<PathBuf>,
+    /// Overrides from the current project's `.xcode-discord-rpc.toml`
+    project_config: ProjectConfig,
+    /// Whether the root of the current project contains the `ignore_marker`
+    project_ignored: bool,
+    /// Project worked on since the last session summary
+    summary_project: Option<String>,
+    /// Files of the current project focused within `file_switch_window`, with
+    /// when they were last focused
+    recent_files: Vec<(String, i64)>,
+    /// Presence behavior for the active macOS Focus mode
+    focus_behavior: FocusBehavior,
+    /// Whether the Mac is on battery or in Low Power Mode
+    power_saving: bool,
+    /// When the power source was last checked
+    power_checked_at: i64,
+    /// Elapsed time, idle detection and backoff
+    timer: PresenceTimer,
+    /// State changed through the control socket
+    control: SharedControlState,
+    /// Watches DerivedData for finished builds
+    build_log_watcher: BuildLogWatcher,
+    /// Result of the last finished build and until when it is shown
+    build_result: Option<(BuildResult, i64)>,
+    /// Active scheme and the build configuration it runs with, for `{scheme}`
+    /// and `{configuration}`
+    scheme: Option<(String, Option<String>)>,
+    /// Whether the scheme has to be read again
+    scheme_stale: bool,
+    /// Version of the running Xcode for `{xcode_version}`, `None` until read
+    xcode_version: Option<Option<String>>,
+    /// Version of the Swift toolchain for `{swift_version}`, `None` until read
+    swift_version: Option<Option<String>>,
+    /// macOS version and model of the Mac for `{macos_version}` and
+    /// `{machine}`, read once per run
+    system_info: Option<SystemInfo>,
+    /// Number of errors and warnings of the last finished build, for
+    /// `{errors}` and `{warnings}`
+    build_issues: Option<(u32, u32)>,
+    /// Phase inferred from DerivedData activity
+    build_phase: Option<BuildPhase>,
+    /// Watches DerivedData for the build phase, started once it is shown
+    phase_monitor: Option<PhaseMonitor>,
+    /// Watches DerivedData for running and finished test runs
+    test_log_watcher: TestLogWatcher,
+    /// Progress of the running tests
+    test_progress: Option<TestProgress>,
+    /// Result of the last finished test run and until when it is shown
+    test_result: Option<(TestResult, i64)>,
+    /// Web URL of the current project's GitHub or GitLab repository
+    repository_url: Option<String>,
+    /// Button linking to the current project's public repository
+    repository_button: Option<ButtonConfig>,
+    /// Open pull request of the checked out branch
+    pull_request: Option<u64>,
+    /// Branch and time of the last pull request check
+    pull_request_checked: Option<(String, i64)>,
+    /// Whether the current project is a workspace, project, package or playground
+    project_kind: Option<ProjectKind>,
+    /// Tooling of the current project
+    project_type: ProjectType,
+    /// Primary platform of the current project
+    platform: Option<Platform>,
+    /// Name and bundle identifier of the app of the current project
+    app_info: AppInfo,
+    /// Active time per day
+    stats: StatsTracker,
+    /// Services other than Discord receiving the activity
+    integrations: Integrations,
+    /// Lines of code per language of the current project
+    language_stats: Option<LanguageStats>,
+    /// When the current project was last scanned with tokei
+    language_stats_scanned_at: i64,
+    /// Number of files with uncommitted changes
+    changed_files: Option<usize>,
+    /// When the uncommitted changes were last counted
+    changed_files_counted_at: i64,
+    /// Texts of the presence in the configured language
+    locale: Locale,
+    /// What is being done in Xcode, for the `{emoji}` placeholder and `emoji`
+    emoji_context: EmojiContext,
+    /// URL of the project icon from `icon_url_template`, if the image exists
+    project_icon: Option<String>,
+    /// Number of source documents open in Xcode, for `{open_files}`
+    open_files: usize,
+    /// Number of projects open besides the current one, for `show_other_projects`
+    other_projects: usize,
+    /// Last run destination and whether it is a physical device
+    run_destination: Option<(String, bool)>,
+    /// When Simulator became the frontmost application, for `simulator_threshold`
+    simulator_since: Option<i64>,
+    /// Last activity sent to Discord, `None` once it was cleared
+    activity: Option<ActivityModel>,
 }
 
 impl<'a> XcodeState<'a> {
     /// Creates a new XcodeState instance with the provided configuration and Discord IPC client
-    pub fn new(config: &'a AppConfig, discord_ipc: &'a mut DiscordIpcClient) -> Self {
+    pub fn new(
+        config: &'a AppConfig,
+        discord_ipc: &'a mut DiscordIpcClient,
+        control: SharedControlState,
+        clock: Box<dyn Clock>,
+        xcode: Box<dyn Xcode>,
+    ) -> Self {
+        let now = clock.instant();
+        let stats = StatsTracker::load(clock.now());
         Self {
+            xcode,
             xcode_is_running: false,
-            xcode_check_cycle_counter: config.xcode_check_cycle,
+            xcode_check: Periodic::new(now),
+            frontmost_check: Periodic::new(now),
+            refresh: Periodic::new(now),
             config,
             discord_ipc,
             discord_is_connected: false,
-            sleep_multiplier: 1,
+            project_root: None,
+            project_config: ProjectConfig::default(),
+            project_ignored: false,
+            recent_files: Vec::new(),
+            summary_project: None,
+            focus_behavior: FocusBehavior::default(),
+            power_saving: false,
+            power_checked_at: 0,
+            timer: PresenceTimer::new(clock),
+            control: control.clone(),
+            build_log_watcher: BuildLogWatcher::new(),
+            build_result: None,
+            build_issues: None,
+            scheme: None,
+            scheme_stale: true,
+            xcode_version: None,
+            swift_version: None,
+            system_info: None,
+            build_phase: None,
+            phase_monitor: None,
+            test_log_watcher: TestLogWatcher::new(),
+            test_progress: None,
+            test_result: None,
+            repository_url: None,
+            repository_button: None,
+            pull_request: None,
+            pull_request_checked: None,
+            project_kind: None,
+            project_type: ProjectType::default(),
+            platform: None,
+            app_info: AppInfo::default(),
+            stats,
+            integrations: Integrations::new(config, control.clone()),
+            language_stats: None,
+            language_stats_scanned_at: 0,
+            changed_files: None,
+            changed_files_counted_at: 0,
+            locale: Locale::new(config.language.as_deref(), &config.translations),
+            emoji_context: EmojiContext::default(),
+            project_icon: None,
+            open_files: 0,
+            other_projects: 0,
+            run_destination: None,
+            simulator_since: None,
+            activity: None,
         }
     }
 
@@ -57,12 +277,12 @@ impl<'a> XcodeState<'a> {
             if let Err(e) = self.discord_ipc.connect() {
                 log::debug!("Discord is not running: {}", e);
                 self.discord_is_connected = false;
-                self.increase_sleep_multiplier();
+                self.timer.increase_backoff();
                 self.sleep_discord_xcode();
                 continue;
             }
             self.discord_is_connected = true;
-            self.reset_sleep_multiplier();
+            self.timer.reset_backoff();
 
             log::info!("Connected to Discord");
             self.handle_discord_session()?;
@@ -75,26 +295,79 @@ impl<'a> XcodeState<'a> {
     }
 
     /// Sleep for the configured update interval to check if Xcode/Discord is running
-    fn sleep_discord_xcode(&self) {
-        sleep(self.config.update_interval * self.sleep_multiplier);
+    fn sleep_discord_xcode(&mut self) {
+        self.sleep(self.backoff_interval());
     }
 
     /// Sleep for the configured Xcode update interval to check for updates
-    fn sleep_xcode_update(&self) {
-        sleep(self.config.xcode_update_interval);
+    fn sleep_xcode_update(&mut self) {
+        self.sleep(self.refresh_interval());
+    }
+
+    /// Sleeps until `deadline`, returning whether the Mac was asleep meanwhile
+    fn sleep_until(&mut self, deadline: Instant) -> bool {
+        self.sleep(deadline.saturating_duration_since(self.timer.instant()))
+    }
+
+    /// Sleeps for `duration`, returning whether the Mac was asleep meanwhile.
+    /// Forces Xcode and Discord to be re-checked right away after the Mac
+    /// wakes up.
+    fn sleep(&mut self, duration: Duration) -> bool {
+        if self.timer.sleep(duration).is_none() {
+            return false;
+        }
+        let now = self.timer.instant();
+        self.xcode_check.reset(now);
+        self.frontmost_check.reset(now);
+        self.refresh.reset(now);
+        true
+    }
+
+    /// `update_interval`, longer while Xcode or Discord stays closed
+    fn backoff_interval(&self) -> Duration {
+        Duration::from_secs(
+            self.config.update_interval * self.timer.backoff() * self.power_multiplier(),
+        )
+    }
+
+    /// Interval between two checks whether Xcode was launched, longer while
+    /// it stays closed
+    fn xcode_check_interval(&self) -> Duration {
+        Duration::from_secs(
+            self.config.xcode_check_interval * self.timer.backoff() * self.power_multiplier(),
+        )
+    }
+
+    /// Interval between two updates of the presence
+    fn refresh_interval(&self) -> Duration {
+        Duration::from_secs(self.xcode_update_interval() * self.power_multiplier())
     }
 
-    /// Increase the sleep multiplier using exponential backoff with a maximum
-    /// cap to avoid excessively frequent checks when Xcode or Discord are not
-    /// running.
-    fn increase_sleep_multiplier(&mut self) {
-        const MAX_MULTIPLIER: u64 = 8;
-        self.sleep_multiplier = (self.sleep_multiplier * 2).min(MAX_MULTIPLIER);
+    /// Multiplier applied to the update intervals to save power on battery
+    fn power_multiplier(&self) -> u64 {
+        if self.power_saving {
+            self.config.battery_interval_multiplier.max(1)
+        } else {
+            1
+        }
     }
 
-    /// Reset the sleep multiplier when Xcode and Discord are running again.
-    fn reset_sleep_multiplier(&mut self) {
-        self.sleep_multiplier = 1;
+    /// Re-checks the power source at most once a minute
+    fn update_power_state(&mut self) {
+        const POWER_CHECK_INTERVAL: i64 = 60;
+        if self.timer.now() - self.power_checked_at < POWER_CHECK_INTERVAL {
+            return;
+        }
+        self.power_checked_at = self.timer.now();
+        match self.xcode.is_power_saving() {
+            Ok(power_saving) => {
+                if power_saving != self.power_saving {
+                    log::debug!("Power saving: {}", power_saving);
+                }
+                self.power_saving = power_saving;
+            }
+            Err(err) => log::debug!("Failed to check power source: {}", err),
+        }
     }
 }
 
@@ -102,37 +375,35 @@ impl<'a> XcodeState<'a> {
 impl XcodeState<'_> {
     /// Checks if Xcode is running and updates internal state
     fn check_xcode(&mut self) -> Result<()> {
-        self.xcode_is_running = check_xcode()?;
-        self.xcode_check_cycle_counter = 0;
+        self.xcode_is_running = self.xcode.is_running()?;
+        self.update_power_state();
         Ok(())
     }
 
     /// Handles periodic Xcode check logic and determines flow control
     fn check_xcode_cycle(&mut self) -> Result<Flow> {
-        log::debug!("Checking Xcode cycle: {}", self.xcode_check_cycle_counter);
-        if self.xcode_check_cycle_counter == self.config.xcode_check_cycle {
-            self.xcode_check_cycle_counter = 0;
+        let now = self.timer.instant();
+        if self.xcode_check.is_due(now) {
             self.check_xcode()?;
-            if !self.xcode_is_running {
+            if self.xcode_is_running {
+                self.timer.reset_backoff();
+            } else {
                 log::debug!("Xcode is not running");
+                self.show_session_summary();
                 if self.discord_is_connected {
                     self.clear_activity()?;
                 }
-                self.increase_sleep_multiplier();
-                self.sleep_discord_xcode();
-                return Ok(Flow::Continue(()));
+                self.timer.increase_backoff();
             }
-            self.reset_sleep_multiplier();
+            self.xcode_check.schedule(now, self.xcode_check_interval());
         }
-        self.xcode_check_cycle_counter += 1;
 
         if !self.xcode_is_running {
-            self.increase_sleep_multiplier();
-            self.sleep_discord_xcode();
+            self.sleep_until(self.xcode_check.next());
             return Ok(Flow::Continue(()));
         }
 
-        self.reset_sleep_multiplier();
+        self.timer.reset_backoff();
 
         Ok(Flow::GoNext)
     }
@@ -142,137 +413,3419 @@ impl XcodeState<'_> {
 impl XcodeState<'_> {
     /// Manages the Discord session and continuously updates Rich Presence based on Xcode activity
     fn handle_discord_session(&mut self) -> Result<()> {
-        let mut started_at = Timestamps::new().start(current_time() * 1000);
-        let mut project_before = String::from("");
-        // let mut last_frontmost_at = current_time();
+        self.timer.restart();
+        let mut project_before: Option<ProjectInfo> = None;
+        self.timer.mark_frontmost();
+        // Discord shows no activity of the new connection yet
+        self.activity = None;
+        // Xcode or the selected toolchain may have changed since Xcode last ran
+        self.xcode_version = None;
+        self.swift_version = None;
+        let now = self.timer.instant();
+        self.frontmost_check.reset(now);
+        self.refresh.reset(now);
 
-        self.reset_sleep_multiplier();
+        self.timer.reset_backoff();
 
         while self.xcode_is_running {
             log::debug!("Xcode is running");
 
-            // self.update_frontmost_time(&mut last_frontmost_at)?;
+            self.timer
+                .restart_after_wake(self.config.wake_reset_threshold);
 
-            let project = self.get_current_project()?;
+            self.check_frontmost()?;
 
-            if project.is_empty(){
+            if self.control.lock().is_paused() {
+                log::debug!("Presence is paused");
                 self.clear_activity()?;
-                self.sleep_xcode_update();
-                self.check_xcode()?;
+                self.wait_for_refresh(self.refresh_interval())?;
+                continue;
+            }
+
+            let presence_override = self.control.lock().presence_override();
+            if let Some(presence) = presence_override {
+                self.set_override_activity(&presence)?;
+                self.wait_for_refresh(self.refresh_interval())?;
                 continue;
             }
 
-            if !project_before.eq(&project) {
-                started_at = Timestamps::new().start(current_time() * 1000);
-                project_before = project.clone();
+            if !self.is_within_schedule() {
+                log::debug!("Outside of schedule, suppressing presence");
+                self.clear_activity()?;
+                self.wait_for_refresh(self.refresh_interval())?;
+                continue;
             }
 
-            //let is_idle = current_time() - last_frontmost_at > self.config.idle_threshold;
+            self.update_focus_behavior();
+            if self.focus_behavior == FocusBehavior::Off {
+                log::debug!("Focus mode is active, suppressing presence");
+                self.clear_activity()?;
+                self.wait_for_refresh(self.refresh_interval())?;
+                continue;
+            }
 
-            
-            self.set_working_activity(&project, &started_at)?;
-            self.sleep_xcode_update();
-            self.check_xcode()?;
+            let front_app = self.front_app()?;
+            if self.set_companion_activity(front_app.as_ref())? {
+                self.wait_for_refresh(self.refresh_interval())?;
+                continue;
+            }
+
+            let Some(project_info) = self.xcode.current_project()? else {
+                self.clear_activity()?;
+                self.wait_for_refresh(self.refresh_interval())?;
+                continue;
+            };
+
+            if project_before.as_ref() != Some(&project_info) {
+                self.timer.restart();
+                self.load_project_config(&project_info);
+                self.build_issues = None;
+                self.scheme_stale = true;
+                project_before = Some(project_info.clone());
+            }
+            let project = project_info.name;
+
+            if self.project_ignored {
+                log::debug!(
+                    "{} is marked as confidential, suppressing presence",
+                    project
+                );
+                self.clear_activity()?;
+                self.wait_for_refresh(self.refresh_interval())?;
+                continue;
+            }
+
+            self.update_other_projects();
+            self.update_build_result(&project);
+            let phase_before = self.build_phase;
+            self.update_build_phase(&project);
+            if self.build_phase == Some(BuildPhase::Building)
+                && phase_before != Some(BuildPhase::Building)
+            {
+                // The scheme or its configuration may have changed for the build
+                self.scheme_stale = true;
+            }
+            self.update_scheme(project_info.path.as_deref());
+            self.update_versions();
+            self.update_test_run(&project);
+            self.update_pull_request();
+            self.update_changed_files();
+            self.update_language_stats();
+
+            if self.is_streaming()? {
+                self.set_streamer_activity()?;
+                self.wait_for_refresh(self.refresh_interval())?;
+                continue;
+            }
+
+            if let Some(PomodoroPhase::Break { remaining }) = self.pomodoro_phase() {
+                self.set_break_activity(&project, remaining)?;
+                self.wait_for_refresh(self.refresh_interval())?;
+                continue;
+            }
+
+            if self.set_device_activity(&project)? {
+                self.wait_for_refresh(self.refresh_interval())?;
+                continue;
+            }
+
+            if self.set_simulator_activity(&project, front_app.as_ref())? {
+                self.wait_for_refresh(self.refresh_interval())?;
+                continue;
+            }
+
+            // Waiting for a build or tests in another app is not idling
+            let is_building = self.build_phase.is_some_and(|phase| phase.is_build())
+                || self.test_progress.is_some();
+            let is_idle = self.config.require_frontmost
+                && !is_building
+                && self.timer.is_idle(self.idle_threshold());
+            if is_idle {
+                self.set_idle_activity()?;
+                continue;
+            }
+
+            self.set_working_activity(&project)?;
+            self.wait_for_refresh(self.refresh_interval())?;
+        }
+        Ok(())
+    }
+
+    /// Waits until the presence is due to be updated again, `interval` after
+    /// the previous update was due, checking whether Xcode is frontmost
+    /// meanwhile, then re-checks whether Xcode is running
+    fn wait_for_refresh(&mut self, interval: Duration) -> Result<()> {
+        self.refresh.schedule(self.timer.instant(), interval);
+        loop {
+            let mut next = self.refresh.next();
+            if self.checks_frontmost() {
+                next = next.min(self.frontmost_check.next());
+            }
+            if self.sleep_until(next) || self.refresh.is_due(self.timer.instant()) {
+                break;
+            }
+            self.check_frontmost()?;
+        }
+        self.check_xcode()
+    }
+
+    /// Whether Xcode has to be frontmost, the check is skipped on battery
+    fn checks_frontmost(&self) -> bool {
+        self.config.require_frontmost && !self.power_saving
+    }
+
+    /// Updates the timestamp for when Xcode was last in the foreground once
+    /// the frontmost check is due
+    fn check_frontmost(&mut self) -> Result<()> {
+        if !self.checks_frontmost() {
+            // Xcode counts as frontmost when the check is disabled or skipped on battery
+            self.timer.mark_frontmost();
+            return Ok(());
+        }
+        let now = self.timer.instant();
+        if !self.frontmost_check.is_due(now) {
+            return Ok(());
+        }
+        if self.xcode.is_frontmost()? {
+            self.timer.mark_frontmost();
+        } else {
+            self.timer.mark_away(self.config.frontmost_grace);
+        }
+        self.frontmost_check.schedule(
+            now,
+            Duration::from_secs(self.config.frontmost_check_interval),
+        );
+        Ok(())
+    }
+
+    /// Returns whether the screen is likely being streamed or recorded
+    fn is_streaming(&self) -> Result<bool> {
+        if !self.config.streamer_mode {
+            return Ok(false);
+        }
+        self.xcode.is_any_app_running(&self.config.streamer_apps)
+    }
+
+    /// Checks DerivedData for a build of `project` that finished since the last check
+    fn update_build_result(&mut self, project: &str) {
+        if let Some(result) = self.build_log_watcher.poll(project) {
+            self.integrations.build_finished(project, &result);
+            if let Some(issues) = result.issues() {
+                self.build_issues = Some(issues);
+            }
+            if self.config.build_result_duration > 0 {
+                self.build_result =
+                    Some((result, self.timer.now() + self.config.build_result_duration));
+            }
+        }
+    }
+
+    /// Reads the active scheme and the build configuration it runs with when
+    /// a template shows them, once per project and again when a build starts
+    fn update_scheme(&mut self, document: Option<&Path>) {
+        if !self.scheme_stale
+            || !(self.uses_placeholder("{scheme}") || self.uses_placeholder("{configuration}"))
+        {
+            return;
+        }
+        self.scheme_stale = false;
+        self.scheme = self
+            .xcode
+            .active_scheme()
+            .unwrap_or_else(|err| {
+                log::debug!("Failed to get the active scheme: {}", err);
+                None
+            })
+            .map(|scheme| {
+                let configuration =
+                    document.and_then(|document| launch_configuration(document, &scheme));
+                (scheme, configuration)
+            });
+    }
+
+    /// Reads the versions of the running Xcode, of the Swift toolchain and of
+    /// macOS once when the small image or a template shows them
+    fn update_versions(&mut self) {
+        if self.xcode_version.is_none() && self.shows_placeholder("{xcode_version}") {
+            self.xcode_version = Some(self.xcode.version().unwrap_or_else(|err| {
+                log::debug!("Failed to get the version of Xcode: {}", err);
+                None
+            }));
+        }
+        if self.swift_version.is_none() && self.shows_placeholder("{swift_version}") {
+            self.swift_version = Some(self.xcode.swift_version().unwrap_or_else(|err| {
+                log::debug!("Failed to get the version of Swift: {}", err);
+                None
+            }));
+        }
+        if self.system_info.is_none()
+            && (self.shows_placeholder("{macos_version}") || self.shows_placeholder("{machine}"))
+        {
+            self.system_info = Some(SystemInfo::read());
+        }
+    }
+
+    /// Returns whether a template or the text of the small image contains
+    /// `placeholder`
+    fn shows_placeholder(&self, placeholder: &str) -> bool {
+        self.uses_placeholder(placeholder)
+            || (self.config.project_type_icon
+                && self
+                    .config
+                    .small_text
+                    .as_deref()
+                    .is_some_and(|template| template.contains(placeholder)))
+    }
+
+    /// Returns the hover text and the image of the small image, the project
+    /// type when `project_type_icon` is enabled
+    fn small_image(&self) -> Option<AssetKeys> {
+        if !self.config.project_type_icon {
+            return None;
+        }
+        let (text, image) = self.project_type.get_asset_keys();
+        let text = self
+            .config
+            .small_text
+            .as_deref()
+            .map(|template| truncate(&self.render(template, &[]), MAX_FIELD_LEN))
+            .filter(|text| !text.is_empty())
+            .map_or(Cow::Borrowed(text), Cow::Owned);
+        Some((text, Cow::Borrowed(image)))
+    }
+
+    /// Checks DerivedData for tests of `project` running or finished since the
+    /// last check
+    fn update_test_run(&mut self, project: &str) {
+        if !self.config.show_test_progress {
+            return;
+        }
+        self.test_progress = None;
+        match self.test_log_watcher.poll(project) {
+            Some(TestRun::Running(progress)) => self.test_progress = Some(progress),
+            Some(TestRun::Finished(result)) if self.config.build_result_duration > 0 => {
+                self.test_result =
+                    Some((result, self.timer.now() + self.config.build_result_duration));
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the result of the last test run while it is shown
+    fn active_test_result(&self) -> Option<&TestResult> {
+        self.test_result
+            .as_ref()
+            .filter(|(_, shown_until)| self.timer.now() < *shown_until)
+            .map(|(result, _)| result)
+    }
+
+    /// Infers whether Xcode or the command line is building or indexing
+    /// `project`
+    fn update_build_phase(&mut self, project: &str) {
+        if self.is_cli_building() {
+            self.build_phase = Some(BuildPhase::CliBuilding);
+            return;
+        }
+        if !self.config.show_build_phase {
+            self.build_phase = None;
+            return;
+        }
+        self.build_phase = self
+            .phase_monitor
+            .get_or_insert_with(PhaseMonitor::new)
+            .phase(project);
+    }
+
+    /// Check if a command-line build is running in the current project's root
+    fn is_cli_building(&self) -> bool {
+        let Some(root) = self
+            .project_root
+            .as_ref()
+            .filter(|_| self.config.detect_cli_builds)
+        else {
+            return false;
+        };
+        is_cli_build_running(root).unwrap_or_else(|err| {
+            log::debug!("Failed to check for command-line builds: {}", err);
+            false
+        })
+    }
+
+    /// Returns the result of the last build while it should still be shown
+    fn active_build_result(&self) -> Option<&BuildResult> {
+        self.build_result
+            .as_ref()
+            .filter(|(_, shown_until)| self.timer.now() < *shown_until)
+            .map(|(result, _)| result)
+    }
+
+    /// Returns whether presence is allowed by the configured schedule
+    fn is_within_schedule(&self) -> bool {
+        let Some(schedule) = &self.config.schedule else {
+            return true;
+        };
+        let now = Local
+            .timestamp_opt(self.timer.now(), 0)
+            .single()
+            .unwrap_or_else(Local::now);
+        schedule.contains(now)
+    }
+
+    /// Updates the presence behavior from the active macOS Focus mode
+    fn update_focus_behavior(&mut self) {
+        if self.config.focus_modes.is_empty() {
+            return;
+        }
+        self.focus_behavior = match current_focus() {
+            Ok(Some(focus)) => {
+                log::debug!("Active Focus mode: {}", focus);
+                self.config
+                    .focus_modes
+                    .get(&focus)
+                    .copied()
+                    .unwrap_or_default()
+            }
+            Ok(None) => FocusBehavior::default(),
+            Err(err) => {
+                log::debug!("Failed to read Focus mode: {}", err);
+                FocusBehavior::default()
+            }
+        };
+    }
+
+    /// Finds the web URL of the project's `origin` remote when it points to
+    /// a GitHub or GitLab repository
+    fn find_repository_url(&self) -> Option<String> {
+        let root = self.project_root.as_ref()?;
+        let remote = origin_url(root).unwrap_or_else(|err| {
+            log::debug!("Failed to get git remote: {}", err);
+            None
+        })?;
+        web_url(&remote)
+    }
+
+    /// Builds the "View Repository" button when the project's repository is public
+    fn find_repository_button(&self) -> Option<ButtonConfig> {
+        if !self.config.repository_button {
+            return None;
+        }
+        let url = self.repository_url.clone()?;
+        if !is_public(&url) {
+            log::debug!("Not linking private or unreachable repository {}", url);
+            return None;
+        }
+        Some(ButtonConfig {
+            label: self.locale.text(Text::ViewRepository).to_string(),
+            url,
+        })
+    }
+
+    /// Resolves `icon_url_template` for `project`, falling back to the
+    /// language image when the icon cannot be found. Hidden projects are not
+    /// looked up, as the request would give their name away.
+    fn find_project_icon(&self, project: &str) -> Option<String> {
+        let template = self
+            .config
+            .icon_url_template
+            .as_ref()
+            .filter(|_| !self.hide_project_details())?;
+        let url = render_template(template, &[("project", &percent_encode(project))]);
+        if !is_ok(&url) {
+            log::debug!("Project icon {} not found", url);
+            return None;
+        }
+        Some(url)
+    }
+
+    /// Loads the per-project configuration for the current project, falling
+    /// back to no overrides when it cannot be found or read
+    fn load_project_config(&mut self, project: &ProjectInfo) {
+        self.project_root = project.path.as_deref().map(project_root);
+        self.project_kind = Some(project.kind);
+        self.project_config = match &self.project_root {
+            Some(root) => ProjectConfig::load(root).unwrap_or_else(|err| {
+                log::warn!("Failed to load project config: {}", err);
+                ProjectConfig::default()
+            }),
+            None => ProjectConfig::default(),
+        };
+        log::debug!("Project config: {:?}", self.project_config);
+        let marker = &self.config.ignore_marker;
+        self.project_ignored = !marker.is_empty()
+            && self
+                .project_root
+                .as_deref()
+                .is_some_and(|root| root.join(marker).exists());
+        self.project_type = self
+            .project_root
+            .as_deref()
+            .map(ProjectType::detect)
+            .unwrap_or_default();
+        self.platform = self.project_root.as_deref().and_then(detect_platform);
+        self.app_info = self
+            .project_root
+            .as_deref()
+            .map(detect_app_info)
+            .unwrap_or_default();
+        self.repository_url = self.find_repository_url();
+        self.repository_button = self.find_repository_button();
+        self.project_icon = self.find_project_icon(&project.name);
+        self.pull_request = None;
+        self.pull_request_checked = None;
+        self.changed_files = None;
+        self.changed_files_counted_at = 0;
+        self.language_stats = None;
+        self.language_stats_scanned_at = 0;
+        self.recent_files.clear();
+    }
+
+    /// Sends `model` to Discord unless it is already shown, so that the
+    /// client does not wake up or redraw the presence for nothing. Returns
+    /// whether the activity was sent.
+    fn set_activity(&mut self, model: ActivityModel) -> Result<bool> {
+        if self.activity.as_ref() == Some(&model) {
+            return Ok(false);
+        }
+        model.send(self.discord_ipc)?;
+        self.activity = Some(model);
+        Ok(true)
+    }
+
+    /// Activity with the generic Xcode image and the elapsed time of the
+    /// current session, for everything but working on a file
+    fn generic_activity(&self, details: Option<String>, state: Option<String>) -> ActivityModel {
+        ActivityModel {
+            details,
+            state,
+            large_image: asset_keys(FileLanguage::Unknown.get_asset_keys()),
+            small_image: None,
+            buttons: Vec::new(),
+            started_at: self.timer.started_at(),
+            activity_type: self.config.activity_type,
+            party: None,
+        }
+    }
+
+    /// Sets Discord activity to idle state, or clears it depending on `idle_action`
+    fn set_idle_activity(&mut self) -> Result<()> {
+        self.stats.pause(self.timer.now());
+        self.show_session_summary();
+        self.integrations.end_session();
+        match self.config.idle_action {
+            IdleAction::Idle => {
+                let idle = if self.config.emoji {
+                    decorate(self.locale.text(Text::Idle), EmojiContext::Idle.emoji())
+                } else {
+                    self.locale.text(Text::Idle).to_string()
+                };
+                if self.set_activity(self.generic_activity(Some(idle.clone()), Some(idle)))? {
+                    log::info!("Updated activity: idle");
+                }
+            }
+            IdleAction::Clear => {
+                self.clear_activity()?;
+                log::info!("Cleared activity: Xcode is not frontmost");
+            }
+        }
+        // Xcode coming back to the front has to be noticed right away
+        self.wait_for_refresh(self.refresh_interval())
+    }
+
+    /// Shows the time worked today on the project of the session that ended,
+    /// once per session, when `session_summary` is enabled
+    fn show_session_summary(&mut self) {
+        let Some(project) = self.summary_project.take() else {
+            return;
+        };
+        let message = self.session_summary(&project);
+        log::info!("{}", message);
+        if let Err(err) = display_notification("xcode-discord-rpc", &message) {
+            log::debug!("Failed to show the session summary: {}", err);
+        }
+    }
+
+    /// Returns the text of the session summary of `project`
+    fn session_summary(&self, project: &str) -> String {
+        self.locale.format(
+            Text::SessionSummary,
+            &[
+                (
+                    "duration",
+                    &format_duration(self.stats.today_on(self.timer.now(), project)),
+                ),
+                ("project", project),
+            ],
+        )
+    }
+
+    /// Sets Discord activity to a generic state that does not reveal the
+    /// project or file, ending the session of the integrations so that they
+    /// stop showing them too
+    fn set_streamer_activity(&mut self) -> Result<()> {
+        self.integrations.end_session();
+        let details = self.locale.text(Text::CodingInXcode).to_string();
+        if self.set_activity(self.generic_activity(Some(details), None))? {
+            log::debug!("Updated activity: streamer mode");
         }
         Ok(())
     }
 
-    /// Updates the timestamp for when Xcode was last in the foreground
-    // fn update_frontmost_time(&self, last_frontmost_at: &mut i64) -> Result<()> {
-    //     if is_xcode_frontmost()? {
-    //         *last_frontmost_at = current_time();
-    //     }
-    //     Ok(())
-    // }
+    /// Returns the frontmost application, `None` when neither `companion_apps`
+    /// nor `simulator_threshold` need it
+    fn front_app(&self) -> Result<Option<FrontApp>> {
+        if self.config.companion_apps.is_empty() && self.config.simulator_threshold <= 0 {
+            return Ok(None);
+        }
+        self.xcode.front_app().map(Some)
+    }
+
+    /// Sets Discord activity to the companion app in front of Xcode, returning
+    /// whether one of `companion_apps` is frontmost
+    fn set_companion_activity(&mut self, front_app: Option<&FrontApp>) -> Result<bool> {
+        let Some((app, front_app)) = front_app.and_then(|front_app| {
+            CompanionApp::find(&self.config.companion_apps, &front_app.name)
+                .map(|app| (app, front_app))
+        }) else {
+            return Ok(false);
+        };
+        self.timer.mark_frontmost();
+        self.stats.pause(self.timer.now());
+        self.integrations.end_session();
+        let details = self.locale.text(app.details()).to_string();
+        let state = app
+            .document_name(&front_app.window_title)
+            .filter(|_| !self.hide_project_details())
+            .map(|document| {
+                let state = self
+                    .locale
+                    .format(Text::StateTemplate, &[("project", document)]);
+                truncate(&state, MAX_FIELD_LEN)
+            });
+        let activity = ActivityModel {
+            large_image: asset_keys(app.asset().get_asset_keys()),
+            ..self.generic_activity(Some(details), state)
+        };
+        if self.set_activity(activity)? {
+            log::info!("Updated activity: {}", app.process_name());
+        }
+        Ok(true)
+    }
 
-    /// Retrieves current project name, respecting hide_project configuration
-    fn get_current_project(&self) -> Result<String> {
-        if self.config.hide_project {
-            Ok(String::from(""))
+    /// Sets Discord activity to running the app on a physical device, returning
+    /// whether the activity view shows it running on one
+    fn set_device_activity(&mut self, project: &str) -> Result<bool> {
+        if !self.config.detect_device_runs {
+            return Ok(false);
+        }
+        let status = self.xcode.activity_status()?;
+        let Some(destination) = parse_run_destination(&status) else {
+            return Ok(false);
+        };
+        if !self.is_physical_device(destination) {
+            return Ok(false);
+        }
+        // The device has the attention rather than Xcode
+        self.timer.mark_frontmost();
+        let device = if self.config.redact_device_name {
+            redact_device_name(destination).unwrap_or(self.locale.text(Text::ADevice))
         } else {
-            current_project()
-        }
-    }
-
-    /// Sets Discord activity to idle state
-    // fn set_idle_activity(&mut self, started_at: &Timestamps) -> Result<()> {
-    //     self.discord_ipc.set_activity(
-    //         Activity::new()
-    //             .timestamps(started_at.clone())
-    //             .assets(
-    //                 Assets::new()
-    //                     .large_text(FileLanguage::Unknown.get_text_asset_key())
-    //                     .large_image(FileLanguage::Unknown.get_image_asset_key()),
-    //             )
-    //             .details("Idle")
-    //             .state("Idle"),
-    //     )?;
-    //     log::info!("Updated activity: idle");
-    //     self.increase_sleep_multiplier();
-    //     self.sleep_discord_xcode();
-    //     self.check_xcode()?;
-    //     Ok(())
-    // }
+            destination
+        };
+        let details = self
+            .locale
+            .format(Text::RunningOnDevice, &[("device", device)]);
+        let state = truncate(&self.get_project_state(project), MAX_FIELD_LEN);
+        let activity = ActivityModel {
+            large_image: asset_keys(ActivityAsset::Device.get_asset_keys()),
+            ..self.generic_activity(Some(truncate(&details, MAX_FIELD_LEN)), Some(state))
+        };
+        if self.set_activity(activity)? {
+            log::info!("Updated activity: running on a device");
+        }
+        Ok(true)
+    }
+
+    /// Returns whether the run destination `destination` is a physical device,
+    /// listing the connected devices only when the destination changed
+    fn is_physical_device(&mut self, destination: &str) -> bool {
+        if let Some((previous, physical)) = &self.run_destination {
+            if previous == destination {
+                return *physical;
+            }
+        }
+        let physical = self
+            .xcode
+            .connected_devices()
+            .map(|devices| devices.iter().any(|device| device == destination))
+            .unwrap_or_else(|err| {
+                log::debug!("Failed to list the connected devices: {}", err);
+                false
+            });
+        self.run_destination = Some((destination.to_string(), physical));
+        physical
+    }
+
+    /// Sets Discord activity to testing `project` in the Simulator once it has
+    /// been frontmost for `simulator_threshold`, returning whether it was set.
+    /// Until then the Simulator counts as Xcode being frontmost.
+    fn set_simulator_activity(
+        &mut self,
+        project: &str,
+        front_app: Option<&FrontApp>,
+    ) -> Result<bool> {
+        let Some(front_app) = front_app
+            .filter(|app| self.config.simulator_threshold > 0 && app.name == SIMULATOR_PROCESS)
+        else {
+            self.simulator_since = None;
+            return Ok(false);
+        };
+        self.timer.mark_frontmost();
+        let now = self.timer.now();
+        let since = *self.simulator_since.get_or_insert(now);
+        if now - since < self.config.simulator_threshold {
+            return Ok(false);
+        }
+        self.stats.pause(self.timer.now());
+        let device = match simulator_device(&front_app.window_title) {
+            Some(device) => Some(device.to_string()),
+            None => self
+                .xcode
+                .booted_simulators()
+                .unwrap_or_else(|err| {
+                    log::debug!("Failed to list the booted simulators: {}", err);
+                    Vec::new()
+                })
+                .into_iter()
+                .next(),
+        };
+        let project = self.display_project_name(project);
+        let details = match &device {
+            Some(device) => self.locale.format(
+                Text::TestingInSimulatorOn,
+                &[("project", &project), ("device", device)],
+            ),
+            None => self
+                .locale
+                .format(Text::TestingInSimulator, &[("project", &project)]),
+        };
+        let activity = ActivityModel {
+            large_image: asset_keys(ActivityAsset::Simulator.get_asset_keys()),
+            ..self.generic_activity(Some(truncate(&details, MAX_FIELD_LEN)), None)
+        };
+        if self.set_activity(activity)? {
+            log::info!("Updated activity: testing in the Simulator");
+        }
+        Ok(true)
+    }
+
+    /// Sets Discord activity to a pomodoro break card
+    fn set_break_activity(&mut self, project: &str, remaining: u64) -> Result<()> {
+        self.stats.pause(self.timer.now());
+        self.integrations.end_session();
+        let details = self.locale.format(
+            Text::OnBreak,
+            &[("countdown", &format_countdown(remaining))],
+        );
+        let state = (!self.config.pomodoro.clear_details_on_break)
+            .then(|| truncate(&self.get_project_state(project), MAX_FIELD_LEN));
+        if self.set_activity(self.generic_activity(Some(details), state))? {
+            log::debug!("Updated activity: pomodoro break");
+        }
+        Ok(())
+    }
+
+    /// Returns the interval of the running pomodoro
+    fn pomodoro_phase(&self) -> Option<PomodoroPhase> {
+        let started_at = self.control.lock().pomodoro_started_at()?;
+        Some(self.config.pomodoro.phase(started_at, self.timer.now()))
+    }
+
+    /// Sets Discord activity to the presence set manually with the `set` command
+    fn set_override_activity(&mut self, presence: &PresenceOverride) -> Result<()> {
+        let details = presence
+            .details
+            .as_deref()
+            .map(|details| truncate(details, MAX_FIELD_LEN));
+        let state = presence
+            .state
+            .as_deref()
+            .map(|state| truncate(state, MAX_FIELD_LEN));
+        if self.set_activity(self.generic_activity(details, state))? {
+            log::debug!("Updated activity: manual presence");
+        }
+        Ok(())
+    }
 
     /// Sets Discord activity to working state with project and file information
-    fn set_working_activity(&mut self, project: &str, started_at: &Timestamps) -> Result<()> {
+    fn set_working_activity(&mut self, project: &str) -> Result<()> {
+        if self.config.session_summary {
+            self.summary_project = Some(project.to_string());
+        }
+        let window_details = self.get_file_details()?;
+        let (file, language) = match &window_details {
+            WindowDetails::File(_, (language, _), file) => (
+                file.clone(),
+                Some(language.to_string())
+                    .filter(|language| language != FileLanguage::Unknown.get_text_asset_key()),
+            ),
+            WindowDetails::Designing(_, file) => (file.clone(), None),
+            WindowDetails::Playground(_) => (
+                None,
+                Some(FileLanguage::Swift.get_text_asset_key().to_string()),
+            ),
+            _ => (None, None),
+        };
+        let max_gap = (self.xcode_update_interval() * self.power_multiplier() * 2) as i64;
+        self.stats.tick(
+            self.timer.now(),
+            project,
+            file.as_deref(),
+            language.as_deref(),
+            max_gap,
+        );
         // Get all data first
-        let (details, (large_text, large_image)) = self.get_file_details()?;
-        let state = self.get_project_state(project);
-
-        // Now use the data to set activity
-        let activity = Activity::new()
-            .timestamps(started_at.clone())
-            .assets(
-                Assets::new()
-                    .large_text(&large_text)
-                    .large_image(&large_image),
+        let (details, state, large_image) = match window_details {
+            WindowDetails::File(details, keys, _) => (
+                self.rotate_details(details, &keys.0),
+                Some(self.get_project_state(project)),
+                keys,
+            ),
+            WindowDetails::NoDocument => (
+                self.get_browsing_details(project),
+                None,
+                asset_keys(FileLanguage::Unknown.get_asset_keys()),
+            ),
+            WindowDetails::Documentation => (
+                self.locale.text(Text::ReadingDocumentation).to_string(),
+                Some(self.get_project_state(project)),
+                asset_keys(ActivityAsset::Documentation.get_asset_keys()),
+            ),
+            WindowDetails::Committing => (
+                self.locale.format(
+                    Text::Committing,
+                    &[("project", &self.display_project_name(project))],
+                ),
+                None,
+                asset_keys(ActivityAsset::SourceControl.get_asset_keys()),
+            ),
+            WindowDetails::Reviewing => (
+                self.locale.format(
+                    Text::Reviewing,
+                    &[("project", &self.display_project_name(project))],
+                ),
+                None,
+                asset_keys(ActivityAsset::Review.get_asset_keys()),
+            ),
+            WindowDetails::Designing(details, _) => (
+                details,
+                Some(self.get_project_state(project)),
+                asset_keys(ActivityAsset::Design.get_asset_keys()),
+            ),
+            WindowDetails::Playground(details) => (
+                details,
+                Some(self.get_project_state(project)),
+                asset_keys(FileLanguage::Swift.get_asset_keys()),
+            ),
+        };
+        let test_summary = match (self.test_progress, self.active_test_result()) {
+            (Some(progress), _) => Some(progress.localized_summary(&self.locale)),
+            (None, Some(result)) => Some(result.localized_summary(&self.locale)),
+            (None, None) => None,
+        };
+        let (details, state) = match (test_summary, self.active_build_result(), self.build_phase) {
+            (Some(summary), _, _) => (summary, Some(self.get_project_state(project))),
+            (None, Some(result), _) => (
+                result.localized_summary(&self.locale),
+                Some(self.get_project_state(project)),
+            ),
+            (None, None, Some(phase)) => (
+                phase.summary(
+                    &self.display_project_name(project),
+                    self.platform,
+                    &self.locale,
+                ),
+                Some(self.get_project_state(project)),
+            ),
+            (None, None, None) => (details, state),
+        };
+        let state = match self.pomodoro_phase() {
+            Some(PomodoroPhase::Work { remaining }) => Some(
+                self.locale
+                    .format(Text::Focus, &[("countdown", &format_countdown(remaining))]),
+            ),
+            _ => state,
+        };
+        let pairing_started_at = self.control.lock().pairing_started_at();
+        let state = match pairing_started_at {
+            Some(_) => Some(self.config.pairing.state(&self.locale)),
+            None => state,
+        };
+        let (details, state) = if self.config.emoji {
+            let emoji = self.emoji_context.emoji();
+            (
+                decorate(&details, emoji),
+                state.map(|state| decorate(&state, emoji)),
             )
-            .details(&details)
-            .state(&state);
+        } else {
+            (details, state)
+        };
+        let details = truncate(&details, MAX_FIELD_LEN);
+        let state = state.map(|state| truncate(&state, MAX_FIELD_LEN));
+        let large_image = self.render_large_text(large_image, project, file.as_deref());
+        let (mut large_text, large_image) = self.large_asset_overrides(large_image);
+        // The texts of the application's assets are short enough
+        if let Cow::Owned(text) = &mut large_text {
+            *text = truncate(text, MAX_FIELD_LEN);
+        }
+        let repository_button = self
+            .repository_button
+            .as_ref()
+            .filter(|_| !self.hide_project_details());
+        let ticket_button = self.ticket_button();
+        let buttons = self
+            .buttons()
+            .iter()
+            .chain(ticket_button.as_ref())
+            .chain(repository_button)
+            .take(2)
+            .map(|button| {
+                (
+                    truncate(&button.label, MAX_BUTTON_LABEL_LEN),
+                    button.url.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
 
-        self.discord_ipc.set_activity(activity)?;
-        log::debug!("Updated activity: working on a project");
-        self.reset_sleep_multiplier();
+        let model = ActivityModel {
+            details: Some(details.clone()),
+            state: state.clone(),
+            large_image: (large_text, large_image),
+            small_image: self.small_image(),
+            buttons,
+            started_at: self.timer.started_at(),
+            activity_type: self.config.activity_type,
+            party: pairing_started_at.map(|started_at| self.config.pairing.party(started_at)),
+        };
+        if self.set_activity(model)? {
+            log::debug!("Updated activity: working on a project");
+        }
+        self.integrations.publish(&ActivitySnapshot {
+            project: project.to_string(),
+            display_project: self.display_project_name(project).to_string(),
+            file,
+            language,
+            details,
+            state,
+            started_at: self.timer.started_at(),
+        });
+        self.timer.reset_backoff();
         Ok(())
     }
 
     /// Clear the Discord activity
     fn clear_activity(&mut self) -> Result<()> {
+        self.stats.pause(self.timer.now());
+        self.integrations.end_session();
+        self.activity = None;
         self.discord_ipc.clear_activity()?;
         Ok(())
     }
 
+    /// Formats the focused file called `name` according to `file_display`
+    fn display_file(&self, name: &str) -> String {
+        let display = self.config.file_display;
+        let path = if display.needs_path() {
+            self.xcode.file_path(name).unwrap_or_else(|err| {
+                log::debug!("Failed to get the path of {}: {}", name, err);
+                None
+            })
+        } else {
+            None
+        };
+        display.format(name, path.as_deref(), self.project_root.as_deref())
+    }
+
+    /// Picks the emoji for the focused file with `extension`, checking for a
+    /// debugger only when an emoji is shown
+    fn update_emoji_context(&mut self, extension: Option<String>) {
+        let debugging = self.uses_emoji()
+            && is_debugger_running().unwrap_or_else(|err| {
+                log::debug!("Failed to check for a debugger: {}", err);
+                false
+            });
+        self.emoji_context = EmojiContext::for_file(extension.as_deref(), debugging);
+    }
+
+    /// Returns whether the presence shows an emoji, through `emoji` or a template
+    fn uses_emoji(&self) -> bool {
+        self.config.emoji || self.uses_placeholder("{emoji}")
+    }
+
+    /// Returns whether one of the templates contains `placeholder`
+    fn uses_placeholder(&self, placeholder: &str) -> bool {
+        [
+            Some(self.details_template()),
+            Some(self.state_template()),
+            self.config.browsing_template.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .any(|template| template.contains(placeholder))
+    }
+
     /// Retrieves detailed information about current file for Discord Rich Presence
-    fn get_file_details(&self) -> Result<(String, (String, String))> {
-        let mut file_language = FileLanguage::Unknown;
-        let mut keys = (
-            String::from(file_language.get_text_asset_key()),
-            String::from(file_language.get_image_asset_key()),
-        );
+    fn get_file_details(&mut self) -> Result<WindowDetails> {
+        if self.config.detect_source_control && self.xcode.is_commit_sheet_open()? {
+            return Ok(WindowDetails::Committing);
+        }
+
+        let mut keys = asset_keys(FileLanguage::Unknown.get_asset_keys());
 
-        let details = if self.config.hide_file {
-            String::from("Working on a file")
+        let window = self.xcode.front_window()?;
+        self.open_files = window.open_files;
+        if self.project_kind == Some(ProjectKind::Playground) {
+            self.update_emoji_context(None);
+            return Ok(WindowDetails::Playground(
+                self.get_playground_details(&window.file)?,
+            ));
+        }
+        if let FocusedFile::Document(name) = &window.file {
+            if self.is_designing(name)? {
+                self.update_emoji_context(None);
+                let file_name = (!self.hide_file_named(name)).then(|| name.clone());
+                let file = match &file_name {
+                    Some(name) => self.display_file(name),
+                    None => self.locale.text(Text::AFile).to_string(),
+                };
+                let details = self.locale.format(Text::Designing, &[("file", &file)]);
+                return Ok(WindowDetails::Designing(details, file_name));
+            }
+        }
+        if let FocusedFile::Document(name) = &window.file {
+            self.record_focused_file(name);
+        }
+        let hidden = match &window.file {
+            FocusedFile::Document(name) => self.hide_file_named(name),
+            _ => self.hide_file(),
+        };
+        let mut file_name = None;
+        let file = if hidden {
+            self.locale.text(Text::AFile).to_string()
         } else {
-            let file = current_file()?;
-            let file_extension = file.get_file_extension();
-            file_language = file_extension.to_file_language();
-            keys = (
-                String::from(file_language.get_text_asset_key()),
-                String::from(file_language.get_image_asset_key()),
-            );
-            format!("Working on {file}")
+            let file = match window.file {
+                FocusedFile::Document(file) => file,
+                FocusedFile::NoDocument => {
+                    self.update_emoji_context(None);
+                    return Ok(WindowDetails::NoDocument);
+                }
+                FocusedFile::Documentation => {
+                    self.update_emoji_context(None);
+                    return Ok(WindowDetails::Documentation);
+                }
+                FocusedFile::Review => {
+                    self.update_emoji_context(None);
+                    return Ok(WindowDetails::Reviewing);
+                }
+            };
+            let file_language = file.get_file_extension().to_file_language();
+            keys = asset_keys(file_language.get_asset_keys());
+            file_name = Some(file.clone());
+            let display = self.display_file(&file);
+            if window.unsaved && self.config.unsaved_indicator {
+                format!("{display} ●")
+            } else {
+                display
+            }
+        };
+        self.update_emoji_context(file_name.as_deref().map(FileExtention::get_file_extension));
+        let details = match self.switching_file_count() {
+            Some(count) => self
+                .locale
+                .format(Text::SwitchingFiles, &[("count", &count.to_string())]),
+            None => self.render(self.details_template(), &[("file", &file)]),
         };
 
-        Ok((details, keys))
+        Ok(WindowDetails::File(details, keys, file_name))
     }
 
-    /// Generates state text based on project name and configuration
-    fn get_project_state(&self, project: &str) -> String {
-        if self.config.hide_project {
-            String::from("in a Project")
-        } else {
-            format!("in {project}")
+    /// Returns the details line of a playground, naming the focused page
+    /// unless files are hidden. The `Contents.swift` of a playground without
+    /// pages is no page.
+    fn get_playground_details(&self, file: &FocusedFile) -> Result<String> {
+        let running = self.xcode.is_playground_running()?;
+        let page = match file {
+            FocusedFile::Document(name) if !self.hide_file() && name != "Contents.swift" => {
+                Some(name.strip_suffix(PLAYGROUND_PAGE_EXTENSION).unwrap_or(name))
+            }
+            _ => None,
+        };
+        Ok(match (running, page) {
+            (true, Some(page)) => self
+                .locale
+                .format(Text::RunningPlaygroundPage, &[("page", page)]),
+            (true, None) => self.locale.text(Text::RunningPlayground).to_string(),
+            (false, Some(page)) => self
+                .locale
+                .format(Text::EditingPlaygroundPage, &[("page", page)]),
+            (false, None) => self.locale.text(Text::EditingPlayground).to_string(),
+        })
+    }
+
+    /// Returns whether the document `name` is designed rather than edited:
+    /// Interface Builder documents always are, Swift files while the focused
+    /// SwiftUI preview canvas is detected
+    fn is_designing(&self, name: &str) -> Result<bool> {
+        let extension = name.get_file_extension();
+        if INTERFACE_BUILDER_EXTENSIONS.contains(&extension.as_str()) {
+            return Ok(true);
         }
+        if extension == "swift" && self.config.detect_swiftui_previews {
+            return self.xcode.is_preview_focused();
+        }
+        Ok(false)
+    }
+
+    /// Picks the fact of `rotation` to show on the details line for the
+    /// current interval, skipping facts that are unavailable or hidden
+    fn rotate_details(&self, details: String, language: &str) -> String {
+        if self.config.rotation.is_empty() {
+            return details;
+        }
+        let facts = self
+            .config
+            .rotation
+            .iter()
+            .filter_map(|item| match item {
+                RotationItem::File => Some(details.clone()),
+                RotationItem::Language => (language != FileLanguage::Unknown.get_text_asset_key())
+                    .then(|| self.locale.format(Text::Writing, &[("language", language)])),
+                RotationItem::Branch => self
+                    .get_branch()
+                    .map(|branch| self.locale.format(Text::OnBranch, &[("branch", &branch)])),
+                RotationItem::Elapsed => Some(self.locale.format(
+                    Text::Session,
+                    &[("duration", &format_duration(self.timer.elapsed()))],
+                )),
+                RotationItem::Changes => self.changed_files.map(|count| match count {
+                    0 => self.locale.text(Text::WorkingTreeClean).to_string(),
+                    1 => self.locale.text(Text::FileChanged).to_string(),
+                    count => self
+                        .locale
+                        .format(Text::FilesChanged, &[("count", &count.to_string())]),
+                }),
+                RotationItem::Ticket => self
+                    .get_ticket()
+                    .map(|ticket| self.locale.format(Text::Ticket, &[("ticket", &ticket)])),
+                RotationItem::PullRequest => self
+                    .pull_request
+                    .filter(|_| !self.hide_project_details())
+                    .map(|number| {
+                        self.locale
+                            .format(Text::PullRequestOpen, &[("number", &number.to_string())])
+                    }),
+            })
+            .collect::<Vec<_>>();
+        let interval = self.config.rotation_interval.max(1) as i64;
+        let index = (self.timer.now() / interval) as usize % facts.len().max(1);
+        facts.into_iter().nth(index).unwrap_or(details)
+    }
+
+    /// Checks for an open pull request when the branch changed or
+    /// `pull_request_interval` passed, and only when rotation shows it
+    fn update_pull_request(&mut self) {
+        if !self.config.rotation.contains(&RotationItem::PullRequest) {
+            return;
+        }
+        let (Some(url), Some(branch)) = (self.repository_url.clone(), self.get_branch()) else {
+            self.pull_request = None;
+            return;
+        };
+        let now = self.timer.now();
+        if let Some((checked_branch, checked_at)) = &self.pull_request_checked {
+            if *checked_branch == branch
+                && now - checked_at < self.config.pull_request_interval as i64
+            {
+                return;
+            }
+        }
+        let token = read_secret(Secret::Github)
+            .ok()
+            .or_else(|| self.config.github_token.clone());
+        self.pull_request =
+            open_pull_request(&self.config.github_api_url, &url, &branch, token.as_deref())
+                .unwrap_or_else(|err| {
+                    log::debug!("Failed to check for an open pull request: {}", err);
+                    None
+                });
+        self.pull_request_checked = Some((branch, now));
     }
-}
\ No newline at end of file
+
+    /// Counts the uncommitted changes every `git_status_interval`, and only
+    /// when rotation shows them
+    fn update_changed_files(&mut self) {
+        if !self.config.rotation.contains(&RotationItem::Changes) {
+            return;
+        }
+        let now = self.timer.now();
+        if now - self.changed_files_counted_at < self.config.git_status_interval as i64 {
+            return;
+        }
+        let Some(root) = self.project_root.as_ref() else {
+            return;
+        };
+        self.changed_files_counted_at = now;
+        self.changed_files = match changed_files(root) {
+            Ok(count) => Some(count),
+            Err(err) => {
+                log::debug!("Failed to count changed files: {}", err);
+                None
+            }
+        };
+    }
+
+    /// Scans the project with tokei every `language_stats_interval`
+    fn update_language_stats(&mut self) {
+        if !self.config.language_stats {
+            return;
+        }
+        let now = self.timer.now();
+        if now - self.language_stats_scanned_at < self.config.language_stats_interval as i64 {
+            return;
+        }
+        let Some(root) = self.project_root.as_ref() else {
+            return;
+        };
+        self.language_stats_scanned_at = now;
+        self.language_stats = scan(root).unwrap_or_else(|err| {
+            log::warn!("Failed to count lines of code: {}", err);
+            None
+        });
+    }
+
+    /// Extracts the issue tracker ticket from the current git branch
+    fn get_ticket(&self) -> Option<String> {
+        ticket_id(&self.get_branch()?, &self.config.ticket_prefixes)
+    }
+
+    /// Builds the button linking to the ticket of the current git branch
+    fn ticket_button(&self) -> Option<ButtonConfig> {
+        let template = self.config.ticket_url_template.as_ref()?;
+        let ticket = self.get_ticket()?;
+        Some(ButtonConfig {
+            label: self.locale.format(Text::OpenTicket, &[("ticket", &ticket)]),
+            url: render_template(template, &[("ticket", &ticket)]),
+        })
+    }
+
+    /// Retrieves the git branch of the current project, unless the project is hidden
+    fn get_branch(&self) -> Option<String> {
+        if self.hide_project_details() {
+            return None;
+        }
+        let root = self.project_root.as_ref()?;
+        current_branch(root).unwrap_or_else(|err| {
+            log::debug!("Failed to get git branch: {}", err);
+            None
+        })
+    }
+
+    /// Generates state text based on project name and configuration,
+    /// mentioning the other open projects when `show_other_projects` is enabled
+    fn get_project_state(&self, project: &str) -> String {
+        let state = self.render(
+            self.state_template(),
+            &[("project", &self.display_project_name(project))],
+        );
+        let others = match self.other_projects {
+            0 => return state,
+            1 => self.locale.text(Text::OtherProject).to_string(),
+            count => self
+                .locale
+                .format(Text::OtherProjects, &[("count", &count.to_string())]),
+        };
+        format!("{state} {others}")
+    }
+
+    /// Counts the projects open besides the current one, only when
+    /// `show_other_projects` is enabled
+    fn update_other_projects(&mut self) {
+        if !self.config.show_other_projects {
+            return;
+        }
+        self.other_projects = self
+            .xcode
+            .open_project_count()
+            .map(|count| count.saturating_sub(1))
+            .unwrap_or_else(|err| {
+                log::debug!("Failed to count the open projects: {}", err);
+                0
+            });
+    }
+
+    /// Generates details text shown while no file is focused
+    fn get_browsing_details(&self, project: &str) -> String {
+        self.render(
+            self.config
+                .browsing_template
+                .as_deref()
+                .unwrap_or(self.locale.text(Text::BrowsingTemplate)),
+            &[("project", &self.display_project_name(project))],
+        )
+    }
+
+    /// Renders `template` with `placeholders` and the placeholders describing
+    /// the current project, which are available in every template
+    fn render(&self, template: &str, placeholders: &[(&str, &str)]) -> String {
+        let top_language = self
+            .language_stats
+            .as_ref()
+            .map(LanguageStats::top_language_summary)
+            .unwrap_or_default();
+        let lines_of_code = self
+            .language_stats
+            .as_ref()
+            .map(LanguageStats::lines_of_code_summary)
+            .unwrap_or_default();
+        let (scheme, configuration) = match &self.scheme {
+            Some((scheme, configuration)) => (
+                if self.hide_project_details() {
+                    ""
+                } else {
+                    scheme.as_str()
+                },
+                configuration.as_deref().unwrap_or_default(),
+            ),
+            None => ("", ""),
+        };
+        let xcode_version = self.xcode_version.clone().flatten().unwrap_or_default();
+        let xcode_version = xcode_version.as_str();
+        let swift_version = self.swift_version.clone().flatten().unwrap_or_default();
+        let swift_version = swift_version.as_str();
+        let system_info = self.system_info.clone().unwrap_or_default();
+        let macos_version = system_info.macos_version.as_deref().unwrap_or_default();
+        let machine = system_info.machine.as_deref().unwrap_or_default();
+        let (errors, warnings) = self.build_issues.unwrap_or_default();
+        let errors = self.issue_count(errors, Text::Error, Text::Errors);
+        let warnings = self.issue_count(warnings, Text::Warning, Text::Warnings);
+        let (app_name, bundle_id) = if self.hide_project_details() {
+            ("", "")
+        } else {
+            (
+                self.app_info.name.as_deref().unwrap_or_default(),
+                self.app_info.bundle_id.as_deref().unwrap_or_default(),
+            )
+        };
+        let today_seconds = self.stats.today(self.timer.now());
+        let today = format_duration(today_seconds);
+        let elapsed = format_duration(self.timer.elapsed());
+        let goal = match self.config.daily_goal {
+            0 => String::new(),
+            goal => self.locale.format(
+                Text::DailyGoal,
+                &[
+                    ("done", &format_hours(today_seconds)),
+                    ("goal", &format_hours(goal)),
+                ],
+            ),
+        };
+        let open_files = self.open_files.to_string();
+        let project_placeholders = [
+            ("emoji", self.emoji_context.emoji()),
+            ("project_type", self.project_type.get_asset_keys().0),
+            ("today", &today),
+            ("elapsed", &elapsed),
+            ("goal", &goal),
+            ("open_files", &open_files),
+            ("xcode_version", xcode_version),
+            ("swift_version", swift_version),
+            ("macos_version", macos_version),
+            ("machine", machine),
+            ("scheme", scheme),
+            ("configuration", configuration),
+            ("app_name", app_name),
+            ("bundle_id", bundle_id),
+            ("errors", &errors),
+            ("warnings", &warnings),
+            ("top_language", &top_language),
+            ("loc", &lines_of_code),
+            (
+                "project_kind",
+                self.project_kind.map_or("", |kind| kind.name()),
+            ),
+            (
+                "platform",
+                self.platform.map_or("", |platform| platform.name()),
+            ),
+        ];
+        let placeholders = placeholders
+            .iter()
+            .chain(&project_placeholders)
+            .copied()
+            .collect::<Vec<_>>();
+        render_template(template, &placeholders)
+    }
+
+    /// Returns `count` issues with the text for one or many, empty for none
+    fn issue_count(&self, count: u32, one: Text, many: Text) -> String {
+        match count {
+            0 => String::new(),
+            1 => self.locale.text(one).to_string(),
+            count => self.locale.format(many, &[("count", &count.to_string())]),
+        }
+    }
+
+    /// Returns the project name to display, respecting hide_project, aliases
+    /// and pseudonymize
+    fn display_project_name<'p>(&'p self, project: &'p str) -> Cow<'p, str> {
+        if self.hide_project() {
+            return Cow::Borrowed(self.locale.text(Text::AProject));
+        }
+        match self.config.project_aliases.get(project) {
+            Some(alias) => Cow::Borrowed(alias),
+            None if self.config.pseudonymize => Cow::Owned(
+                self.locale
+                    .format(Text::ProjectCodename, &[("codename", codename(project))]),
+            ),
+            None => Cow::Borrowed(project),
+        }
+    }
+}
+
+/// Configuration accessors for `XcodeState` that apply per-project overrides
+impl XcodeState<'_> {
+    fn hide_file(&self) -> bool {
+        self.focus_behavior == FocusBehavior::Hidden
+            || self
+                .project_config
+                .hide_file
+                .unwrap_or(self.config.hide_file)
+    }
+
+    /// Adds the focused file `name` to the history of recently focused files,
+    /// dropping the files focused longer than `file_switch_window` ago
+    fn record_focused_file(&mut self, name: &str) {
+        if self.config.file_switch_threshold == 0 {
+            return;
+        }
+        let now = self.timer.now();
+        let window = self.config.file_switch_window;
+        self.recent_files
+            .retain(|(file, focused_at)| file != name && now - focused_at <= window);
+        self.recent_files.push((name.to_string(), now));
+    }
+
+    /// Returns the number of recently focused files once it reaches
+    /// `file_switch_threshold`
+    fn switching_file_count(&self) -> Option<usize> {
+        let threshold = self.config.file_switch_threshold;
+        (threshold > 0 && self.recent_files.len() >= threshold).then_some(self.recent_files.len())
+    }
+
+    /// Returns whether the file called `name` is hidden, either because all
+    /// files are or because it matches `hidden_files`
+    fn hide_file_named(&self, name: &str) -> bool {
+        self.hide_file()
+            || self
+                .config
+                .hidden_files
+                .iter()
+                .any(|pattern| glob_matches(pattern, name))
+    }
+
+    fn hide_project(&self) -> bool {
+        self.focus_behavior == FocusBehavior::Hidden
+            || self
+                .project_config
+                .hide_project
+                .unwrap_or(self.config.hide_project)
+    }
+
+    /// Returns whether details that give the project away, like its branch
+    /// or repository, are left out
+    fn hide_project_details(&self) -> bool {
+        self.hide_project() || self.config.pseudonymize
+    }
+
+    fn idle_threshold(&self) -> i64 {
+        self.project_config
+            .idle_threshold
+            .unwrap_or(self.config.idle_threshold)
+    }
+
+    fn xcode_update_interval(&self) -> u64 {
+        self.project_config
+            .xcode_update_interval
+            .unwrap_or(self.config.xcode_update_interval)
+            .max(1)
+    }
+
+    fn details_template(&self) -> &str {
+        self.project_config
+            .details_template
+            .as_deref()
+            .or(self.config.details_template.as_deref())
+            .unwrap_or(self.locale.text(Text::DetailsTemplate))
+    }
+
+    fn state_template(&self) -> &str {
+        self.project_config
+            .state_template
+            .as_deref()
+            .or(self.config.state_template.as_deref())
+            .unwrap_or(self.locale.text(Text::StateTemplate))
+    }
+
+    /// Renders `large_text_template` as the hover text of the large image,
+    /// where `{language}` is the text of `keys`
+    fn render_large_text(&self, keys: AssetKeys, project: &str, file: Option<&str>) -> AssetKeys {
+        let Some(template) = self.config.large_text_template.as_deref() else {
+            return keys;
+        };
+        let (text, image) = keys;
+        let file = match file {
+            Some(name) => self.display_file(name),
+            None if self.hide_file() => self.locale.text(Text::AFile).to_string(),
+            None => String::new(),
+        };
+        let text = self.render(
+            template,
+            &[
+                ("language", &text),
+                ("file", &file),
+                ("project", &self.display_project_name(project)),
+            ],
+        );
+        (Cow::Owned(text), image)
+    }
+
+    /// Replaces the large image and its hover text with the ones of the
+    /// per-project configuration, unless the project is hidden
+    fn large_asset_overrides(&self, (text, image): AssetKeys) -> AssetKeys {
+        if self.hide_project_details() {
+            return (text, image);
+        }
+        (
+            self.project_config
+                .large_text
+                .clone()
+                .map_or(text, Cow::Owned),
+            self.project_config
+                .large_image
+                .clone()
+                .or_else(|| self.project_icon.clone())
+                .map_or(image, Cow::Owned),
+        )
+    }
+
+    fn buttons(&self) -> &[ButtonConfig] {
+        self.project_config
+            .buttons
+            .as_deref()
+            .unwrap_or(&self.config.buttons)
+    }
+}
+
+/// Borrows static (text, image) asset keys
+fn asset_keys((text, image): (&'static str, &'static str)) -> AssetKeys {
+    (Cow::Borrowed(text), Cow::Borrowed(image))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        io::Write,
+        process::{Command, Stdio},
+        sync::Arc,
+        time::SystemTime,
+    };
+
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{
+        control::{self, Request},
+        simulation::Scene,
+        testing::{
+            config, discord_server::FakeDiscord, http_server::FakeHttp, sandbox,
+            scripted_xcode::ScriptedXcode,
+        },
+        utils::{
+            clock::SimulatedClock,
+            osascript_parser::{parse_front_window, parse_project, split_fields, strip_output},
+        },
+    };
+
+    /// Runs the presence loop through `script` against a fake Discord,
+    /// returning the activities it sent
+    fn run(config: &AppConfig, script: Vec<Option<Scene>>) -> Vec<Value> {
+        let clock = SimulatedClock::new();
+        let control = SharedControlState::new(Arc::new(clock.clone()));
+        run_with_clock(config, control, clock, script)
+    }
+
+    /// Runs the presence loop like `run`, with the state set through the
+    /// control socket
+    fn run_with_control(
+        config: &AppConfig,
+        control: SharedControlState,
+        script: Vec<Option<Scene>>,
+    ) -> Vec<Value> {
+        run_with_clock(config, control, SimulatedClock::new(), script)
+    }
+
+    /// Runs the presence loop like `run`, on `clock`
+    fn run_with_clock(
+        config: &AppConfig,
+        control: SharedControlState,
+        clock: SimulatedClock,
+        script: Vec<Option<Scene>>,
+    ) -> Vec<Value> {
+        sandbox();
+        let discord = FakeDiscord::start();
+        let mut client = DiscordIpcClient::new(&config.application_id).unwrap();
+        let result = XcodeState::new(
+            config,
+            &mut client,
+            control,
+            Box::new(clock),
+            Box::new(ScriptedXcode::new(script)),
+        )
+        .run();
+        assert!(matches!(result, Err(crate::Error::Oascript(_))));
+        drop(client);
+        discord.stop()
+    }
+
+    /// Kinds of the events recorded in the event log of `control`
+    fn event_kinds(control: &SharedControlState) -> Vec<String> {
+        let logs = control::handle_request(Request::Logs { tail: None }, control);
+        logs.message
+            .lines()
+            .filter_map(|line| line.split_whitespace().nth(2))
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// DerivedData folder of `project` in the sandbox
+    fn derived_data(project: &str) -> PathBuf {
+        sandbox()
+            .join("Library/Developer/Xcode/DerivedData")
+            .join(format!("{project}-bqxnyuvcdpcfhnfyxjgwzkhtgbqd"))
+    }
+
+    /// Editing `file` in `project`, whose root in the sandbox contains `files`
+    fn in_root(project: &str, file: &str, files: &[(&str, &str)]) -> Scene {
+        let root = sandbox().join(project);
+        fs::create_dir_all(&root).unwrap();
+        for (name, content) in files {
+            fs::write(root.join(name), content).unwrap();
+        }
+        Scene {
+            project: Some(ProjectInfo {
+                name: project.to_string(),
+                path: Some(root.join(format!("{project}.xcodeproj"))),
+                kind: ProjectKind::Project,
+            }),
+            ..Scene::editing(project, file)
+        }
+    }
+
+    /// Runs git in the root of `project`, which `in_root` created
+    fn git(project: &str, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(sandbox().join(project))
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    /// Payload of a "Playing" activity with the elapsed time since the start
+    /// of the simulated clock
+    fn activity(details: &str, state: &str, (text, image): (&str, &str)) -> Value {
+        json!({
+            "details": details,
+            "state": state,
+            "assets": { "large_text": text, "large_image": image },
+            "timestamps": { "start": SimulatedClock::EPOCH * 1000 },
+            "type": 0,
+        })
+    }
+
+    #[test]
+    fn sends_activity_changes() {
+        let config = config("language = \"en\"\n");
+        let main = Scene::editing("Demo", "main.swift");
+        let activities = run(
+            &config,
+            vec![
+                Some(main.clone()),
+                Some(main),
+                Some(Scene::editing("Demo", "Info.plist")),
+                None,
+                None,
+            ],
+        );
+        assert_eq!(
+            activities,
+            vec![
+                activity("Working on main.swift", "in Demo", ("Swift", "swift")),
+                activity("Working on Info.plist", "in Demo", ("Xcode", "xcode")),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn goes_idle_when_not_frontmost() {
+        let config =
+            config("language = \"en\"\nrequire_frontmost = true\nidle_threshold = \"5s\"\n");
+        let editing = Scene::editing("Demo", "main.swift");
+        let away = Scene {
+            frontmost: false,
+            ..editing.clone()
+        };
+        let activities = run(
+            &config,
+            vec![
+                Some(editing.clone()),
+                Some(away.clone()),
+                Some(away),
+                Some(editing),
+                None,
+                None,
+            ],
+        );
+        let working = activity("Working on main.swift", "in Demo", ("Swift", "swift"));
+        assert_eq!(
+            activities,
+            vec![
+                working.clone(),
+                activity("Idle", "Idle", ("Xcode", "xcode")),
+                working,
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn clears_presence_when_not_frontmost() {
+        let config = config(
+            "language = \"en\"\nrequire_frontmost = true\nidle_threshold = \"5s\"\n\
+             idle_action = \"clear\"\n",
+        );
+        let editing = Scene::editing("Demo", "main.swift");
+        let away = Scene {
+            frontmost: false,
+            ..editing.clone()
+        };
+        let activities = run(
+            &config,
+            vec![
+                Some(editing.clone()),
+                Some(away.clone()),
+                Some(away),
+                Some(editing),
+                None,
+                None,
+            ],
+        );
+        let working = activity("Working on main.swift", "in Demo", ("Swift", "swift"));
+        assert_eq!(
+            activities,
+            vec![working.clone(), Value::Null, working, Value::Null]
+        );
+    }
+
+    #[test]
+    fn notices_return_from_idle_without_backoff() {
+        let config =
+            config("language = \"en\"\nrequire_frontmost = true\nidle_threshold = \"5s\"\n");
+        let editing = Scene::editing("Demo", "main.swift");
+        let away = Scene {
+            frontmost: false,
+            ..editing.clone()
+        };
+        let elapsed = |script: Vec<Option<Scene>>| {
+            let clock = SimulatedClock::new();
+            let activities = run_with_clock(
+                &config,
+                SharedControlState::default(),
+                clock.clone(),
+                script,
+            );
+            (activities.len(), clock.now() - SimulatedClock::EPOCH)
+        };
+        let (_, working) = elapsed(vec![
+            Some(editing.clone()),
+            Some(editing.clone()),
+            Some(editing.clone()),
+            Some(editing.clone()),
+            Some(editing.clone()),
+        ]);
+        let (activities, idling) = elapsed(vec![
+            Some(editing.clone()),
+            Some(away.clone()),
+            Some(away.clone()),
+            Some(away),
+            Some(editing),
+        ]);
+        // Working, idle and working again
+        assert_eq!(activities, 3);
+        // Checks go on at the refresh interval while idle
+        assert_eq!(idling, working);
+    }
+
+    #[test]
+    fn hides_project_while_streaming() {
+        let config = config("language = \"en\"\nstreamer_mode = true\n");
+        let streaming = Scene {
+            running_apps: vec!["OBS".to_string()],
+            ..Scene::editing("ClientApp", "Secrets.swift")
+        };
+        let activities = run(&config, vec![Some(streaming), None, None]);
+        let mut generic = activity("Coding in Xcode", "", ("Xcode", "xcode"));
+        generic.as_object_mut().unwrap().remove("state");
+        assert_eq!(activities, vec![generic, Value::Null]);
+    }
+
+    #[test]
+    fn sends_working_activity_only_when_changed() {
+        let config = config("language = \"en\"\n");
+        let main = Scene::editing("Unchanged", "main.swift");
+        let app = Scene::editing("Unchanged", "App.swift");
+        let activities = run(
+            &config,
+            vec![
+                Some(main.clone()),
+                Some(main.clone()),
+                Some(main),
+                Some(app.clone()),
+                Some(app),
+                None,
+                None,
+            ],
+        );
+        assert_eq!(
+            activities,
+            vec![
+                activity("Working on main.swift", "in Unchanged", ("Swift", "swift")),
+                activity("Working on App.swift", "in Unchanged", ("Swift", "swift")),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_repeated_generic_activities() {
+        let config = config("language = \"en\"\nstreamer_mode = true\n");
+        let streaming = Scene {
+            running_apps: vec!["OBS".to_string()],
+            ..Scene::editing("Repeated", "main.swift")
+        };
+        let activities = run(
+            &config,
+            vec![
+                Some(streaming.clone()),
+                Some(streaming.clone()),
+                Some(streaming.clone()),
+                None,
+                None,
+                Some(streaming),
+                None,
+                None,
+            ],
+        );
+        let details = activities
+            .iter()
+            .map(|activity| activity["details"].as_str().unwrap_or_default())
+            .collect::<Vec<_>>();
+        // Once cleared, the same activity is sent again
+        assert_eq!(details, ["Coding in Xcode", "", "Coding in Xcode", ""]);
+    }
+
+    #[test]
+    fn ends_integration_session_in_streamer_mode() {
+        let config = config("language = \"en\"\nstreamer_mode = true\n");
+        let editing = Scene::editing("Secret", "main.swift");
+        let streaming = Scene {
+            running_apps: vec!["OBS".to_string()],
+            ..editing.clone()
+        };
+        let control = SharedControlState::default();
+        let activities = run_with_control(
+            &config,
+            control.clone(),
+            vec![Some(editing.clone()), Some(streaming), Some(editing)],
+        );
+        assert_eq!(activities[0]["details"], "Working on main.swift");
+        assert_eq!(activities[1]["details"], "Coding in Xcode");
+        assert_eq!(activities[2]["details"], "Working on main.swift");
+        // The session ends while streaming and once the loop stops
+        assert_eq!(
+            event_kinds(&control),
+            [
+                "session_start",
+                "update",
+                "session_end",
+                "session_start",
+                "update",
+                "session_end"
+            ]
+        );
+    }
+
+    #[test]
+    fn suppresses_presence_while_paused() {
+        let config = config("language = \"en\"\n");
+        let control = SharedControlState::default();
+        assert!(control::handle_request(Request::Pause { seconds: None }, &control).ok);
+        let script = || vec![Some(Scene::editing("Muted", "main.swift")), None, None];
+        let activities = run_with_control(&config, control.clone(), script());
+        assert!(!activities.is_empty());
+        assert!(activities.iter().all(Value::is_null), "{activities:?}");
+
+        assert!(control::handle_request(Request::Resume, &control).ok);
+        let activities = run_with_control(&config, control, script());
+        assert_eq!(
+            activities,
+            vec![
+                activity("Working on main.swift", "in Muted", ("Swift", "swift")),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn shows_presence_set_manually() {
+        let config = config("language = \"en\"\n");
+        let control = SharedControlState::default();
+        let set = Request::Set {
+            details: Some("Pairing with a teammate".to_string()),
+            state: Some("on SecretApp".to_string()),
+            seconds: None,
+        };
+        assert!(control::handle_request(set, &control).ok);
+        let activities = run_with_control(
+            &config,
+            control,
+            vec![Some(Scene::editing("SecretApp", "main.swift")), None, None],
+        );
+        assert_eq!(
+            activities,
+            vec![
+                activity(
+                    "Pairing with a teammate",
+                    "on SecretApp",
+                    ("Xcode", "xcode")
+                ),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn shows_pairing_party() {
+        let config = config("language = \"en\"\n[pairing]\npartner = \"Alex\"\n");
+        let control = SharedControlState::default();
+        assert!(control::handle_request(Request::PairStart, &control).ok);
+        let activities = run_with_control(
+            &config,
+            control,
+            vec![Some(Scene::editing("Demo", "main.swift")), None, None],
+        );
+        assert_eq!(activities[0]["state"], "👥 Pairing with Alex");
+        assert_eq!(activities[0]["party"]["size"], json!([2, 2]));
+        assert_eq!(activities[1], Value::Null);
+    }
+
+    #[test]
+    fn shows_designing_in_interface_builder() {
+        let config = config("language = \"en\"\ndetect_swiftui_previews = true\n");
+        let canvas = Scene {
+            preview_focused: true,
+            ..Scene::editing("Demo", "ContentView.swift")
+        };
+        let activities = run(
+            &config,
+            vec![
+                Some(Scene::editing("Demo", "Main.storyboard")),
+                Some(canvas),
+                Some(Scene::editing("Demo", "ContentView.swift")),
+                None,
+                None,
+            ],
+        );
+        let design = ("Interface Builder", "design");
+        assert_eq!(
+            activities,
+            vec![
+                activity("Designing Main.storyboard", "in Demo", design),
+                activity("Designing ContentView.swift", "in Demo", design),
+                activity(
+                    "Working on ContentView.swift",
+                    "in Demo",
+                    ("Swift", "swift")
+                ),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn shows_playground_execution() {
+        let config = config("language = \"en\"\n");
+        let editing = Scene {
+            project: Some(ProjectInfo {
+                name: "Sketch".to_string(),
+                path: None,
+                kind: ProjectKind::Playground,
+            }),
+            ..Scene::editing("Sketch", "Shapes.xcplaygroundpage")
+        };
+        let running = Scene {
+            playground_running: true,
+            ..editing.clone()
+        };
+        let activities = run(&config, vec![Some(editing), Some(running), None, None]);
+        let swift = ("Swift", "swift");
+        assert_eq!(
+            activities,
+            vec![
+                activity("Editing Shapes", "in Sketch", swift),
+                activity("Running Shapes", "in Sketch", swift),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn shows_companion_app() {
+        let config = config("language = \"en\"\n");
+        let composing = Scene {
+            front_app: Some(FrontApp {
+                name: "Reality Composer Pro".to_string(),
+                window_title: "Garden.realitycomposerpro — Scene.usda".to_string(),
+            }),
+            ..Scene::editing("Demo", "main.swift")
+        };
+        let activities = run(&config, vec![Some(composing), None, None]);
+        assert_eq!(
+            activities,
+            vec![
+                activity(
+                    "Composing a RealityKit scene",
+                    "in Garden",
+                    ("Reality Composer Pro", "reality_composer_pro")
+                ),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn shows_training_in_create_ml() {
+        let config = config("language = \"en\"\n");
+        let training = |window_title: &str| Scene {
+            front_app: Some(FrontApp {
+                name: "Create ML".to_string(),
+                window_title: window_title.to_string(),
+            }),
+            ..Scene::editing("Trained", "main.swift")
+        };
+        let activities = run(
+            &config,
+            vec![
+                Some(training("Classifier.mlproj — Model Sources")),
+                Some(training("")),
+                None,
+                None,
+            ],
+        );
+        let named = activity(
+            "Training a model in Create ML",
+            "in Classifier",
+            ("Create ML", "create_ml"),
+        );
+        // The project name is only shown while a document is open
+        let mut unnamed = named.clone();
+        unnamed.as_object_mut().unwrap().remove("state");
+        assert_eq!(activities, vec![named, unnamed, Value::Null]);
+    }
+
+    #[test]
+    fn shows_testing_in_simulator() {
+        let config = config("language = \"en\"\nsimulator_threshold = \"1s\"\n");
+        let testing = Scene {
+            front_app: Some(FrontApp {
+                name: "Simulator".to_string(),
+                window_title: "iPhone 15 – iOS 17.0".to_string(),
+            }),
+            ..Scene::editing("Demo", "main.swift")
+        };
+        let activities = run(
+            &config,
+            vec![Some(testing.clone()), Some(testing), None, None],
+        );
+        let mut simulator = activity(
+            "Testing Demo in the Simulator (iPhone 15)",
+            "",
+            ("Simulator", "simulator"),
+        );
+        simulator.as_object_mut().unwrap().remove("state");
+        assert_eq!(
+            activities,
+            vec![
+                activity("Working on main.swift", "in Demo", ("Swift", "swift")),
+                simulator,
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn shows_running_on_device() {
+        let config = config("language = \"en\"\ndetect_device_runs = true\n");
+        let running = |destination: &str| Scene {
+            activity_status: format!("Running Demo on {destination}"),
+            connected_devices: vec!["Hamza's iPhone".to_string()],
+            ..Scene::editing("Demo", "main.swift")
+        };
+        let activities = run(
+            &config,
+            vec![
+                Some(running("Hamza's iPhone")),
+                Some(running("iPhone 15")),
+                None,
+                None,
+            ],
+        );
+        assert_eq!(
+            activities,
+            vec![
+                activity(
+                    "Running on Hamza's iPhone",
+                    "in Demo",
+                    ("Physical Device", "device")
+                ),
+                activity("Working on main.swift", "in Demo", ("Swift", "swift")),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn shows_archiving() {
+        let config = config("language = \"en\"\n");
+        let build_db = derived_data("Shipping").join(
+            "Build/Intermediates.noindex/ArchiveIntermediates/Shipping\
+             /IntermediateBuildFilesPath/XCBuildData/build.db",
+        );
+        fs::create_dir_all(build_db.parent().unwrap()).unwrap();
+        fs::write(&build_db, "").unwrap();
+        let activities = run(
+            &config,
+            vec![Some(Scene::editing("Shipping", "main.swift")), None, None],
+        );
+        assert_eq!(
+            activities,
+            vec![
+                activity(
+                    "Archiving Shipping for release 🚀",
+                    "in Shipping",
+                    ("Swift", "swift")
+                ),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn shows_resolving_packages() {
+        let config = config("language = \"en\"\n");
+        let checkouts = derived_data("Packages").join("SourcePackages/checkouts");
+        fs::create_dir_all(&checkouts).unwrap();
+        let activities = run(
+            &config,
+            vec![Some(Scene::editing("Packages", "main.swift")), None, None],
+        );
+        assert_eq!(
+            activities,
+            vec![
+                activity(
+                    "Resolving Package Graph…",
+                    "in Packages",
+                    ("Swift", "swift")
+                ),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn fills_build_issue_placeholders() {
+        let config = config(
+            "language = \"en\"\nbuild_result_duration = 0\n\
+             details_template = \"Working on {file} · {errors}\"\n\
+             state_template = \"in {project} ({warnings})\"\n",
+        );
+        let logs = derived_data("Issues").join("Logs/Build");
+        fs::create_dir_all(&logs).unwrap();
+        let message = "2@11\"error: oops4\"oops0000000000000000^0#0#0(";
+        let build_log = format!(
+            "SLF010#21%IDEActivityLogSection1@0#11\"Build MyApp\
+             31%IDEDiagnosticActivityLogMessage{message}2#{message}2#{message}1#12\"Build failed"
+        );
+        let path = logs.join("Build.xcactivitylog");
+        let mut gzip = Command::new("gzip")
+            .arg("-c")
+            .stdin(Stdio::piped())
+            .stdout(fs::File::create(&path).unwrap())
+            .spawn()
+            .unwrap();
+        gzip.stdin
+            .take()
+            .unwrap()
+            .write_all(build_log.as_bytes())
+            .unwrap();
+        gzip.wait().unwrap();
+        // The build finishes after the presence loop started
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+
+        let activities = run(
+            &config,
+            vec![Some(Scene::editing("Issues", "Foo.swift")), None, None],
+        );
+        assert_eq!(
+            activities,
+            vec![
+                activity(
+                    "Working on Foo.swift · 2 errors",
+                    "in Issues (1 warning)",
+                    ("Swift", "swift")
+                ),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn fills_scheme_placeholders() {
+        let config = config(
+            "language = \"en\"\nstate_template = \"in {project} ({scheme}, {configuration})\"\n",
+        );
+        let document = sandbox().join("Schemes/App.xcodeproj");
+        let schemes = document.join("xcshareddata/xcschemes");
+        fs::create_dir_all(&schemes).unwrap();
+        fs::write(
+            schemes.join("App Beta.xcscheme"),
+            "<Scheme>\n<LaunchAction\n   buildConfiguration = \"Beta\">\n</LaunchAction>\n</Scheme>",
+        )
+        .unwrap();
+        let editing = Scene {
+            project: Some(ProjectInfo {
+                name: "App".to_string(),
+                path: Some(document),
+                kind: ProjectKind::Project,
+            }),
+            scheme: Some("App Beta".to_string()),
+            ..Scene::editing("App", "main.swift")
+        };
+        let activities = run(&config, vec![Some(editing), None, None]);
+        assert_eq!(
+            activities,
+            vec![
+                activity(
+                    "Working on main.swift",
+                    "in App (App Beta, Beta)",
+                    ("Swift", "swift")
+                ),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn shows_xcode_version_as_small_text() {
+        let config = config("language = \"en\"\nproject_type_icon = true\n");
+        let editing = Scene {
+            version: Some("Xcode 16.2 beta".to_string()),
+            ..Scene::editing("Demo", "main.swift")
+        };
+        let activities = run(&config, vec![Some(editing), None, None]);
+        let mut expected = activity("Working on main.swift", "in Demo", ("Swift", "swift"));
+        expected["assets"]["small_text"] = json!("Xcode 16.2 beta");
+        expected["assets"]["small_image"] = json!("xcode");
+        assert_eq!(activities, vec![expected, Value::Null]);
+    }
+
+    #[test]
+    fn shows_project_type_from_root() {
+        let config = config(
+            "language = \"en\"\nproject_type_icon = true\n\
+             details_template = \"Working on {file} ({project_type})\"\n",
+        );
+        let editing = in_root("Podded", "main.swift", &[("Podfile", "")]);
+        let activities = run(&config, vec![Some(editing), None, None]);
+        let mut expected = activity(
+            "Working on main.swift (CocoaPods)",
+            "in Podded",
+            ("Swift", "swift"),
+        );
+        expected["assets"]["small_text"] = json!("CocoaPods");
+        expected["assets"]["small_image"] = json!("cocoapods");
+        assert_eq!(activities, vec![expected, Value::Null]);
+    }
+
+    #[test]
+    fn shows_number_of_open_files() {
+        let config =
+            config("language = \"en\"\ndetails_template = \"Juggling {open_files} files\"\n");
+        let juggling = Scene {
+            open_files: 14,
+            ..Scene::editing("Juggler", "main.swift")
+        };
+        let activities = run(&config, vec![Some(juggling), None, None]);
+        assert_eq!(
+            activities,
+            vec![
+                activity("Juggling 14 files", "in Juggler", ("Swift", "swift")),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn follows_project_of_frontmost_window() {
+        let config = config("language = \"en\"\nshow_other_projects = true\n");
+        let front = |project| Scene {
+            open_projects: 3,
+            ..Scene::editing(project, "main.swift")
+        };
+        let activities = run(
+            &config,
+            vec![
+                Some(front("Windowed")),
+                Some(front("Background")),
+                None,
+                None,
+            ],
+        );
+        let states = activities
+            .iter()
+            .map(|activity| activity["state"].as_str().unwrap_or_default())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            states,
+            [
+                "in Windowed and 2 other projects",
+                "in Background and 2 other projects",
+                "",
+            ]
+        );
+    }
+
+    #[test]
+    fn shows_names_parsed_from_osascript_output() {
+        let config = config("language = \"en\"\n");
+        // The project name contains the title separator, the file name a
+        // byte that is not UTF-8, and the output is missing the project path
+        let project = String::from_utf8_lossy(
+            b"Caf\xc3\xa9 \xe2\x80\x94 Tools.xcworkspace\x1fmissing value\r\n",
+        );
+        let window = String::from_utf8_lossy(
+            b"Caf\xc3\xa9 \xe2\x80\x94 Tools.xcworkspace\x1f\
+              Caf\xc3\xa9 \xe2\x80\x94 Tools \xe2\x80\x94 Bad\xffName.swift\x1f3\x1f\n",
+        );
+        let [name, path] = split_fields(strip_output(&project));
+        let window = parse_front_window(strip_output(&window));
+        let scene = Scene {
+            project: parse_project(name, path),
+            file: window.file,
+            unsaved: window.unsaved,
+            open_files: window.open_files,
+            ..Scene::editing("", "")
+        };
+        let activities = run(&config, vec![Some(scene), None, None]);
+        assert_eq!(
+            activities,
+            vec![
+                activity(
+                    "Working on Bad\u{fffd}Name.swift",
+                    "in Café — Tools",
+                    ("Swift", "swift")
+                ),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn shows_swift_version() {
+        let config = config(
+            "language = \"en\"\ndetails_template = \"Working on {file} with {swift_version}\"\n",
+        );
+        let editing = Scene {
+            swift_version: Some("Swift 6.0".to_string()),
+            ..Scene::editing("Toolchain", "main.swift")
+        };
+        let activities = run(&config, vec![Some(editing), None, None]);
+        assert_eq!(
+            activities,
+            vec![
+                activity(
+                    "Working on main.swift with Swift 6.0",
+                    "in Toolchain",
+                    ("Swift", "swift")
+                ),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn hides_files_matching_globs() {
+        let config = config("language = \"en\"\nhidden_files = [\"*Secrets*\"]\n");
+        let activities = run(
+            &config,
+            vec![
+                Some(Scene::editing("Vault", "AppSecrets.swift")),
+                Some(Scene::editing("Vault", "main.swift")),
+                None,
+                None,
+            ],
+        );
+        assert_eq!(
+            activities,
+            vec![
+                activity("Working on a file", "in Vault", ("Xcode", "xcode")),
+                activity("Working on main.swift", "in Vault", ("Swift", "swift")),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn suppresses_projects_with_ignore_marker() {
+        let config = config("language = \"en\"\n");
+        let root = sandbox().join("Confidential");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join(".discord-ignore"), "").unwrap();
+        let confidential = Scene {
+            project: Some(ProjectInfo {
+                name: "Confidential".to_string(),
+                path: Some(root.join("Confidential.xcodeproj")),
+                kind: ProjectKind::Project,
+            }),
+            ..Scene::editing("Confidential", "main.swift")
+        };
+        let activities = run(
+            &config,
+            vec![
+                Some(confidential),
+                Some(Scene::editing("Public", "main.swift")),
+                None,
+                None,
+            ],
+        );
+        // The elapsed time restarts when the other project opens
+        let mut public = activity("Working on main.swift", "in Public", ("Swift", "swift"));
+        public["timestamps"]["start"] = json!(1_700_000_003_000_i64);
+        assert_eq!(activities, vec![Value::Null, public, Value::Null]);
+    }
+
+    #[test]
+    fn pseudonymizes_projects() {
+        let config = config("language = \"en\"\npseudonymize = true\n");
+        let activities = run(
+            &config,
+            vec![Some(Scene::editing("MyApp", "main.swift")), None, None],
+        );
+        assert_eq!(
+            activities,
+            vec![
+                activity(
+                    "Working on main.swift",
+                    "in Project Obsidian",
+                    ("Swift", "swift")
+                ),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn renders_large_text_template() {
+        let templated =
+            config("language = \"en\"\nlarge_text_template = \"{language} in {project}\"\n");
+        let activities = run(
+            &templated,
+            vec![Some(Scene::editing("Hover", "main.swift")), None, None],
+        );
+        assert_eq!(
+            activities,
+            vec![
+                activity(
+                    "Working on main.swift",
+                    "in Hover",
+                    ("Swift in Hover", "swift")
+                ),
+                Value::Null,
+            ]
+        );
+
+        let blank = config("language = \"en\"\nlarge_text_template = \"\"\n");
+        let activities = run(
+            &blank,
+            vec![Some(Scene::editing("Hover", "main.swift")), None, None],
+        );
+        let mut expected = activity("Working on main.swift", "in Hover", ("", "swift"));
+        expected["assets"] = json!({ "large_image": "swift" });
+        assert_eq!(activities, vec![expected, Value::Null]);
+    }
+
+    #[test]
+    fn reads_idle_threshold_of_project() {
+        let config =
+            config("language = \"en\"\nrequire_frontmost = true\nidle_threshold = \"5s\"\n");
+        let root = sandbox().join("LongBuilds");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join(".xcode-discord-rpc.toml"),
+            "idle_threshold = \"1h\"\n",
+        )
+        .unwrap();
+        let editing = Scene {
+            project: Some(ProjectInfo {
+                name: "LongBuilds".to_string(),
+                path: Some(root.join("LongBuilds.xcodeproj")),
+                kind: ProjectKind::Project,
+            }),
+            ..Scene::editing("LongBuilds", "main.swift")
+        };
+        let away = Scene {
+            frontmost: false,
+            ..editing.clone()
+        };
+        let activities = run(
+            &config,
+            vec![Some(editing), Some(away.clone()), Some(away), None, None],
+        );
+        assert_eq!(
+            activities,
+            vec![
+                activity("Working on main.swift", "in LongBuilds", ("Swift", "swift")),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn stays_active_while_building() {
+        let config =
+            config("language = \"en\"\nrequire_frontmost = true\nidle_threshold = \"5s\"\n");
+        let build_db =
+            derived_data("Waiting").join("Build/Intermediates.noindex/XCBuildData/build.db");
+        fs::create_dir_all(build_db.parent().unwrap()).unwrap();
+        fs::write(&build_db, "").unwrap();
+        let editing = Scene::editing("Waiting", "main.swift");
+        let away = Scene {
+            frontmost: false,
+            ..editing.clone()
+        };
+        let activities = run(
+            &config,
+            vec![Some(editing), Some(away.clone()), Some(away), None, None],
+        );
+        assert_eq!(
+            activities,
+            vec![
+                activity("Building…", "in Waiting", ("Swift", "swift")),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn shows_command_line_builds_in_project() {
+        let config = config("language = \"en\"\ndetect_cli_builds = true\n");
+        let editing = in_root("Scripted", "main.swift", &[]);
+        let xcodebuild = sandbox().join("bin/xcodebuild");
+        fs::create_dir_all(xcodebuild.parent().unwrap()).unwrap();
+        fs::copy("/bin/sleep", &xcodebuild).unwrap();
+        let mut build = Command::new(&xcodebuild)
+            .arg("30")
+            .current_dir(sandbox().join("Scripted"))
+            .spawn()
+            .unwrap();
+        let activities = run(&config, vec![Some(editing), None, None]);
+        build.kill().unwrap();
+        build.wait().unwrap();
+        assert_eq!(activities[0]["details"], "Building from CLI");
+        assert_eq!(activities[0]["state"], "in Scripted");
+    }
+
+    #[test]
+    fn shows_open_pull_request_of_branch() {
+        let github = FakeHttp::start(r#"[{"number":42}]"#);
+        let config = config(&format!(
+            "language = \"en\"\nrotation = [\"pull_request\"]\nrepository_button = false\n\
+             github_api_url = \"{}\"\n",
+            github.url()
+        ));
+        let editing = in_root("Reviewed", "main.swift", &[]);
+        git("Reviewed", &["init", "-q", "-b", "feature/login"]);
+        git(
+            "Reviewed",
+            &["commit", "-q", "--allow-empty", "-m", "Start"],
+        );
+        git(
+            "Reviewed",
+            &["remote", "add", "origin", "git@github.com:octo/app.git"],
+        );
+        let activities = run(&config, vec![Some(editing), None, None]);
+        assert_eq!(
+            activities,
+            vec![
+                activity("PR #42 open", "in Reviewed", ("Swift", "swift")),
+                Value::Null,
+            ]
+        );
+        let requests = github.stop();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].path,
+            "/repos/octo/app/pulls?state=open&head=octo%3Afeature%2Flogin"
+        );
+    }
+
+    #[test]
+    fn prefers_keychain_github_token_over_configured() {
+        let github = FakeHttp::start("[]");
+        let editing = in_root("Keyed", "main.swift", &[]);
+        git("Keyed", &["init", "-q"]);
+        git("Keyed", &["commit", "-q", "--allow-empty", "-m", "Start"]);
+        git(
+            "Keyed",
+            &["remote", "add", "origin", "https://github.com/octo/keyed"],
+        );
+        let overrides = format!(
+            "language = \"en\"\nrotation = [\"pull_request\"]\nrepository_button = false\n\
+             github_api_url = \"{}\"\n",
+            github.url()
+        );
+        run(&config(&overrides), vec![Some(editing.clone()), None, None]);
+        let overrides = format!("{overrides}github_token = \"configured\"\n");
+        run(&config(&overrides), vec![Some(editing), None, None]);
+        let tokens = github
+            .stop()
+            .iter()
+            .map(|request| {
+                request
+                    .headers
+                    .iter()
+                    .find(|header| header.starts_with("Authorization: "))
+                    .cloned()
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(tokens, ["Authorization: Bearer github-token"; 2]);
+    }
+
+    #[test]
+    fn shows_uncommitted_changes() {
+        let config = config("language = \"en\"\nrotation = [\"changes\"]\n");
+        let editing = in_root(
+            "Changing",
+            "main.swift",
+            &[("main.swift", ""), ("App.swift", "")],
+        );
+        git("Changing", &["init", "-q"]);
+        let activities = run(&config, vec![Some(editing), None, None]);
+        assert_eq!(
+            activities,
+            vec![
+                activity("● 2 files changed", "in Changing", ("Swift", "swift")),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn shows_hosted_icon_of_project() {
+        let icons = FakeHttp::start("");
+        let found = config(&format!(
+            "language = \"en\"\nicon_url_template = \"{}/{{project}}.png\"\n",
+            icons.url()
+        ));
+        let activities = run(
+            &found,
+            vec![Some(Scene::editing("Icon App", "main.swift")), None, None],
+        );
+        let icon = format!("{}/Icon%20App.png", icons.url());
+        assert_eq!(
+            activities,
+            vec![
+                activity("Working on main.swift", "in Icon App", ("Swift", &icon)),
+                Value::Null,
+            ]
+        );
+        let requests = icons.stop();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "HEAD");
+        // Nothing listens on port 1, so the language image is shown
+        let missing =
+            config("language = \"en\"\nicon_url_template = \"http://127.0.0.1:1/{project}.png\"\n");
+        let activities = run(
+            &missing,
+            vec![Some(Scene::editing("Icon App", "main.swift")), None, None],
+        );
+        assert_eq!(
+            activities,
+            vec![
+                activity("Working on main.swift", "in Icon App", ("Swift", "swift")),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn does_not_look_up_icons_of_hidden_projects() {
+        let icon_host = std::net::TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        icon_host.set_nonblocking(true).unwrap();
+        let config = config(&format!(
+            "language = \"en\"\npseudonymize = true\n\
+             icon_url_template = \"http://127.0.0.1:{}/{{project}}.png\"\n",
+            icon_host.local_addr().unwrap().port()
+        ));
+        let activities = run(
+            &config,
+            vec![Some(Scene::editing("MyApp", "main.swift")), None, None],
+        );
+        assert_eq!(
+            activities,
+            vec![
+                activity(
+                    "Working on main.swift",
+                    "in Project Obsidian",
+                    ("Swift", "swift")
+                ),
+                Value::Null,
+            ]
+        );
+        assert!(icon_host.accept().is_err());
+    }
+
+    #[test]
+    fn forgives_peeks_within_frontmost_grace() {
+        let config = config(
+            "language = \"en\"\nrequire_frontmost = true\nidle_threshold = \"5s\"\n\
+             frontmost_grace = \"1m\"\n",
+        );
+        let editing = Scene::editing("Peeking", "main.swift");
+        let away = Scene {
+            frontmost: false,
+            ..editing.clone()
+        };
+        // Peeks add up to more than the threshold, but each is forgiven
+        let mut script = Vec::new();
+        for _ in 0..4 {
+            script.extend([Some(editing.clone()), Some(away.clone())]);
+        }
+        // A long absence still turns idle after the threshold alone
+        script.extend([
+            Some(editing),
+            Some(away.clone()),
+            Some(away.clone()),
+            Some(away),
+        ]);
+        let activities = run(&config, script);
+        assert_eq!(
+            activities,
+            vec![
+                activity("Working on main.swift", "in Peeking", ("Swift", "swift")),
+                activity("Idle", "Idle", ("Xcode", "xcode")),
+            ]
+        );
+    }
+
+    #[test]
+    fn summarizes_switching_between_files() {
+        let config = config("language = \"en\"\nfile_switch_threshold = 3\n");
+        let activities = run(
+            &config,
+            vec![
+                Some(Scene::editing("Bouncing", "A.swift")),
+                Some(Scene::editing("Bouncing", "B.swift")),
+                Some(Scene::editing("Bouncing", "C.swift")),
+                None,
+                None,
+            ],
+        );
+        assert_eq!(
+            activities,
+            vec![
+                activity("Working on A.swift", "in Bouncing", ("Swift", "swift")),
+                activity("Working on B.swift", "in Bouncing", ("Swift", "swift")),
+                activity(
+                    "Switching between 3 files",
+                    "in Bouncing",
+                    ("Swift", "swift")
+                ),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn applies_overrides_of_project() {
+        let config = config("language = \"en\"\n");
+        let client = in_root(
+            "Client",
+            "main.swift",
+            &[(
+                ".xcode-discord-rpc.toml",
+                "hide_project = true\ndetails_template = \"Hacking on {file}\"\n\
+                 buttons = [{ label = \"Portfolio\", url = \"https://example.com\" }]\n",
+            )],
+        );
+        let activities = run(
+            &config,
+            vec![
+                Some(client),
+                Some(in_root("Personal", "main.swift", &[])),
+                None,
+                None,
+            ],
+        );
+        let mut client = activity("Hacking on main.swift", "in a Project", ("Swift", "swift"));
+        client["buttons"] = json!([{ "label": "Portfolio", "url": "https://example.com" }]);
+        // The elapsed time restarts when the other project opens
+        let mut personal = activity("Working on main.swift", "in Personal", ("Swift", "swift"));
+        personal["timestamps"]["start"] = json!(1_700_000_003_000_i64);
+        assert_eq!(activities, vec![client, personal, Value::Null]);
+    }
+
+    #[test]
+    fn shows_assets_and_buttons_of_project() {
+        let config = config("language = \"en\"\n");
+        let assets = "large_image = \"https://example.com/icon.png\"\nlarge_text = \"Open App\"\n\
+                      buttons = [{ label = \"Get it on TestFlight\", url = \"https://testflight.apple.com/join/x\" }]\n";
+        let open = in_root(
+            "OpenSource",
+            "main.swift",
+            &[(".xcode-discord-rpc.toml", assets)],
+        );
+        let closed = in_root(
+            "ClosedSource",
+            "main.swift",
+            &[(
+                ".xcode-discord-rpc.toml",
+                &format!("hide_project = true\n{assets}"),
+            )],
+        );
+        let activities = run(&config, vec![Some(open), Some(closed), None, None]);
+        let mut open = activity(
+            "Working on main.swift",
+            "in OpenSource",
+            ("Open App", "https://example.com/icon.png"),
+        );
+        open["buttons"] = json!([{
+            "label": "Get it on TestFlight",
+            "url": "https://testflight.apple.com/join/x",
+        }]);
+        // Hidden projects keep the language image, which gives nothing away
+        let mut closed = activity("Working on main.swift", "in a Project", ("Swift", "swift"));
+        closed["timestamps"]["start"] = json!(1_700_000_003_000_i64);
+        closed["buttons"] = open["buttons"].clone();
+        assert_eq!(activities, vec![open, closed, Value::Null]);
+    }
+
+    #[test]
+    fn shows_alias_of_project() {
+        let config = config(
+            "language = \"en\"\n[project_aliases]\ninternal-codename-xyz = \"My Indie App\"\n",
+        );
+        let activities = run(
+            &config,
+            vec![
+                Some(Scene::editing("internal-codename-xyz", "main.swift")),
+                None,
+                None,
+            ],
+        );
+        assert_eq!(
+            activities,
+            vec![
+                activity(
+                    "Working on main.swift",
+                    "in My Indie App",
+                    ("Swift", "swift")
+                ),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn suppresses_presence_outside_of_schedule() {
+        // The window opens a minute after the simulated clock starts
+        let time = |offset: i64| {
+            Local
+                .timestamp_opt(SimulatedClock::EPOCH + offset, 0)
+                .unwrap()
+                .format("%H:%M")
+                .to_string()
+        };
+        let config = config(&format!(
+            "language = \"en\"\n[schedule]\nstart = \"{}\"\nend = \"{}\"\n",
+            time(60),
+            time(3660)
+        ));
+        let editing = Scene::editing("Overtime", "main.swift");
+        let script = vec![Some(editing.clone()), Some(editing), None];
+        let activities = run(&config, script.clone());
+        assert!(!activities.is_empty());
+        assert!(activities.iter().all(Value::is_null), "{activities:?}");
+
+        let clock = SimulatedClock::new();
+        clock.advance(Duration::from_secs(120));
+        let activities = run_with_clock(&config, SharedControlState::default(), clock, script);
+        assert!(activities
+            .iter()
+            .any(|activity| activity["details"] == "Working on main.swift"));
+    }
+
+    #[test]
+    fn applies_behavior_of_focus_mode() {
+        let config =
+            config("language = \"en\"\n[focus_modes]\nWork = \"hidden\"\nPersonal = \"off\"\n");
+        let database = sandbox().join("Library/DoNotDisturb/DB");
+        fs::create_dir_all(&database).unwrap();
+        let assertions = json!({ "data": [{ "storeAssertionRecords": [{
+            "assertionDetails": { "assertionDetailsModeIdentifier": "com.apple.focus.work" },
+        }] }] });
+        let configurations = json!({ "data": [{ "modeConfigurations": {
+            "com.apple.focus.work": { "mode": { "name": "Work" } },
+        } }] });
+        fs::write(database.join("Assertions.json"), assertions.to_string()).unwrap();
+        fs::write(
+            database.join("ModeConfigurations.json"),
+            configurations.to_string(),
+        )
+        .unwrap();
+        let activities = run(
+            &config,
+            vec![Some(Scene::editing("Client", "main.swift")), None, None],
+        );
+        assert_eq!(
+            activities,
+            vec![
+                activity("Working on a file", "in a Project", ("Xcode", "xcode")),
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn slows_down_on_battery() {
+        let config =
+            config("language = \"en\"\nrequire_frontmost = true\nidle_threshold = \"5s\"\n");
+        let plugged = Scene::editing("Unplugged", "main.swift");
+        let on_battery = Scene {
+            power_saving: true,
+            ..plugged.clone()
+        };
+        let run_for = |scene: &Scene| {
+            let clock = SimulatedClock::new();
+            let activities = run_with_clock(
+                &config,
+                SharedControlState::default(),
+                clock.clone(),
+                vec![Some(scene.clone()); 4],
+            );
+            (activities, clock.now() - SimulatedClock::EPOCH)
+        };
+        let (_, plugged_elapsed) = run_for(&plugged);
+        // Xcode being in the background goes unnoticed on battery
+        let (activities, elapsed) = run_for(&Scene {
+            frontmost: false,
+            ..on_battery
+        });
+        assert_eq!(
+            activities,
+            vec![activity(
+                "Working on main.swift",
+                "in Unplugged",
+                ("Swift", "swift")
+            )]
+        );
+        assert_eq!(elapsed, 2 * plugged_elapsed);
+    }
+
+    #[test]
+    fn counts_time_worked_today() {
+        let config = config("language = \"en\"\ndetails_template = \"{today} today\"\n");
+        let clock = SimulatedClock::new();
+        // A day of its own, as other tests add to the shared statistics
+        clock.advance(Duration::from_secs(1000 * 24 * 60 * 60));
+        let editing = Scene::editing("Counted", "main.swift");
+        let mut script = vec![Some(editing); 25];
+        script.extend([None, None]);
+        let activities = run_with_clock(&config, SharedControlState::default(), clock, script);
+        let details = activities
+            .iter()
+            .map(|activity| activity["details"].as_str().unwrap_or_default())
+            .collect::<Vec<_>>();
+        assert_eq!(details, vec!["0m today", "1m today", ""]);
+    }
+
+    #[test]
+    fn shows_progress_toward_daily_goal() {
+        let config =
+            config("language = \"en\"\ndaily_goal = \"5h\"\nstate_template = \"{goal}\"\n");
+        let clock = SimulatedClock::new();
+        // A day of its own, as other tests add to the shared statistics
+        clock.advance(Duration::from_secs(3000 * 24 * 60 * 60));
+        let mut script = vec![Some(Scene::editing("Goals", "main.swift")); 62];
+        script.extend([None, None]);
+        let activities = run_with_clock(&config, SharedControlState::default(), clock, script);
+        let states = activities
+            .iter()
+            .map(|activity| activity["state"].as_str().unwrap_or_default())
+            .collect::<Vec<_>>();
+        assert_eq!(states, ["0h / 5h today", "0.1h / 5h today", ""]);
+    }
+
+    #[test]
+    fn exports_recorded_sessions() {
+        let config = config("language = \"en\"\n");
+        let script = vec![
+            Some(Scene::editing("Exported", "main.swift")),
+            Some(Scene::editing("Exported", "main.swift")),
+            Some(Scene::editing("Exported", "App.swift")),
+            None,
+            None,
+        ];
+        let discord = FakeDiscord::start();
+        let mut client = DiscordIpcClient::new(&config.application_id).unwrap();
+        let mut state = XcodeState::new(
+            &config,
+            &mut client,
+            SharedControlState::default(),
+            Box::new(SimulatedClock::new()),
+            Box::new(ScriptedXcode::new(script)),
+        );
+        assert!(state.run().is_err());
+        // Sessions of other tests may have been loaded before this one
+        let sessions = state.stats.sessions();
+        let csv = crate::export::sessions_to_csv(&sessions[sessions.len() - 1..]);
+        drop(state);
+        drop(client);
+        discord.stop();
+        let row = csv.lines().nth(1).unwrap().split(',').collect::<Vec<_>>();
+        assert_eq!(row[2..], ["Exported", "2", "Swift=6", "0"]);
+    }
+
+    #[cfg(feature = "activitywatch")]
+    #[test]
+    fn sends_heartbeats_to_activitywatch() {
+        let activitywatch = FakeHttp::start("{}");
+        let config = config(&format!(
+            "language = \"en\"\n[activitywatch]\nurl = \"{}/\"\n",
+            activitywatch.url()
+        ));
+        run(
+            &config,
+            vec![
+                Some(Scene::editing("Watched", "main.swift")),
+                Some(Scene::editing("Watched", "App.swift")),
+                None,
+                None,
+            ],
+        );
+        let requests = activitywatch.stop();
+        let bucket = format!(
+            "/api/0/buckets/aw-watcher-xcode_{}",
+            crate::integrations::activitywatch::hostname()
+        );
+        let paths = requests
+            .iter()
+            .map(|request| request.path.as_str())
+            .collect::<Vec<_>>();
+        let heartbeat = format!("{bucket}/heartbeat?pulsetime=30");
+        assert_eq!(paths, [bucket.as_str(), &heartbeat, &heartbeat]);
+        let files = requests[1..]
+            .iter()
+            .map(|request| serde_json::from_str::<Value>(&request.body).unwrap()["data"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            files,
+            [
+                json!({ "project": "Watched", "file": "main.swift", "language": "Swift" }),
+                json!({ "project": "Watched", "file": "App.swift", "language": "Swift" }),
+            ]
+        );
+    }
+
+    #[cfg(feature = "slack")]
+    #[test]
+    fn mirrors_session_to_slack_status() {
+        let slack = FakeHttp::start(r#"{"ok":true}"#);
+        let config = config(&format!(
+            "language = \"en\"\n[slack]\napi_url = \"{}\"\n",
+            slack.url()
+        ));
+        run(
+            &config,
+            vec![
+                Some(Scene::editing("Slacking", "main.swift")),
+                Some(Scene::editing("Slacking", "App.swift")),
+                None,
+                None,
+            ],
+        );
+        let requests = slack.stop();
+        assert!(requests.iter().all(|request| {
+            request.path == "/users.profile.set"
+                && request
+                    .headers
+                    .contains(&"Authorization: Bearer slack-token".to_string())
+        }));
+        let profiles = requests
+            .iter()
+            .map(|request| serde_json::from_str::<Value>(&request.body).unwrap()["profile"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            profiles,
+            [
+                json!({
+                    "status_text": "Working on Slacking",
+                    "status_emoji": ":hammer_and_wrench:",
+                    "status_expiration": 0,
+                }),
+                json!({ "status_text": "", "status_emoji": "", "status_expiration": 0 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_integrations_left_out_of_the_build() {
+        let hook = FakeHttp::start("");
+        let config = config(&format!(
+            "language = \"en\"\n[[webhooks]]\nurl = \"{}\"\n",
+            hook.url()
+        ));
+        let activities = run(
+            &config,
+            vec![Some(Scene::editing("Slim", "main.swift")), None, None],
+        );
+        assert_eq!(
+            activities,
+            vec![
+                activity("Working on main.swift", "in Slim", ("Swift", "swift")),
+                Value::Null,
+            ]
+        );
+        // Built with `--no-default-features`, the webhook is ignored
+        assert_eq!(hook.stop().is_empty(), !cfg!(feature = "webhooks"));
+    }
+
+    #[cfg(feature = "webhooks")]
+    #[test]
+    fn posts_session_events_to_webhooks() {
+        let hook = FakeHttp::start("");
+        let config = config(&format!(
+            "language = \"en\"\n[[webhooks]]\nurl = \"{0}/all\"\n\
+             [[webhooks]]\nurl = \"{0}/switch\"\nevents = [\"project_switch\"]\n",
+            hook.url()
+        ));
+        run(
+            &config,
+            vec![
+                Some(Scene::editing("Hooked", "main.swift")),
+                Some(Scene::editing("Unhooked", "App.swift")),
+                None,
+                None,
+            ],
+        );
+        let events = hook
+            .stop()
+            .iter()
+            .map(|request| {
+                let payload = serde_json::from_str::<Value>(&request.body).unwrap();
+                format!(
+                    "{} {} {}",
+                    request.path, payload["event"], payload["project"]
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            [
+                r#"/all "session_start" "Hooked""#,
+                r#"/all "session_end" "Hooked""#,
+                r#"/all "project_switch" "Unhooked""#,
+                r#"/switch "project_switch" "Unhooked""#,
+                r#"/all "session_start" "Unhooked""#,
+                r#"/all "session_end" "Unhooked""#,
+            ]
+        );
+    }
+
+    #[cfg(feature = "mqtt")]
+    #[test]
+    fn publishes_presence_to_mqtt() {
+        let config =
+            config("language = \"en\"\n[mqtt]\ntopic = \"Published\"\nusername = \"light\"\n");
+        let editing = Scene::editing("Published", "main.swift");
+        run(
+            &config,
+            vec![Some(editing.clone()), Some(editing), None, None],
+        );
+        let messages = fs::read_to_string(sandbox().join("Published.mqtt")).unwrap();
+        let messages = messages
+            .lines()
+            .map(|message| serde_json::from_str::<Value>(message).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            [
+                json!({
+                    "project": "Published",
+                    "display_project": "Published",
+                    "file": "main.swift",
+                    "language": "Swift",
+                    "details": "Working on main.swift",
+                    "state": "in Published",
+                    "started_at": SimulatedClock::EPOCH,
+                    "active": true,
+                }),
+                json!({ "active": false }),
+            ]
+        );
+        // The password is read from an options file instead of the arguments
+        let options = fs::read_to_string(sandbox().join("Published.options")).unwrap();
+        assert_eq!(options, "-u light\n-P mqtt-token\n".repeat(2));
+        let args = fs::read_to_string(sandbox().join("Published.args")).unwrap();
+        assert!(!args.contains("mqtt-token"));
+    }
+
+    #[cfg(feature = "overlay")]
+    #[test]
+    fn writes_presence_to_overlay_file() {
+        let config =
+            config("language = \"en\"\n[overlay]\npath = \"~/Overlay.json\"\nformat = \"json\"\n");
+        let overlay = || {
+            let mut overlay: Value =
+                serde_json::from_str(&fs::read_to_string(sandbox().join("Overlay.json")).unwrap())
+                    .unwrap();
+            // The elapsed time is measured on the wall clock
+            overlay.as_object_mut().unwrap().remove("elapsed");
+            overlay
+        };
+        let editing = Scene::editing("Overlaid", "main.swift");
+        let discord = FakeDiscord::start();
+        let mut client = DiscordIpcClient::new(&config.application_id).unwrap();
+        let mut state = XcodeState::new(
+            &config,
+            &mut client,
+            SharedControlState::default(),
+            Box::new(SimulatedClock::new()),
+            Box::new(ScriptedXcode::new([Some(editing.clone()), Some(editing)])),
+        );
+        assert!(state.run().is_err());
+        assert_eq!(
+            overlay(),
+            json!({
+                "active": true,
+                "details": "Working on main.swift",
+                "state": "in Overlaid",
+                "started_at": SimulatedClock::EPOCH,
+            })
+        );
+        drop(state);
+        drop(client);
+        discord.stop();
+        assert_eq!(overlay(), json!({ "active": false }));
+    }
+
+    #[test]
+    fn streams_presence_changes_as_json_lines() {
+        let config = config("language = \"en\"\n");
+        let path = sandbox().join("events.ndjson");
+        let discord = FakeDiscord::start();
+        let mut client = DiscordIpcClient::new(&config.application_id).unwrap();
+        let mut state = XcodeState::new(
+            &config,
+            &mut client,
+            SharedControlState::default(),
+            Box::new(SimulatedClock::new()),
+            Box::new(ScriptedXcode::new([
+                Some(Scene::editing("Streamed", "main.swift")),
+                Some(Scene::editing("Streamed", "main.swift")),
+                Some(Scene::editing("Streamed", "App.swift")),
+                Some(Scene::editing("Other", "App.swift")),
+                None,
+                None,
+            ])),
+        );
+        state
+            .integrations
+            .push(Box::new(crate::integrations::events::EventStream::new(
+                Box::new(fs::File::create(&path).unwrap()),
+            )));
+        assert!(state.run().is_err());
+        drop(state);
+        drop(client);
+        discord.stop();
+        let events = fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let event = serde_json::from_str::<Value>(line).unwrap();
+                format!("{} {} {}", event["event"], event["project"], event["file"])
+            })
+            .collect::<Vec<_>>();
+        // Updates that changed nothing are skipped
+        assert_eq!(
+            events,
+            [
+                r#""session_start" "Streamed" "main.swift""#,
+                r#""update" "Streamed" "main.swift""#,
+                r#""update" "Streamed" "App.swift""#,
+                r#""session_end" "Streamed" "App.swift""#,
+                r#""project_switch" "Other" "App.swift""#,
+                r#""session_start" "Other" "App.swift""#,
+                r#""update" "Other" "App.swift""#,
+                r#""session_end" "Other" "App.swift""#,
+            ]
+        );
+    }
+
+    #[test]
+    fn notifies_summary_when_session_ends() {
+        let config = config("language = \"en\"\nsession_summary = true\n");
+        let clock = SimulatedClock::new();
+        // A day of its own, as other tests add to the shared statistics
+        clock.advance(Duration::from_secs(2000 * 24 * 60 * 60));
+        let mut script = vec![Some(Scene::editing("Summarized", "main.swift")); 25];
+        script.extend([None, None]);
+        run_with_clock(&config, SharedControlState::default(), clock, script);
+        let scripts = fs::read_to_string(sandbox().join("osascript.log")).unwrap();
+        assert!(scripts.lines().any(|script| script
+            == r#"display notification "You worked 1m on Summarized today" with title "xcode-discord-rpc""#));
+    }
+
+    #[test]
+    fn restarts_session_after_long_system_sleep() {
+        let config = config("language = \"en\"\nwake_reset_threshold = \"15m\"\n");
+        let clock = SimulatedClock::new();
+        // The Mac sleeps during the first wait for the refresh
+        clock.sleep_system(3600);
+        let editing = Scene::editing("Sleepy", "main.swift");
+        let activities = run_with_clock(
+            &config,
+            SharedControlState::default(),
+            clock,
+            vec![Some(editing.clone()), Some(editing), None, None],
+        );
+        let mut woken = activity("Working on main.swift", "in Sleepy", ("Swift", "swift"));
+        woken["timestamps"]["start"] = json!((SimulatedClock::EPOCH + 3603) * 1000);
+        assert_eq!(
+            activities,
+            vec![
+                activity("Working on main.swift", "in Sleepy", ("Swift", "swift")),
+                woken,
+                Value::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn rotates_details_between_facts() {
+        let config = config(
+            "language = \"en\"\nrotation = [\"file\", \"language\", \"elapsed\"]\n\
+             rotation_interval = \"3s\"\n",
+        );
+        let editing = Scene::editing("Rotating", "main.swift");
+        let activities = run(&config, vec![Some(editing); 4]);
+        let details = activities
+            .iter()
+            .map(|activity| activity["details"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            details,
+            [
+                "Session: 0m",
+                "Working on main.swift",
+                "Writing Swift",
+                "Session: 0m"
+            ]
+        );
+    }
+
+    #[test]
+    fn truncates_long_names_between_graphemes() {
+        let config = config("language = \"en\"\n");
+        // Two characters per grapheme, so that an odd cut would split one
+        let project = "e\u{301}".repeat(100);
+        let file = format!("{}.swift", "🧑‍💻".repeat(50));
+        let activities = run(
+            &config,
+            vec![Some(Scene::editing(&project, &file)), None, None],
+        );
+        let details = activities[0]["details"].as_str().unwrap();
+        let state = activities[0]["state"].as_str().unwrap();
+        assert_eq!(state.chars().count(), 128);
+        assert_eq!(state, format!("in {}…", "e\u{301}".repeat(62)));
+        assert!(details.chars().count() <= 128);
+        assert_eq!(details, format!("Working on {}…", "🧑‍💻".repeat(38)));
+    }
+
+    #[test]
+    fn shows_committing_in_commit_sheet() {
+        let config = config("language = \"en\"\ndetect_source_control = true\n");
+        let committing = Scene {
+            commit_sheet: true,
+            ..Scene::editing("Committed", "main.swift")
+        };
+        let activities = run(&config, vec![Some(committing), None, None]);
+        let mut expected = activity(
+            "Committing changes to Committed",
+            "",
+            ("Source Control", "git"),
+        );
+        expected.as_object_mut().unwrap().remove("state");
+        assert_eq!(activities, vec![expected, Value::Null]);
+    }
+
+    #[test]
+    fn fills_elapsed_placeholder() {
+        let config = config("language = \"en\"\nstate_template = \"in {project} for {elapsed}\"\n");
+        let activities = run(
+            &config,
+            vec![Some(Scene::editing("Elapsed", "main.swift")), None, None],
+        );
+        assert_eq!(
+            activities,
+            vec![
+                activity(
+                    "Working on main.swift",
+                    "in Elapsed for 0m",
+                    ("Swift", "swift")
+                ),
+                Value::Null,
+            ]
+        );
+    }
+}
@@ -0,0 +1,114 @@
+//! Runs the binary in the background against a simulated Xcode
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+    thread,
+    time::{Duration, Instant},
+};
+
+const SCENARIO: &str = r#"
+[[step]]
+duration = "10m"
+project = "Daemon.xcodeproj"
+file = "main.swift"
+"#;
+
+/// Runs the binary with `args`, with the home, data and runtime directories
+/// pointing to `home`
+fn run(home: &Path, args: &[&str]) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_xcode-discord-rpc"));
+    command.args(args).current_dir(home);
+    for key in [
+        "HOME",
+        "XDG_CACHE_HOME",
+        "XDG_CONFIG_HOME",
+        "XDG_DATA_HOME",
+        "XDG_RUNTIME_DIR",
+    ] {
+        command.env(key, home);
+    }
+    command.output().expect("binary can be run")
+}
+
+/// Home directory of the daemon, which is stopped and the directory removed
+/// when dropped, also when an assertion fails
+struct Home {
+    dir: PathBuf,
+    /// PID of the daemon while it runs
+    pid: Option<libc::pid_t>,
+}
+
+impl Drop for Home {
+    fn drop(&mut self) {
+        if let Some(pid) = self.pid {
+            // SAFETY: `kill` has no memory effects
+            unsafe { libc::kill(pid, libc::SIGTERM) };
+        }
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Waits for `condition` for up to 10 seconds
+fn wait_for(mut condition: impl FnMut() -> bool) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while Instant::now() < deadline {
+        if condition() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    false
+}
+
+#[test]
+fn detaches_and_logs_to_file() {
+    let mut guard = Home {
+        dir: env::temp_dir().join(format!("xcode-discord-rpc-daemon-{}", std::process::id())),
+        pid: None,
+    };
+    let home = guard.dir.clone();
+    fs::create_dir_all(&home).unwrap();
+    fs::write(home.join("scenario.toml"), SCENARIO).unwrap();
+
+    let started = run(&home, &["--daemon", "--simulate", "scenario.toml"]);
+    assert!(started.status.success());
+    let message = String::from_utf8_lossy(&started.stdout).to_string();
+    let pid = message
+        .split("(PID ")
+        .nth(1)
+        .and_then(|rest| rest.split(')').next())
+        .expect("the PID of the daemon is printed")
+        .to_string();
+    guard.pid = Some(pid.parse().expect("the PID is a number"));
+    let log = PathBuf::from(
+        message
+            .trim_end()
+            .split("logging to ")
+            .nth(1)
+            .expect("the log file is printed"),
+    );
+    assert!(
+        wait_for(
+            || fs::read_to_string(&log).is_ok_and(|log| log.contains("Starting xcode-discord-rpc"))
+        ),
+        "the daemon logs to {}",
+        log.display()
+    );
+
+    // A second instance fails in the terminal instead of in the background
+    let second = run(&home, &["--daemon", "--simulate", "scenario.toml"]);
+    assert!(!second.status.success());
+    assert!(String::from_utf8_lossy(&second.stderr).contains(&format!("(PID {pid})")));
+
+    let status = run(&home, &["status"]);
+    assert!(String::from_utf8_lossy(&status.stdout).starts_with("Running"));
+    let stopped = run(&home, &["stop"]);
+    assert!(stopped.status.success());
+    assert!(String::from_utf8_lossy(&stopped.stdout).contains(&format!("(PID {pid})")));
+    assert!(wait_for(|| {
+        String::from_utf8_lossy(&run(&home, &["status"]).stdout).starts_with("Not running")
+    }));
+    guard.pid = None;
+}